@@ -6,10 +6,10 @@ async fn main() {
     dotenv::dotenv().unwrap();
 
     // Make the Spotify client
-    let client = Client::with_refresh(
-        ClientCredentials::from_env().unwrap(),
-        std::fs::read_to_string(".refresh_token").unwrap(),
-    );
+    let client =
+        Client::with_refresh_file(ClientCredentials::from_env().unwrap(), ".refresh_token")
+            .await
+            .unwrap();
 
     // Call the Spotify API to get the playing track
     let playing = client.player().get_playing_track(None).await.unwrap().data;
@@ -23,7 +23,8 @@ async fn main() {
             match item {
                 PlayingType::Track(track) => print!("the track {}", track.name),
                 PlayingType::Episode(ep) => print!("the episode {}", ep.name),
-                PlayingType::Ad(ad) => print!("the advert {}", ad.name),
+                PlayingType::Ad(Some(ad)) => print!("the advert {}", ad.name),
+                PlayingType::Ad(None) => print!("an advert"),
                 PlayingType::Unknown(item) => print!("an unknown track {}", item.name),
             }
             println!(".");
@@ -6,10 +6,10 @@ async fn main() {
     dotenv::dotenv().unwrap();
 
     // Make the Spotify client
-    let client = Client::with_refresh(
-        ClientCredentials::from_env().unwrap(),
-        std::fs::read_to_string(".refresh_token").unwrap(),
-    );
+    let client =
+        Client::with_refresh_file(ClientCredentials::from_env().unwrap(), ".refresh_token")
+            .await
+            .unwrap();
 
     let recent = client
         .player()
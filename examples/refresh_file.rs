@@ -12,36 +12,15 @@ async fn main() {
     let client = Client::new(ClientCredentials::from_env().unwrap());
 
     // Get the URL to send the user to, requesting all the scopes and redirecting to a non-existant website.
-    let (url, state) = aspotify::authorization_url(
+    let request = aspotify::authorization_request(
         &client.credentials.id,
-        [
-            Scope::UgcImageUpload,
-            Scope::UserReadPlaybackState,
-            Scope::UserModifyPlaybackState,
-            Scope::UserReadCurrentlyPlaying,
-            Scope::Streaming,
-            Scope::AppRemoteControl,
-            Scope::UserReadEmail,
-            Scope::UserReadPrivate,
-            Scope::PlaylistReadCollaborative,
-            Scope::PlaylistModifyPublic,
-            Scope::PlaylistReadPrivate,
-            Scope::PlaylistModifyPrivate,
-            Scope::UserLibraryModify,
-            Scope::UserLibraryRead,
-            Scope::UserTopRead,
-            Scope::UserReadRecentlyPlayed,
-            Scope::UserFollowRead,
-            Scope::UserFollowModify,
-        ]
-        .iter()
-        .copied(),
+        Scope::all().iter().copied(),
         false,
         "http://non.existant/",
     );
 
     // Get the user to authorize our application.
-    println!("Go to this website: {}", url);
+    println!("Go to this website: {}", request.url);
 
     // Receive the URL that was redirected to.
     print!("Enter the URL that you were redirected to: ");
@@ -50,7 +29,7 @@ async fn main() {
     io::stdin().read_line(&mut redirect).unwrap();
 
     // Create the refresh token from the redirected URL.
-    client.redirected(&redirect, &state).await.unwrap();
+    client.redirected(&redirect, &request.state).await.unwrap();
 
     // Put the refresh token in a file.
     fs::write(".refresh_token", client.refresh_token().await.unwrap()).unwrap();
@@ -0,0 +1,31 @@
+use aspotify::{Client, ClientCredentials, PlayingType};
+
+#[tokio::main]
+async fn main() {
+    // Read the client credentials from the .env file
+    dotenv::dotenv().unwrap();
+
+    // Make the Spotify client
+    let client =
+        Client::with_refresh_file(ClientCredentials::from_env().unwrap(), ".refresh_token")
+            .await
+            .unwrap();
+
+    // Make a remote control for the user's active device
+    let remote = client.remote();
+
+    // Play an album and turn the volume up a little
+    remote.play_album("03JPFQvZRnHHysSZrSFmKY").await.unwrap();
+    match remote.volume_up(5).await.unwrap() {
+        Some(volume) => println!("Turned the volume up to {}.", volume),
+        None => println!("The volume of the device isn't known."),
+    }
+
+    // Print out what is playing
+    match remote.now_playing().await.unwrap() {
+        Some(PlayingType::Track(track)) => println!("Now playing the track {}.", track.name),
+        Some(PlayingType::Episode(ep)) => println!("Now playing the episode {}.", ep.name),
+        Some(_) => println!("Now playing something else."),
+        None => println!("Nothing is playing."),
+    }
+}
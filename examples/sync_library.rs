@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use aspotify::{Client, ClientCredentials, HttpClientBuilder};
+use futures_util::future;
+
+#[tokio::main]
+async fn main() {
+    // Read the client credentials from the .env file
+    dotenv::dotenv().unwrap();
+
+    // Make the Spotify client, keeping more idle connections alive than the default so that the
+    // concurrent requests below reuse them
+    let mut client =
+        Client::with_refresh_file(ClientCredentials::from_env().unwrap(), ".refresh_token")
+            .await
+            .unwrap();
+    client.set_http_client(
+        HttpClientBuilder::new()
+            .pool_idle_timeout(Duration::from_secs(300))
+            .pool_max_idle_per_host(16)
+            .build()
+            .unwrap(),
+    );
+
+    let start = Instant::now();
+
+    // Get the first page to find out how many saved tracks there are, then get the rest
+    // concurrently
+    let library = client.library();
+    let first = library.get_saved_tracks(50, 0, None).await.unwrap().data;
+    let rest = future::try_join_all(
+        (50..first.total)
+            .step_by(50)
+            .map(|offset| library.get_saved_tracks(50, offset, None)),
+    )
+    .await
+    .unwrap();
+
+    let tracks = first.items.len() + rest.iter().map(|page| page.data.items.len()).sum::<usize>();
+
+    // Print the results
+    println!(
+        "Synced {} saved tracks in {} requests in {:?}",
+        tracks,
+        rest.len() + 1,
+        start.elapsed()
+    );
+}
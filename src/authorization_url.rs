@@ -1,6 +1,16 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Mutex;
+
 use itertools::Itertools;
 use url::Url;
 
+use crate::{util, ParseEnumError};
+
 /// A scope that the user can grant access to.
 ///
 /// [Reference](https://developer.spotify.com/documentation/general/guides/scopes/).
@@ -29,12 +39,98 @@ pub enum Scope {
 }
 
 impl Scope {
+    /// Every scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let request = aspotify::authorization_request(
+    ///     "client id",
+    ///     aspotify::Scope::all().iter().copied(),
+    ///     false,
+    ///     "http://localhost:8888/callback",
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::UgcImageUpload,
+            Self::UserReadPlaybackState,
+            Self::UserModifyPlaybackState,
+            Self::UserReadCurrentlyPlaying,
+            Self::Streaming,
+            Self::AppRemoteControl,
+            Self::UserReadEmail,
+            Self::UserReadPrivate,
+            Self::PlaylistReadCollaborative,
+            Self::PlaylistModifyPublic,
+            Self::PlaylistReadPrivate,
+            Self::PlaylistModifyPrivate,
+            Self::UserLibraryModify,
+            Self::UserLibraryRead,
+            Self::UserTopRead,
+            Self::UserReadRecentlyPlayed,
+            Self::UserReadPlaybackPosition,
+            Self::UserFollowRead,
+            Self::UserFollowModify,
+        ]
+    }
+    /// The scopes required to read and control the user's playback: `user-read-playback-state`,
+    /// `user-modify-playback-state` and `user-read-currently-playing`.
+    #[must_use]
+    pub const fn playback() -> &'static [Self] {
+        &[
+            Self::UserReadPlaybackState,
+            Self::UserModifyPlaybackState,
+            Self::UserReadCurrentlyPlaying,
+        ]
+    }
+    /// The scopes required to read and modify the user's library: `user-library-read` and
+    /// `user-library-modify`.
+    #[must_use]
+    pub const fn library() -> &'static [Self] {
+        &[Self::UserLibraryRead, Self::UserLibraryModify]
+    }
+    /// The scopes required to read and modify all of the user's playlists:
+    /// `playlist-read-private`, `playlist-read-collaborative`, `playlist-modify-public` and
+    /// `playlist-modify-private`.
+    #[must_use]
+    pub const fn playlists() -> &'static [Self] {
+        &[
+            Self::PlaylistReadPrivate,
+            Self::PlaylistReadCollaborative,
+            Self::PlaylistModifyPublic,
+            Self::PlaylistModifyPrivate,
+        ]
+    }
+    /// The scopes required to read and modify who the user follows: `user-follow-read` and
+    /// `user-follow-modify`.
+    #[must_use]
+    pub const fn follow() -> &'static [Self] {
+        &[Self::UserFollowRead, Self::UserFollowModify]
+    }
+    /// The scopes required to read the user's listening history: `user-top-read`,
+    /// `user-read-recently-played` and `user-read-playback-position`.
+    #[must_use]
+    pub const fn listening_history() -> &'static [Self] {
+        &[
+            Self::UserTopRead,
+            Self::UserReadRecentlyPlayed,
+            Self::UserReadPlaybackPosition,
+        ]
+    }
+    /// The scopes required to read the user's profile: `user-read-email` and
+    /// `user-read-private`.
+    #[must_use]
+    pub const fn user() -> &'static [Self] {
+        &[Self::UserReadEmail, Self::UserReadPrivate]
+    }
     /// Get the scope as a string (in `kebab-case` like Spotify requires).
     ///
     /// # Examples
     ///
     /// ```
-    /// let scope = aspotify::Sope::UserReadEmail;
+    /// let scope = aspotify::Scope::UserReadEmail;
     ///
     /// assert_eq!(scope.as_str(), "user-read-email");
     /// ```
@@ -64,12 +160,235 @@ impl Scope {
     }
 }
 
-/// Like [`authorization_url`], but you supply your own state.
+impl FromStr for Scope {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(s, "scope", Self::all(), Self::as_str)
+    }
+}
+impl TryFrom<&str> for Scope {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A builder for the URL to redirect the user's browser to so that the URL can be generated for
+/// the [`Client::redirected`](super::Client::redirected) function.
+///
+/// Make sure that you have whitelisted the redirect uri in your Spotify dashboard, and
+/// `redirect_uri` must not contain any query strings.
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::{AuthorizationUrlBuilder, Scope};
+///
+/// let request = AuthorizationUrlBuilder::new("client id", "http://localhost:8888/callback")
+///     .scopes(Scope::playback().iter().copied())
+///     .show_dialog(true)
+///     .param("locale", "en")
+///     .build_request();
+/// ```
+///
+/// [Reference](https://developer.spotify.com/documentation/general/guides/authorization-guide/#1-have-your-application-request-authorization-the-user-logs-in-and-authorizes-access).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationUrlBuilder<'a> {
+    client_id: &'a str,
+    redirect_uri: &'a str,
+    scopes: Vec<Scope>,
+    show_dialog: bool,
+    state: Option<String>,
+    code_challenge: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl<'a> AuthorizationUrlBuilder<'a> {
+    /// Create a new builder from your client ID and the URI to redirect to.
+    #[must_use]
+    pub fn new(client_id: &'a str, redirect_uri: &'a str) -> Self {
+        Self {
+            client_id,
+            redirect_uri,
+            scopes: Vec::new(),
+            show_dialog: false,
+            state: None,
+            code_challenge: None,
+            params: Vec::new(),
+        }
+    }
+    /// Add scopes to request access to.
+    #[must_use]
+    pub fn scopes(mut self, scopes: impl IntoIterator<Item = Scope>) -> Self {
+        self.scopes.extend(scopes);
+        self
+    }
+    /// Whether to force the user to approve the app again even if they already have. Defaults to
+    /// false.
+    #[must_use]
+    pub fn show_dialog(mut self, show_dialog: bool) -> Self {
+        self.show_dialog = show_dialog;
+        self
+    }
+    /// Use your own state instead of a randomly generated one.
+    ///
+    /// It is recommended to use randomly generated state for security, so use this if you wish to
+    /// use your own random state generator. Without the `rand` feature of this library a state must
+    /// always be set with this, as no state is generated.
+    #[must_use]
+    pub fn state(mut self, state: impl Into<String>) -> Self {
+        self.state = Some(state.into());
+        self
+    }
+    /// Use the [PKCE
+    /// extension](https://developer.spotify.com/documentation/general/guides/authorization-guide/#authorization-code-flow-with-proof-key-for-code-exchange-pkce),
+    /// with the given code challenge.
+    ///
+    /// The code challenge must be the base64url-encoded SHA256 hash of your code verifier. The code
+    /// verifier itself must then be passed to
+    /// [`Client::redirected_with_verifier`](super::Client::redirected_with_verifier) or
+    /// [`Client::exchange_code_with_verifier`](super::Client::exchange_code_with_verifier).
+    #[must_use]
+    pub fn code_challenge(mut self, code_challenge: impl Into<String>) -> Self {
+        self.code_challenge = Some(code_challenge.into());
+        self
+    }
+    /// Add an arbitrary extra query parameter to the URL.
+    #[must_use]
+    pub fn param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+    /// Build the URL.
+    ///
+    /// If no state was set using [`state`](Self::state), it is randomly generated when the `rand`
+    /// feature of this library is activated (which it is by default).
+    ///
+    /// Without the `rand` feature and without a state set using [`state`](Self::state), the state
+    /// is left empty and no state parameter is sent. The URL is then open to cross-site request
+    /// forgery, and [`Client::redirected`](super::Client::redirected) will reject the redirect
+    /// with [`RedirectedError::IncorrectState`](super::RedirectedError::IncorrectState), so only
+    /// do this if you check the redirect yourself and use
+    /// [`Client::exchange_code`](super::Client::exchange_code).
+    #[must_use]
+    pub fn build_request(self) -> AuthorizationRequest {
+        let state = match self.state {
+            Some(state) => state,
+            #[cfg(feature = "rand")]
+            None => random_state(),
+            #[cfg(not(feature = "rand"))]
+            None => String::new(),
+        };
+
+        let mut url = Url::parse_with_params(
+            "https://accounts.spotify.com/authorize",
+            &[
+                ("response_type", "code"),
+                ("client_id", self.client_id),
+                (
+                    "scope",
+                    &self.scopes.into_iter().map(Scope::as_str).join(" "),
+                ),
+                (
+                    "show_dialog",
+                    if self.show_dialog { "true" } else { "false" },
+                ),
+                ("redirect_uri", self.redirect_uri),
+            ],
+        )
+        .unwrap();
+
+        {
+            let mut pairs = url.query_pairs_mut();
+            if !state.is_empty() {
+                pairs.append_pair("state", &state);
+            }
+            if let Some(code_challenge) = &self.code_challenge {
+                pairs
+                    .append_pair("code_challenge_method", "S256")
+                    .append_pair("code_challenge", code_challenge);
+            }
+            for (key, value) in &self.params {
+                pairs.append_pair(key, value);
+            }
+        }
+
+        AuthorizationRequest { url, state }
+    }
+    /// Build the URL, and save its state in a [`StateStore`] so that it can later be checked by
+    /// [`Client::redirected_with_store`](super::Client::redirected_with_store).
+    pub async fn build_with_store(self, store: &dyn StateStore) -> AuthorizationRequest {
+        let request = self.build_request();
+        store.insert(request.state.clone()).await;
+        request
+    }
+}
+
+/// A URL to redirect the user's browser to, and the state it was generated with.
+///
+/// The state must be passed to [`Client::redirected`](super::Client::redirected) along with the
+/// URL the user is redirected back to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationRequest {
+    /// The URL to send the user to.
+    pub url: Url,
+    /// The state parameter of the URL, which is empty if there isn't one.
+    pub state: String,
+}
+
+/// A place to keep the states of authorization URLs that have been sent to users, so that the
+/// state doesn't need to be tracked manually.
+///
+/// Each state can only be used once, which prevents a redirect URL from being replayed. A store
+/// backed by a shared database allows a user to be redirected back to a different instance of an
+/// application than the one that generated their authorization URL.
+///
+/// Use it with [`AuthorizationUrlBuilder::build_with_store`] and
+/// [`Client::redirected_with_store`](super::Client::redirected_with_store).
+pub trait StateStore: Debug + Send + Sync {
+    /// Save a newly generated state.
+    fn insert(&self, state: String) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+
+    /// Remove a state, returning whether it was in the store.
+    ///
+    /// If the store can't be accessed, this should return `false`.
+    fn take<'a>(&'a self, state: &'a str) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
+}
+
+/// A [`StateStore`] that keeps states in memory.
+///
+/// States are only removed once they are used, so states of authorization URLs that are never
+/// followed accumulate.
+#[derive(Debug, Default)]
+pub struct MemoryStateStore {
+    states: Mutex<HashSet<String>>,
+}
+
+impl MemoryStateStore {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateStore for MemoryStateStore {
+    fn insert(&self, state: String) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.states.lock().unwrap().insert(state);
+        Box::pin(async {})
+    }
+    fn take<'a>(&'a self, state: &'a str) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        let present = self.states.lock().unwrap().remove(state);
+        Box::pin(async move { present })
+    }
+}
+
+/// Like [`authorization_request`], but you supply your own state.
 ///
 /// It is recommended to use randomly generated state for security, so use this if you wish to use
 /// your own random state generator.
 ///
-/// This function, unlike [`authorization_url`] does not require features to be activated.
+/// This function, unlike [`authorization_request`] does not require features to be activated.
 ///
 /// See the docs of the other function for information about the parameters.
 pub fn authorization_url_with_state(
@@ -79,19 +398,13 @@ pub fn authorization_url_with_state(
     redirect_uri: &str,
     state: &str,
 ) -> String {
-    Url::parse_with_params(
-        "https://accounts.spotify.com/authorize",
-        &[
-            ("response_type", "code"),
-            ("state", &state),
-            ("client_id", client_id),
-            ("scope", &scopes.into_iter().map(Scope::as_str).join(" ")),
-            ("show_dialog", if force_approve { "true" } else { "false" }),
-            ("redirect_uri", redirect_uri),
-        ],
-    )
-    .unwrap()
-    .into_string()
+    AuthorizationUrlBuilder::new(client_id, redirect_uri)
+        .scopes(scopes)
+        .show_dialog(force_approve)
+        .state(state)
+        .build_request()
+        .url
+        .into()
 }
 
 /// Get the URL to redirect the user's browser to so that the URL can be generated for the
@@ -101,20 +414,45 @@ pub fn authorization_url_with_state(
 /// Make sure that you have whitelisted the redirect uri in your Spotify dashboard, and
 /// `redirect_uri` must not contain any query strings.
 ///
-/// This method returns a tuple of the generated url and the state parameter, which is randomly
-/// generated for security.
+/// The state of the returned request is randomly generated for security. To pass more options,
+/// use [`AuthorizationUrlBuilder`].
 ///
 /// This function is only available when the `rand` feature of this library is activated, and it is
 /// activated by default.
 ///
 /// [Reference](https://developer.spotify.com/documentation/general/guides/authorization-guide/#1-have-your-application-request-authorization-the-user-logs-in-and-authorizes-access).
 #[cfg(feature = "rand")]
+pub fn authorization_request(
+    client_id: &str,
+    scopes: impl IntoIterator<Item = Scope>,
+    force_approve: bool,
+    redirect_uri: &str,
+) -> AuthorizationRequest {
+    AuthorizationUrlBuilder::new(client_id, redirect_uri)
+        .scopes(scopes)
+        .show_dialog(force_approve)
+        .build_request()
+}
+
+/// Like [`authorization_request`], but returns a tuple of the generated url and the state
+/// parameter.
+///
+/// This function is only available when the `rand` feature of this library is activated, and it is
+/// activated by default.
+#[cfg(feature = "rand")]
+#[deprecated = "use `authorization_request`, which returns the URL and state as named fields"]
 pub fn authorization_url(
     client_id: &str,
     scopes: impl IntoIterator<Item = Scope>,
     force_approve: bool,
     redirect_uri: &str,
 ) -> (String, String) {
+    let request = authorization_request(client_id, scopes, force_approve, redirect_uri);
+    (request.url.into(), request.state)
+}
+
+#[cfg(feature = "rand")]
+fn random_state() -> String {
     use rand::Rng as _;
 
     const STATE_LEN: usize = 16;
@@ -126,9 +464,29 @@ pub fn authorization_url(
     for _ in 0..STATE_LEN {
         state.push(STATE_CHARS[rng.gen_range(0..STATE_CHARS.len())].into());
     }
+    state
+}
 
-    (
-        authorization_url_with_state(client_id, scopes, force_approve, redirect_uri, &state),
-        state,
-    )
+#[cfg(test)]
+mod tests {
+    use super::{AuthorizationUrlBuilder, MemoryStateStore, StateStore};
+
+    #[tokio::test]
+    async fn memory_state_store() {
+        let store = MemoryStateStore::new();
+        let request = AuthorizationUrlBuilder::new("client id", "http://localhost/callback")
+            .state("abc")
+            .build_with_store(&store)
+            .await;
+        assert!(request
+            .url
+            .query_pairs()
+            .any(|(key, value)| key == "state" && value == "abc"));
+        assert_eq!(request.state, "abc");
+
+        assert!(!store.take("abd").await);
+        assert!(store.take("abc").await);
+        // States can only be used once.
+        assert!(!store.take("abc").await);
+    }
 }
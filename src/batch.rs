@@ -0,0 +1,113 @@
+use std::future::Future;
+
+use crate::Client;
+
+/// Several requests to send concurrently, created by [`Client::batch`].
+///
+/// Requests of any type are added with [`add`](Self::add), and [`run`](Self::run) sends them all
+/// at once, returning their results as a tuple in the order they were added. Up to 8 requests can
+/// be in one batch.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example(client: &aspotify::Client) -> Result<(), aspotify::Error> {
+/// let (album, artist) = client
+///     .batch()
+///     .add(client.albums().get_album("03JPFQvZRnHHysSZrSFmKY", None))
+///     .add(client.artists().get_artist("0L8ExT028jH3ddEcZwqJJ5"))
+///     .run()
+///     .await;
+/// println!("{} by {}", album?.data.name, artist?.data.name);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "a batch does nothing unless it is run"]
+pub struct Batch<'a, T> {
+    client: &'a Client,
+    futures: T,
+}
+
+impl Client {
+    /// Start a batch of requests to send concurrently.
+    pub fn batch(&self) -> Batch<'_, ()> {
+        Batch {
+            client: self,
+            futures: (),
+        }
+    }
+}
+
+impl<'a> Batch<'a, ()> {
+    /// Add a request to the batch.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<F: Future>(self, future: F) -> Batch<'a, (F,)> {
+        Batch {
+            client: self.client,
+            futures: (future,),
+        }
+    }
+}
+
+macro_rules! impl_batch {
+    ($($fut:ident),*) => {
+        impl<'a, $($fut: Future,)*> Batch<'a, ($($fut,)*)> {
+            /// Send all the requests in the batch concurrently, and get their results in the order
+            /// they were added.
+            ///
+            /// If the client was recently rate limited, this first waits until the rate limit
+            /// resets.
+            #[allow(non_snake_case)]
+            pub async fn run(self) -> ($($fut::Output,)*) {
+                self.client.wait_for_rate_limit().await;
+                let ($($fut,)*) = self.futures;
+                futures_util::join!($($fut),*)
+            }
+        }
+    };
+    ($($fut:ident),*; $next:ident) => {
+        impl_batch!($($fut),*);
+
+        impl<'a, $($fut: Future,)*> Batch<'a, ($($fut,)*)> {
+            /// Add a request to the batch.
+            #[allow(non_snake_case, clippy::should_implement_trait)]
+            pub fn add<$next: Future>(self, future: $next) -> Batch<'a, ($($fut,)* $next,)> {
+                let ($($fut,)*) = self.futures;
+                Batch {
+                    client: self.client,
+                    futures: ($($fut,)* future,),
+                }
+            }
+        }
+    };
+}
+
+impl_batch!(A; B);
+impl_batch!(A, B; C);
+impl_batch!(A, B, C; D);
+impl_batch!(A, B, C, D; E);
+impl_batch!(A, B, C, D, E; F);
+impl_batch!(A, B, C, D, E, F; G);
+impl_batch!(A, B, C, D, E, F, G; H);
+impl_batch!(A, B, C, D, E, F, G, H);
+
+#[cfg(all(test, feature = "albums", feature = "artists", feature = "tracks"))]
+mod tests {
+    use crate::endpoints::client;
+
+    #[tokio::test]
+    async fn test_batch() {
+        let client = client();
+        let (album, artist, track) = client
+            .batch()
+            .add(client.albums().get_album("03JPFQvZRnHHysSZrSFmKY", None))
+            .add(client.artists().get_artist("0L8ExT028jH3ddEcZwqJJ5"))
+            .add(client.tracks().get_track("1Jwc3ODLQxtbnS8M9TflSP", None))
+            .run()
+            .await;
+        assert_eq!(album.unwrap().data.name, "Inside In / Inside Out");
+        assert_eq!(artist.unwrap().data.name, "Red Hot Chili Peppers");
+        assert_eq!(track.unwrap().data.name, "Walk Like an Egyptian");
+    }
+}
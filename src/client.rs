@@ -0,0 +1,2052 @@
+//! The client, which sends requests to Spotify and manages access tokens.
+
+use std::collections::HashMap;
+use std::env::{self, VarError};
+use std::error::Error as StdError;
+use std::ffi::OsStr;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::{header, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, watch, Mutex, MutexGuard};
+
+use crate::{
+    util, AuthError, EndpointError, Error, Image, ParseMode, RequestId, Scope, StateStore,
+    Subscription, UserPrivate,
+};
+
+/// Re-exports from [`http`], so that headers can be passed to [`Client::set_default_header`]
+/// without depending on the same version of `http` as this crate.
+pub use http::header::{HeaderMap, HeaderName, HeaderValue};
+/// Re-exports from [`reqwest`], so that the HTTP client can be configured with
+/// [`Client::set_http_client`] without depending on the same version of `reqwest` as this crate.
+pub use reqwest::{Client as HttpClient, ClientBuilder as HttpClientBuilder};
+
+/// A client to the Spotify API.
+///
+/// By default it will use the [client credentials
+/// flow](https://developer.spotify.com/documentation/general/guides/authorization-guide/#client-credentials-flow)
+/// to send requests to the Spotify API. The [`set_refresh_token`](Client::set_refresh_token) and
+/// [`redirected`](Client::redirected) methods tell it to use the [authorization code
+/// flow](https://developer.spotify.com/documentation/general/guides/authorization-guide/#authorization-code-flow)
+/// instead.
+#[derive(Debug)]
+pub struct Client {
+    /// Your Spotify client credentials.
+    pub credentials: ClientCredentials,
+    pub(crate) client: reqwest::Client,
+    cache: Mutex<AccessToken>,
+    user_id: Mutex<Option<String>>,
+    rate_limit: Mutex<Option<RateLimitState>>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) debug: bool,
+    parse_mode: ParseMode,
+    verify_responses: bool,
+    rate_limit_retries: Option<usize>,
+    token_events: broadcast::Sender<TokenEvent>,
+    refresh_file: Option<PathBuf>,
+    refresh_file_lock: Mutex<()>,
+    pub(crate) api_base_url: Option<Url>,
+    token_url: Url,
+    paused: watch::Sender<bool>,
+    paused_receiver: watch::Receiver<bool>,
+    default_headers: HeaderMap,
+}
+
+impl Client {
+    /// Create a new client from your Spotify client credentials.
+    #[must_use]
+    pub fn new(credentials: ClientCredentials) -> Self {
+        Self::with_token(credentials, AccessToken::new(None))
+    }
+    /// Create a new client with your Spotify client credentials and a refresh token.
+    #[must_use]
+    pub fn with_refresh(credentials: ClientCredentials, refresh_token: String) -> Self {
+        Self::with_token(credentials, AccessToken::new(Some(refresh_token)))
+    }
+    /// Create a new client with your Spotify client credentials and a token snapshot taken by
+    /// [`token_snapshot`](Self::token_snapshot), possibly by another client or process.
+    ///
+    /// The access token is used until it expires, so this doesn't cause a token refresh.
+    #[must_use]
+    pub fn from_token_snapshot(credentials: ClientCredentials, token: SerializableToken) -> Self {
+        Self::with_token(
+            credentials,
+            AccessToken::from_snapshot(token, SystemClock.now()),
+        )
+    }
+    fn with_token(credentials: ClientCredentials, token: AccessToken) -> Self {
+        let (paused, paused_receiver) = watch::channel(false);
+        Self {
+            credentials,
+            client: reqwest::Client::new(),
+            cache: Mutex::new(token),
+            user_id: Mutex::new(None),
+            rate_limit: Mutex::new(None),
+            clock: Arc::new(SystemClock),
+            debug: false,
+            parse_mode: ParseMode::default(),
+            verify_responses: false,
+            rate_limit_retries: None,
+            token_events: broadcast::channel(16).0,
+            refresh_file: None,
+            refresh_file_lock: Mutex::new(()),
+            api_base_url: None,
+            token_url: Url::parse("https://accounts.spotify.com/api/token").unwrap(),
+            paused,
+            paused_receiver,
+            default_headers: HeaderMap::new(),
+        }
+    }
+    /// Create a new client with your Spotify client credentials and the refresh token stored in a
+    /// file, such as the `.refresh_token` file created by the `refresh_file` example.
+    ///
+    /// Whenever the client's refresh token changes, for example because Spotify issued a new one
+    /// when the access token was refreshed, the new refresh token is written back to the file. The
+    /// file is replaced atomically by writing to a temporary file next to it and renaming it, so
+    /// it is never left half-written. If writing the file fails, a
+    /// [`TokenEvent::PersistFailed`] is sent.
+    ///
+    /// Writes by the client are never interleaved, but the file isn't locked against other
+    /// processes, so only one process may use a refresh token file at a time. If several do, one
+    /// can overwrite a token another has just been issued, and since Spotify may revoke the old
+    /// refresh token when it issues a new one, the token left in the file may no longer work.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read.
+    pub async fn with_refresh_file(
+        credentials: ClientCredentials,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let refresh_token = tokio::fs::read_to_string(&path).await?;
+        let mut client = Self::with_refresh(credentials, refresh_token.trim().to_owned());
+        client.refresh_file = Some(path);
+        Ok(client)
+    }
+    /// Get how strictly the client parses responses from Spotify.
+    #[must_use]
+    pub const fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+    /// Set how strictly the client parses responses from Spotify.
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+    /// Set whether the client checks that responses from Spotify are fully understood. This is
+    /// off by default, and has no effect in release builds.
+    ///
+    /// When it is on, every response is serialized again after being parsed and compared with the
+    /// JSON Spotify sent, and the path of any field that was dropped is printed to stderr. This
+    /// helps find fields that are missing from the [object model](crate::model). Responses parsed
+    /// as a type of your own, by [`get_page`](Self::get_page) and
+    /// [`get_with_query`](Self::get_with_query), are not checked.
+    pub fn set_verify_responses(&mut self, verify: bool) {
+        self.verify_responses = verify;
+    }
+    /// Set the HTTP client used to send requests, for example to tune its connection pool.
+    ///
+    /// By default the client uses HTTP/2 when Spotify offers it, multiplexing concurrent requests
+    /// over a single connection, and keeps idle connections alive for 90 seconds. Syncing large
+    /// libraries with many concurrent requests may benefit from keeping more connections alive
+    /// for longer:
+    ///
+    /// ```
+    /// # fn doc(client: &mut aspotify::Client) -> Result<(), aspotify::Error> {
+    /// use std::time::Duration;
+    ///
+    /// client.set_http_client(
+    ///     aspotify::HttpClientBuilder::new()
+    ///         .pool_idle_timeout(Duration::from_secs(300))
+    ///         .pool_max_idle_per_host(16)
+    ///         .build()?,
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`HttpClientBuilder::http2_prior_knowledge`] skips protocol negotiation entirely, which is
+    /// only useful when requests go through a proxy that is known to speak HTTP/2.
+    pub fn set_http_client(&mut self, client: HttpClient) {
+        self.client = client;
+    }
+    /// Get how many times a request is retried after being rate limited, or [`None`] if it is
+    /// retried indefinitely.
+    #[must_use]
+    pub const fn rate_limit_retries(&self) -> Option<usize> {
+        self.rate_limit_retries
+    }
+    /// Set how many times a request is retried after being rate limited. This applies to requests
+    /// for access tokens as well as to API requests. [`None`], the default, retries indefinitely.
+    /// Once the retries are exhausted the request fails with [`Error::RateLimited`].
+    pub fn set_rate_limit_retries(&mut self, retries: Option<usize>) {
+        self.rate_limit_retries = retries;
+    }
+    /// Send API requests to a different base URL instead of `https://api.spotify.com`, such as a
+    /// caching proxy or a mock server. [`None`], the default, sends them to Spotify.
+    ///
+    /// Requests for access tokens are always sent to Spotify.
+    pub fn set_api_base_url(&mut self, url: Option<Url>) {
+        self.api_base_url = url;
+    }
+    /// Get the headers that are sent with every request, set by
+    /// [`set_default_header`](Self::set_default_header).
+    #[must_use]
+    pub const fn default_headers(&self) -> &HeaderMap {
+        &self.default_headers
+    }
+    /// Send a header with every API request and every request for an access token, such as an
+    /// extra authentication header required by a gateway that the requests are routed through.
+    /// Setting a header that is already set replaces it.
+    ///
+    /// Headers that the client sets on a request itself, such as its `Content-Type`, take
+    /// priority. Default headers are not sent when downloading images with
+    /// [`get_image`](Self::get_image), as those requests go to Spotify's image servers.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the header is `Authorization`, which the client sets itself.
+    pub fn set_default_header(
+        &mut self,
+        name: HeaderName,
+        mut value: HeaderValue,
+    ) -> Result<(), ReservedHeader> {
+        if name == header::AUTHORIZATION {
+            return Err(ReservedHeader(name));
+        }
+        // The header may hold a secret, so keep it out of the client's debug output.
+        value.set_sensitive(true);
+        self.default_headers.insert(name, value);
+        Ok(())
+    }
+    /// Stop sending a header set by [`set_default_header`](Self::set_default_header).
+    pub fn remove_default_header(&mut self, name: &HeaderName) {
+        self.default_headers.remove(name);
+    }
+    /// Add the default headers to a request, without replacing any of its own headers.
+    fn apply_default_headers(&self, request: &mut reqwest::Request) {
+        for (name, value) in &self.default_headers {
+            request
+                .headers_mut()
+                .entry(name)
+                .or_insert_with(|| value.clone());
+        }
+    }
+    /// Set the clock the client uses to determine when tokens and responses expire and to wait
+    /// before retrying rate limited requests. By default this is [`SystemClock`].
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    /// Subscribe to events in the lifecycle of the client's access token, such as it being
+    /// refreshed or the user revoking the client's authorization.
+    ///
+    /// Only events that happen after subscribing are received. If the receiver falls more than 16
+    /// events behind, the oldest events are dropped.
+    #[must_use]
+    pub fn token_events(&self) -> broadcast::Receiver<TokenEvent> {
+        self.token_events.subscribe()
+    }
+    /// Hold all new requests until [`resume_requests`](Self::resume_requests) is called, for
+    /// example while Spotify is having an incident.
+    ///
+    /// Requests that have already been sent are not affected, but requests waiting to be retried
+    /// after being rate limited are held before they are retried.
+    pub fn pause_requests(&self) {
+        self.paused.send(true).ok();
+    }
+    /// Send the requests held since [`pause_requests`](Self::pause_requests) was called, and stop
+    /// holding new ones.
+    pub fn resume_requests(&self) {
+        self.paused.send(false).ok();
+    }
+    /// Whether requests are being held by [`pause_requests`](Self::pause_requests).
+    #[must_use]
+    pub fn requests_paused(&self) -> bool {
+        *self.paused_receiver.borrow()
+    }
+    /// Get the last time the client was rate limited, or [`None`] if it never has been.
+    pub async fn rate_limit_state(&self) -> Option<RateLimitState> {
+        *self.rate_limit.lock().await
+    }
+    /// Get the client's refresh token.
+    pub async fn refresh_token(&self) -> Option<String> {
+        self.cache.lock().await.refresh_token.clone()
+    }
+    /// Set the client's refresh token.
+    ///
+    /// If the client was created with [`with_refresh_file`](Self::with_refresh_file), the new
+    /// refresh token is written to the file.
+    pub async fn set_refresh_token(&self, refresh_token: Option<String>) {
+        if let Some(refresh_token) = &refresh_token {
+            self.persist_refresh_token(refresh_token).await;
+        }
+        let mut cache = self.cache.lock().await;
+        if let Some(old) = &mut cache.refresh_token {
+            wipe(old);
+        }
+        cache.refresh_token = refresh_token;
+        drop(cache);
+        *self.user_id.lock().await = None;
+    }
+    /// Get the client's access token values.
+    pub async fn current_access_token(&self) -> (String, Instant) {
+        let cache = self.cache.lock().await;
+        (cache.token.clone(), cache.expires)
+    }
+    /// Take a snapshot of the client's tokens, which can be serialized and later used to create a
+    /// client with [`from_token_snapshot`](Self::from_token_snapshot), or restored with
+    /// [`restore_token_snapshot`](Self::restore_token_snapshot).
+    pub async fn token_snapshot(&self) -> SerializableToken {
+        let cache = self.cache.lock().await;
+        let remaining = cache.expires.saturating_duration_since(self.clock.now());
+        SerializableToken {
+            access_token: cache.token.clone(),
+            expires_at: SystemTime::now() + remaining,
+            refresh_token: cache.refresh_token.clone(),
+            scope: cache.scope.clone(),
+        }
+    }
+    /// Replace the client's tokens with a snapshot taken by
+    /// [`token_snapshot`](Self::token_snapshot).
+    pub async fn restore_token_snapshot(&self, token: SerializableToken) {
+        *self.cache.lock().await = AccessToken::from_snapshot(token, self.clock.now());
+        *self.user_id.lock().await = None;
+    }
+    /// Explicitly override the client's access token values. Useful if you acquire the
+    /// access token elsewhere.
+    pub async fn set_current_access_token(&self, token: String, expires: Instant) {
+        let mut cache = self.cache.lock().await;
+        wipe(&mut cache.token);
+        cache.token = token;
+        cache.expires = expires;
+        drop(cache);
+        *self.user_id.lock().await = None;
+    }
+    /// Get the current user's ID.
+    ///
+    /// The first time this is called the user's profile is fetched, and the ID is then cached
+    /// until the client's refresh token or access token is changed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if getting the current user's profile fails, which it always does when using the
+    /// client credentials flow.
+    pub async fn current_user_id(&self) -> Result<String, Error> {
+        if let Some(id) = &*self.user_id.lock().await {
+            return Ok(id.clone());
+        }
+
+        // The user ID isn't kept locked while sending the request, which locks the token cache, so
+        // that the two locks are never held at once.
+        let id = self
+            .send_json::<UserPrivate>(self.client.get("https://api.spotify.com/v1/me"))
+            .await?
+            .data
+            .id;
+        *self.user_id.lock().await = Some(id.clone());
+        Ok(id)
+    }
+
+    async fn token_request(
+        &self,
+        params: TokenRequest<'_>,
+        id: RequestId,
+    ) -> Result<AccessToken, Error> {
+        let mut retries = 0;
+        let response = loop {
+            self.wait_until_resumed().await;
+            let mut request = self
+                .client
+                .post(self.token_url.clone())
+                .basic_auth(&self.credentials.id, Some(&self.credentials.secret))
+                .form(&params)
+                .build()?;
+            self.apply_default_headers(&mut request);
+
+            if self.debug {
+                eprintln!("[{}] Requesting an access token", id);
+                dbg!(&request, &params);
+            }
+
+            let response = self.client.execute(request).await?;
+            if !self.handle_rate_limit(&response, &mut retries, id).await? {
+                break response;
+            }
+        };
+        let status = response.status();
+        let mut text = response.text().await?;
+        if !status.is_success() {
+            if self.debug {
+                eprintln!(
+                    "[{}] Authentication failed ({}). Response body is '{}'",
+                    id, status, text
+                );
+            }
+            let mut error: AuthError = self.parse(&text)?;
+            error.request_id = Some(id);
+            return Err(Error::Auth(error));
+        }
+
+        let token: Result<TokenResponse, _> = self.parse(&text);
+        wipe(&mut text);
+        let token = token?;
+        if self.debug {
+            dbg!(status, &token);
+        }
+        Ok(AccessToken {
+            token: token.access_token,
+            expires: self.clock.now() + Duration::from_secs(token.expires_in),
+            refresh_token: token.refresh_token,
+            scope: token.scope,
+        })
+    }
+
+    /// Set the refresh token from the URL the client was redirected to and the state that was used
+    /// to send them there.
+    ///
+    /// Use the [`authorization_request()`](crate::authorization_request) function to generate the
+    /// URL to which you can send the client to to generate the URL here.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the URL is invalid in some way, the state was incorrect for the URL or Spotify
+    /// fails.
+    pub async fn redirected(&self, url: &str, state: &str) -> Result<(), RedirectedError> {
+        self.redirected_inner(url, state, None).await
+    }
+
+    /// Like [`redirected`](Self::redirected), but for authorization URLs that used the [PKCE
+    /// extension](https://developer.spotify.com/documentation/general/guides/authorization-guide/#authorization-code-flow-with-proof-key-for-code-exchange-pkce).
+    ///
+    /// `code_verifier` is the code verifier whose hash was passed to
+    /// [`AuthorizationUrlBuilder::code_challenge`](crate::AuthorizationUrlBuilder::code_challenge).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the URL is invalid in some way, the state was incorrect for the URL or Spotify
+    /// fails, which it does if the code verifier doesn't match the code challenge.
+    pub async fn redirected_with_verifier(
+        &self,
+        url: &str,
+        state: &str,
+        code_verifier: &str,
+    ) -> Result<(), RedirectedError> {
+        self.redirected_inner(url, state, Some(code_verifier)).await
+    }
+
+    async fn redirected_inner(
+        &self,
+        url: &str,
+        state: &str,
+        code_verifier: Option<&str>,
+    ) -> Result<(), RedirectedError> {
+        let url = Url::parse(url)?;
+
+        let pairs: HashMap<_, _> = url.query_pairs().collect();
+
+        if pairs
+            .get("state")
+            .map_or(true, |url_state| url_state != state)
+        {
+            return Err(RedirectedError::IncorrectState);
+        }
+
+        if let Some(error) = pairs.get("error") {
+            return Err(RedirectedError::AuthFailed(error.to_string()));
+        }
+
+        let code = pairs
+            .get("code")
+            .ok_or_else(|| RedirectedError::AuthFailed(String::new()))?;
+
+        self.exchange_code_inner(code, &url[..url::Position::AfterPath], code_verifier)
+            .await?;
+        Ok(())
+    }
+
+    /// Like [`redirected`](Self::redirected), but the state is checked against and removed from a
+    /// [`StateStore`] instead of being passed in.
+    ///
+    /// The state must have been put in the store by
+    /// [`AuthorizationUrlBuilder::build_with_store`](crate::AuthorizationUrlBuilder::build_with_store),
+    /// and each state can only be used once.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the URL is invalid in some way, the state is not in the store or Spotify fails.
+    pub async fn redirected_with_store(
+        &self,
+        url: &str,
+        store: &dyn StateStore,
+    ) -> Result<(), RedirectedError> {
+        let state = Url::parse(url)?
+            .query_pairs()
+            .find(|(key, _)| key == "state")
+            .map(|(_, state)| state.into_owned())
+            .ok_or(RedirectedError::IncorrectState)?;
+        if !store.take(&state).await {
+            return Err(RedirectedError::IncorrectState);
+        }
+        self.redirected(url, &state).await
+    }
+
+    /// Set the refresh token by exchanging an authorization code for it.
+    ///
+    /// This is a lower-level alternative to [`redirected`](Self::redirected) for when the code
+    /// has already been extracted from the redirect URL, for example by a web framework. Unlike
+    /// `redirected`, this does not check the state, so the caller must check it themselves.
+    ///
+    /// `redirect_uri` must be the same redirect URI that was used to generate the authorization
+    /// URL, without a query string.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Spotify rejects the code or the redirect URI.
+    pub async fn exchange_code(&self, code: &str, redirect_uri: &str) -> Result<(), Error> {
+        self.exchange_code_inner(code, redirect_uri, None).await
+    }
+
+    /// Like [`exchange_code`](Self::exchange_code), but for authorization URLs that used the PKCE
+    /// extension. See [`redirected_with_verifier`](Self::redirected_with_verifier).
+    ///
+    /// # Errors
+    ///
+    /// Fails if Spotify rejects the code, the redirect URI or the code verifier.
+    pub async fn exchange_code_with_verifier(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> Result<(), Error> {
+        self.exchange_code_inner(code, redirect_uri, Some(code_verifier))
+            .await
+    }
+
+    async fn exchange_code_inner(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: Option<&str>,
+    ) -> Result<(), Error> {
+        let token = self
+            .token_request(
+                TokenRequest::AuthorizationCode {
+                    code,
+                    redirect_uri,
+                    client_id: code_verifier.map(|_| &*self.credentials.id),
+                    code_verifier,
+                },
+                RequestId::next(),
+            )
+            .await?;
+        if let Some(refresh_token) = &token.refresh_token {
+            self.persist_refresh_token(refresh_token).await;
+        }
+        *self.cache.lock().await = token;
+        *self.user_id.lock().await = None;
+        Ok(())
+    }
+
+    /// Get the access token, refreshing it if it has expired. A refresh is done as part of the
+    /// request `id`.
+    async fn access_token(&self, id: RequestId) -> Result<MutexGuard<'_, AccessToken>, Error> {
+        let mut cache = self.cache.lock().await;
+        if cache.is_expired(self.clock.now()) {
+            let result = match &cache.refresh_token {
+                // Authorization code flow
+                Some(refresh_token) => {
+                    self.token_request(TokenRequest::RefreshToken { refresh_token }, id)
+                        .await
+                }
+                // Client credentials flow
+                None => {
+                    self.token_request(TokenRequest::ClientCredentials, id)
+                        .await
+                }
+            };
+
+            match result {
+                Ok(mut token) => {
+                    self.token_events
+                        .send(TokenEvent::TokenRefreshed {
+                            expires_at: token.expires,
+                        })
+                        .ok();
+                    match &token.refresh_token {
+                        // Spotify only sometimes issues a new refresh token. The old one is moved
+                        // rather than copied so that no copy of it is left behind.
+                        None => token.refresh_token = cache.refresh_token.take(),
+                        Some(refresh_token) => {
+                            if cache.refresh_token.as_ref() != Some(refresh_token) {
+                                self.persist_refresh_token(refresh_token).await;
+                            }
+                        }
+                    }
+                    *cache = token;
+                }
+                Err(e) => {
+                    self.token_events
+                        .send(TokenEvent::TokenRefreshFailed {
+                            error: e.to_string(),
+                        })
+                        .ok();
+                    if matches!(&e, Error::Auth(e) if e.error == "invalid_grant") {
+                        self.token_events
+                            .send(TokenEvent::AuthorizationRevoked)
+                            .ok();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(cache)
+    }
+
+    /// Write a new refresh token to the client's refresh token file, if it has one.
+    async fn persist_refresh_token(&self, refresh_token: &str) {
+        let Some(path) = &self.refresh_file else {
+            return;
+        };
+        // Writes from concurrent refreshes must not interleave.
+        let _guard = self.refresh_file_lock.lock().await;
+        if let Err(e) = write_atomic(path, refresh_token).await {
+            self.token_events
+                .send(TokenEvent::PersistFailed {
+                    error: e.to_string(),
+                })
+                .ok();
+        }
+    }
+
+    /// Check that the client's credentials work.
+    ///
+    /// This gets an access token if there isn't a valid one already, and if the authorization
+    /// code flow is being used, gets the current user's profile. The returned report describes
+    /// what the credentials give access to, which is useful for diagnosing misconfigured
+    /// credentials.
+    ///
+    /// # Errors
+    ///
+    /// Fails if an access token could not be obtained or getting the current user's profile
+    /// fails.
+    pub async fn verify_credentials(&self) -> Result<CredentialsReport, Error> {
+        let (flow, scopes) = {
+            let token = self.access_token(RequestId::next()).await?;
+            let flow = if token.refresh_token.is_some() {
+                AuthFlow::AuthorizationCode
+            } else {
+                AuthFlow::ClientCredentials
+            };
+            let scopes = token
+                .scope
+                .split_whitespace()
+                .filter_map(|scope| scope.parse().ok())
+                .collect();
+            (flow, scopes)
+        };
+
+        let user = match flow {
+            AuthFlow::AuthorizationCode => Some(
+                self.send_json::<UserPrivate>(self.client.get("https://api.spotify.com/v1/me"))
+                    .await?
+                    .data,
+            ),
+            AuthFlow::ClientCredentials => None,
+        };
+
+        Ok(CredentialsReport {
+            flow,
+            scopes,
+            user_id: user.as_ref().map(|user| user.id.clone()),
+            product: user.and_then(|user| user.product),
+        })
+    }
+
+    /// Download an image, such as an album's cover art.
+    ///
+    /// This reuses the client's HTTP connections, and does not need an access token.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or the image server responds with an error status.
+    pub async fn get_image(&self, image: &Image) -> Result<ImageData, Error> {
+        let response = self
+            .client
+            .get(&image.url)
+            .send()
+            .await?
+            .error_for_status()?;
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(ToOwned::to_owned);
+
+        Ok(ImageData {
+            bytes: response.bytes().await?.to_vec(),
+            content_type,
+        })
+    }
+
+    /// Download the most suitable image out of a list of images for displaying at the given
+    /// width, as chosen by [`Image::best_for`].
+    ///
+    /// Returns [`None`] if the list is empty.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or the image server responds with an error status.
+    pub async fn get_image_for(
+        &self,
+        images: &[Image],
+        width: usize,
+    ) -> Result<Option<ImageData>, Error> {
+        match Image::best_for(images, width) {
+            Some(image) => Ok(Some(self.get_image(image).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn send_text(&self, request: RequestBuilder) -> Result<Response<String>, Error> {
+        let id = RequestId::next();
+        let mut retries = 0;
+        let mut reauthorized = false;
+        let response = loop {
+            self.wait_until_resumed().await;
+            let (mut request, expires) = {
+                let token = self.access_token(id).await?;
+                let request = request.try_clone().unwrap().bearer_auth(&token.token);
+                (request.build()?, token.expires)
+            };
+            self.apply_default_headers(&mut request);
+            if let Some(base) = &self.api_base_url {
+                if let Some(url) = rebase_url(request.url(), base) {
+                    *request.url_mut() = url;
+                }
+            }
+
+            if self.debug {
+                eprintln!("[{}] Sending request", id);
+                dbg!(&request, body_str(&request));
+            }
+
+            let response = self.client.execute(request).await?;
+
+            // The access token may have been revoked before it expired, so get a new one and try
+            // again once.
+            if response.status() == 401 && !reauthorized {
+                if self.debug {
+                    eprintln!(
+                        "[{}] Access token was rejected; retrying with a new one",
+                        id
+                    );
+                }
+                reauthorized = true;
+                let mut cache = self.cache.lock().await;
+                // Another request may have already got a new token, which expires at a different
+                // time.
+                if cache.expires == expires {
+                    cache.expires = self.clock.now();
+                }
+                continue;
+            }
+            if !self.handle_rate_limit(&response, &mut retries, id).await? {
+                break response;
+            }
+        };
+        let status = response.status();
+        let cache_control = Duration::from_secs(
+            response
+                .headers()
+                .get_all(header::CACHE_CONTROL)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(|c| c == ','))
+                .find_map(|value| {
+                    let mut parts = value.trim().splitn(2, '=');
+                    if parts.next().unwrap().eq_ignore_ascii_case("max-age") {
+                        parts.next().and_then(|max| max.parse::<u64>().ok())
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_default(),
+        );
+
+        let data = response.text().await?;
+        if !status.is_success() {
+            if self.debug {
+                eprintln!("[{}] Failed ({}). Response body is '{}'", id, status, data);
+            }
+            let mut error: EndpointError = self.parse(&data)?;
+            error.request_id = Some(id);
+            return Err(Error::Endpoint(error));
+        }
+
+        if self.debug {
+            dbg!(status);
+            eprintln!("[{}] Response body is '{}'", id, data);
+        }
+
+        Ok(Response {
+            data,
+            expires: self.clock.now() + cache_control,
+        })
+    }
+
+    /// If the response says the client is rate limited, record it, wait for as long as Spotify
+    /// says to and return `true` so that the request is retried. Fails with
+    /// [`Error::RateLimited`] once the client's retries are exhausted.
+    ///
+    /// This is shared by API requests and token requests, as both can be rate limited.
+    async fn handle_rate_limit(
+        &self,
+        response: &reqwest::Response,
+        retries: &mut usize,
+        id: RequestId,
+    ) -> Result<bool, Error> {
+        if response.status() != 429 {
+            return Ok(false);
+        }
+        let wait = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|secs| secs.parse::<u64>().ok());
+        // 2 seconds is default retry after time; should never be used if the Spotify API and
+        // my code are both correct.
+        let retry_after = Duration::from_secs(wait.unwrap_or(2));
+        *self.rate_limit.lock().await = Some(RateLimitState {
+            retry_after,
+            resets: self.clock.now() + retry_after,
+        });
+
+        if self.rate_limit_retries.is_some_and(|max| *retries >= max) {
+            if self.debug {
+                eprintln!("[{}] Rate limited; out of retries", id);
+            }
+            return Err(Error::RateLimited(retry_after));
+        }
+        *retries += 1;
+        if self.debug {
+            eprintln!(
+                "[{}] Rate limited; retry {} in {} seconds",
+                id,
+                retries,
+                retry_after.as_secs()
+            );
+        }
+        self.clock.sleep(retry_after).await;
+        Ok(true)
+    }
+
+    /// Wait until requests are no longer paused by [`pause_requests`](Self::pause_requests).
+    async fn wait_until_resumed(&self) {
+        let mut paused = self.paused_receiver.clone();
+        while *paused.borrow() {
+            if paused.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Wait until the client is no longer rate limited or paused, so that requests that are about
+    /// to be sent together aren't all rate limited.
+    pub(crate) async fn wait_for_rate_limit(&self) {
+        self.wait_until_resumed().await;
+        if let Some(state) = self.rate_limit_state().await {
+            let now = self.clock.now();
+            if state.resets > now {
+                self.clock.sleep(state.resets - now).await;
+            }
+        }
+    }
+
+    #[cfg(any(
+        feature = "follow",
+        feature = "library",
+        feature = "player",
+        feature = "playlists"
+    ))]
+    pub(crate) async fn send_empty(&self, request: RequestBuilder) -> Result<(), Error> {
+        self.send_text(request).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "player")]
+    pub(crate) async fn send_opt_json<T: DeserializeOwned + Serialize>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response<Option<T>>, Error> {
+        let res = self.send_text(request).await?;
+        Ok(Response {
+            data: if res.data.is_empty() {
+                None
+            } else {
+                let data = self.parse(&res.data)?;
+                self.verify(&data, &res.data);
+                data
+            },
+            expires: res.expires,
+        })
+    }
+
+    pub(crate) async fn send_json<T: DeserializeOwned + Serialize>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response<T>, Error> {
+        let res = self.send_text(request).await?;
+        let data = self.parse(&res.data)?;
+        self.verify(&data, &res.data);
+        Ok(Response {
+            data,
+            expires: res.expires,
+        })
+    }
+
+    /// Like [`send_json`](Self::send_json), but the response isn't verified. This is for types
+    /// that deliberately only parse some of the response, and for types chosen by the user which
+    /// might not be serializable.
+    pub(crate) async fn send_json_unverified<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response<T>, Error> {
+        let res = self.send_text(request).await?;
+        Ok(Response {
+            data: self.parse(&res.data)?,
+            expires: res.expires,
+        })
+    }
+
+    /// Print the fields of a response that were dropped when parsing it, if the client verifies
+    /// responses.
+    fn verify<T: Serialize>(&self, data: &T, text: &str) {
+        if !cfg!(debug_assertions) || !self.verify_responses {
+            return;
+        }
+        let (Ok(original), Ok(reserialized)) =
+            (serde_json::from_str(text), serde_json::to_value(data))
+        else {
+            return;
+        };
+        for path in util::dropped_fields(&original, &reserialized) {
+            eprintln!(
+                "Field `{}` was dropped when parsing a response as {}",
+                path,
+                std::any::type_name::<T>()
+            );
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, serde_json::Error> {
+        util::with_parse_mode(self.parse_mode, || serde_json::from_str(text))
+    }
+
+    #[cfg(feature = "playlists")]
+    pub(crate) async fn send_snapshot_id(&self, request: RequestBuilder) -> Result<String, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct SnapshotId {
+            snapshot_id: String,
+        }
+        Ok(self
+            .send_json::<SnapshotId>(request)
+            .await?
+            .data
+            .snapshot_id)
+    }
+}
+
+/// The result of a request to a Spotify endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Response<T> {
+    /// The data itself.
+    pub data: T,
+    /// When the cache expires.
+    pub expires: Instant,
+}
+
+impl<T> Response<T> {
+    /// Map the contained data if there is any.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Response<U> {
+        Response {
+            data: f(self.data),
+            expires: self.expires,
+        }
+    }
+}
+
+/// A source of the current time for a [`Client`].
+///
+/// The client uses its clock to determine when access tokens and [`Response`]s expire, and to
+/// wait before retrying a rate limited request. Replacing it with
+/// [`Client::set_clock`] allows tests to simulate the passing of time without waiting.
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+
+    /// Wait for a duration. By default this uses Tokio's timer.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// The real clock, used by [`Client`]s by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A snapshot of a [`Client`]'s tokens, that can be stored and used to resume the session later
+/// or in another process.
+///
+/// Unlike the [`Instant`] returned by
+/// [`current_access_token`](Client::current_access_token), the expiry time is a [`SystemTime`], so
+/// it stays meaningful after being serialized.
+///
+/// The tokens are redacted in its [`Debug`] output; use [`reveal`](Self::reveal) to show them.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SerializableToken {
+    /// The access token.
+    pub access_token: String,
+    /// When the access token expires.
+    pub expires_at: SystemTime,
+    /// The refresh token, if the authorization code flow is being used.
+    pub refresh_token: Option<String>,
+    /// The space-separated scopes the access token grants.
+    #[serde(default)]
+    pub scope: String,
+}
+
+impl SerializableToken {
+    /// Get a value whose [`Debug`] output includes the tokens.
+    #[must_use]
+    pub fn reveal(&self) -> Reveal<'_, Self> {
+        Reveal(self)
+    }
+    fn debug(&self, f: &mut Formatter<'_>, reveal: bool) -> fmt::Result {
+        f.debug_struct("SerializableToken")
+            .field("access_token", &Secret(&self.access_token, reveal))
+            .field("expires_at", &self.expires_at)
+            .field(
+                "refresh_token",
+                &self
+                    .refresh_token
+                    .as_ref()
+                    .map(|token| Secret(token, reveal)),
+            )
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+impl Debug for SerializableToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.debug(f, false)
+    }
+}
+
+impl Debug for Reveal<'_, SerializableToken> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, true)
+    }
+}
+
+/// An event in the lifecycle of a [`Client`]'s access token, received from
+/// [`Client::token_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TokenEvent {
+    /// A new access token was obtained.
+    TokenRefreshed {
+        /// When the new access token expires.
+        expires_at: Instant,
+    },
+    /// Getting a new access token failed. The request that needed it fails with the same error.
+    TokenRefreshFailed {
+        /// A description of the error.
+        error: String,
+    },
+    /// Spotify rejected the refresh token, usually because the user revoked the client's access.
+    /// Requests on behalf of the user will keep failing until the client is authorized again,
+    /// for example with [`Client::redirected`].
+    AuthorizationRevoked,
+    /// A new refresh token could not be written to the file given to
+    /// [`Client::with_refresh_file`]. The client keeps using the new refresh token.
+    PersistFailed {
+        /// A description of the error.
+        error: String,
+    },
+}
+
+/// Move a URL to the Spotify API onto a different base URL, or return [`None`] if it isn't to the
+/// Spotify API.
+fn rebase_url(url: &Url, base: &Url) -> Option<Url> {
+    let path = url.as_str().strip_prefix("https://api.spotify.com/")?;
+    Url::parse(&format!("{}/{}", base.as_str().trim_end_matches('/'), path)).ok()
+}
+
+/// Replace the contents of a file, by writing to a temporary file next to it and renaming it over
+/// the original.
+///
+/// The temporary file has a name unique to this process and write, and on Unix it is created
+/// readable only by its owner and then given the permissions of the original file, so the secret
+/// isn't exposed while it is being written. The file isn't locked, so if several processes write
+/// it at once the last rename wins.
+async fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = PathBuf::from(temp);
+
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&temp).await?;
+
+    let result: io::Result<()> = async {
+        #[cfg(unix)]
+        if let Ok(metadata) = tokio::fs::metadata(path).await {
+            file.set_permissions(metadata.permissions()).await?;
+        }
+        file.write_all(contents.as_bytes()).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&temp, path).await
+    }
+    .await;
+    if result.is_err() {
+        tokio::fs::remove_file(&temp).await.ok();
+    }
+    result
+}
+
+/// The state of the rate limit the last time a [`Client`] was rate limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RateLimitState {
+    /// How long Spotify said to wait before retrying, from its `Retry-After` header.
+    pub retry_after: Duration,
+    /// When the rate limit resets.
+    pub resets: Instant,
+}
+
+/// Which authorization flow a [`Client`] is using.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthFlow {
+    /// The [client credentials
+    /// flow](https://developer.spotify.com/documentation/general/guides/authorization-guide/#client-credentials-flow),
+    /// which cannot access user information.
+    ClientCredentials,
+    /// The [authorization code
+    /// flow](https://developer.spotify.com/documentation/general/guides/authorization-guide/#authorization-code-flow),
+    /// which acts on behalf of a user.
+    AuthorizationCode,
+}
+
+/// A report about a client's credentials, created by [`Client::verify_credentials`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialsReport {
+    /// The authorization flow in use.
+    pub flow: AuthFlow,
+    /// The scopes that have been granted. This is empty for the client credentials flow, or if
+    /// the access token was set with
+    /// [`set_current_access_token`](Client::set_current_access_token).
+    pub scopes: Vec<Scope>,
+    /// The ID of the current user, if the authorization code flow is in use.
+    pub user_id: Option<String>,
+    /// The current user's subscription level, if the authorization code flow is in use and
+    /// `user-read-private` has been granted.
+    pub product: Option<Subscription>,
+}
+
+/// The contents of an image, downloaded with [`Client::get_image`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageData {
+    /// The raw bytes of the image.
+    pub bytes: Vec<u8>,
+    /// The MIME type of the image, such as `image/jpeg`, if the server sent one.
+    pub content_type: Option<String>,
+}
+
+/// An object that holds your Spotify Client ID and Client Secret.
+///
+/// See [the Spotify guide on Spotify
+/// apps](https://developer.spotify.com/documentation/general/guides/app-settings/) for how to get
+/// these.
+///
+/// # Examples
+///
+/// ```no_run
+/// use aspotify::ClientCredentials;
+///
+/// // Create from inside the program.
+/// let credentials = ClientCredentials {
+///     id: "your client id here".to_owned(),
+///     secret: "your client secret here".to_owned()
+/// };
+///
+/// // Create from CLIENT_ID and CLIENT_SECRET environment variables
+/// let credentials = ClientCredentials::from_env()
+///     .expect("CLIENT_ID or CLIENT_SECRET environment variables not set");
+///
+/// // Or use custom env var names
+/// let credentials = ClientCredentials::from_env_vars("SPOTIFY_ID", "SPOTIFY_SECRET")
+///     .expect("SPOTIFY_ID or SPOTIFY_SECRET environment variables not set");
+/// ```
+///
+/// The secret is redacted in the [`Debug`] output; use [`reveal`](Self::reveal) to show it.
+///
+/// It implements [`Drop`] so that the `zeroize` feature can wipe the secret, whether or not that
+/// feature is enabled, so its fields can be borrowed or cloned but not moved out.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClientCredentials {
+    /// The Client ID.
+    pub id: String,
+    /// The Client Secret.
+    pub secret: String,
+}
+
+impl ClientCredentials {
+    /// Attempts to create a `ClientCredentials` by reading environment variables.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the environment variables are not present or are not unicode.
+    pub fn from_env_vars<I: AsRef<OsStr>, S: AsRef<OsStr>>(
+        client_id: I,
+        client_secret: S,
+    ) -> Result<Self, VarError> {
+        Ok(Self {
+            id: env::var(client_id)?,
+            secret: env::var(client_secret)?,
+        })
+    }
+    /// Attempts to create a `ClientCredentials` by reading the `CLIENT_ID` and `CLIENT_SECRET`
+    /// environment variables.
+    ///
+    /// Equivalent to `ClientCredentials::from_env_vars("CLIENT_ID", "CLIENT_SECRET")`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the environment variables are not present or are not unicode.
+    pub fn from_env() -> Result<Self, VarError> {
+        Self::from_env_vars("CLIENT_ID", "CLIENT_SECRET")
+    }
+    /// Get a value whose [`Debug`] output includes the secret.
+    #[must_use]
+    pub fn reveal(&self) -> Reveal<'_, Self> {
+        Reveal(self)
+    }
+    fn debug(&self, f: &mut Formatter<'_>, reveal: bool) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("id", &self.id)
+            .field("secret", &Secret(&self.secret, reveal))
+            .finish()
+    }
+}
+
+impl Drop for ClientCredentials {
+    fn drop(&mut self) {
+        wipe(&mut self.secret);
+    }
+}
+
+impl Debug for ClientCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.debug(f, false)
+    }
+}
+
+impl Debug for Reveal<'_, ClientCredentials> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, true)
+    }
+}
+
+/// The configuration of a [`Client`], which can be read from environment variables.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn doc() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = aspotify::ClientConfig::from_env()?.build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The secret and refresh token are redacted in the [`Debug`] output; use
+/// [`reveal`](Self::reveal) to show them.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// The client credentials.
+    pub credentials: ClientCredentials,
+    /// The refresh token to use, if any. See [`Client::with_refresh`].
+    pub refresh_token: Option<String>,
+    /// The base URL to send API requests to. See [`Client::set_api_base_url`].
+    pub api_base_url: Option<Url>,
+    /// How many times a request is retried after being rate limited. See
+    /// [`Client::set_rate_limit_retries`].
+    pub rate_limit_retries: Option<usize>,
+    /// How long a request can take before it fails, or [`None`] for no limit.
+    pub timeout: Option<Duration>,
+}
+
+impl ClientConfig {
+    /// Read the configuration from environment variables:
+    ///
+    /// - `CLIENT_ID` and `CLIENT_SECRET`, which are required.
+    /// - `REFRESH_TOKEN`.
+    /// - `API_BASE_URL`.
+    /// - `RATE_LIMIT`, the number of times to retry rate limited requests.
+    /// - `TIMEOUT`, the request timeout in seconds.
+    ///
+    /// Optional variables that are set to an empty string are treated as not being set.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a required variable is not set, or a variable is not unicode or is invalid.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        fn var(name: &'static str) -> Result<Option<String>, ConfigError> {
+            match env::var(name) {
+                Ok(value) if value.is_empty() => Ok(None),
+                Ok(value) => Ok(Some(value)),
+                Err(VarError::NotPresent) => Ok(None),
+                Err(error) => Err(ConfigError::Var { name, error }),
+            }
+        }
+        fn required(name: &'static str) -> Result<String, ConfigError> {
+            var(name)?.ok_or(ConfigError::Var {
+                name,
+                error: VarError::NotPresent,
+            })
+        }
+        fn parsed<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+            var(name)?
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| ConfigError::Invalid { name, value })
+                })
+                .transpose()
+        }
+
+        Ok(Self {
+            credentials: ClientCredentials {
+                id: required("CLIENT_ID")?,
+                secret: required("CLIENT_SECRET")?,
+            },
+            refresh_token: var("REFRESH_TOKEN")?,
+            api_base_url: parsed("API_BASE_URL")?,
+            rate_limit_retries: parsed("RATE_LIMIT")?,
+            timeout: parsed("TIMEOUT")?.map(Duration::from_secs),
+        })
+    }
+    /// Create a client with this configuration.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the HTTP client could not be created.
+    pub fn build(self) -> Result<Client, Error> {
+        let mut client = match self.refresh_token {
+            Some(refresh_token) => Client::with_refresh(self.credentials, refresh_token),
+            None => Client::new(self.credentials),
+        };
+        if let Some(timeout) = self.timeout {
+            client.set_http_client(HttpClientBuilder::new().timeout(timeout).build()?);
+        }
+        client.set_api_base_url(self.api_base_url);
+        client.set_rate_limit_retries(self.rate_limit_retries);
+        Ok(client)
+    }
+    /// Get a value whose [`Debug`] output includes the secret and refresh token.
+    #[must_use]
+    pub fn reveal(&self) -> Reveal<'_, Self> {
+        Reveal(self)
+    }
+    fn debug(&self, f: &mut Formatter<'_>, reveal: bool) -> fmt::Result {
+        let mut s = f.debug_struct("ClientConfig");
+        if reveal {
+            s.field("credentials", &self.credentials.reveal());
+        } else {
+            s.field("credentials", &self.credentials);
+        }
+        s.field(
+            "refresh_token",
+            &self
+                .refresh_token
+                .as_ref()
+                .map(|token| Secret(token, reveal)),
+        )
+        .field("api_base_url", &self.api_base_url)
+        .field("rate_limit_retries", &self.rate_limit_retries)
+        .field("timeout", &self.timeout)
+        .finish()
+    }
+}
+
+impl Debug for ClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.debug(f, false)
+    }
+}
+
+impl Debug for Reveal<'_, ClientConfig> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.debug(f, true)
+    }
+}
+
+/// A wrapper whose [`Debug`] output includes secrets that are otherwise redacted, created by
+/// functions like [`ClientCredentials::reveal`].
+#[derive(Clone, Copy)]
+pub struct Reveal<'a, T>(&'a T);
+
+/// Wipe a secret from memory when the `zeroize` feature is enabled.
+#[cfg(feature = "zeroize")]
+fn wipe(secret: &mut String) {
+    zeroize::Zeroize::zeroize(secret);
+}
+
+/// Wipe a secret from memory when the `zeroize` feature is enabled.
+#[cfg(not(feature = "zeroize"))]
+fn wipe(_secret: &mut String) {}
+
+/// A secret in [`Debug`] output, which is redacted unless it is being revealed.
+struct Secret<'a>(&'a str, bool);
+
+impl Debug for Secret<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.1 {
+            Debug::fmt(self.0, f)
+        } else {
+            f.write_str("<redacted>")
+        }
+    }
+}
+
+/// An error reading a [`ClientConfig`] from environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The variable is required but not set, or is not unicode.
+    Var {
+        /// The name of the variable.
+        name: &'static str,
+        /// The error reading it.
+        error: VarError,
+    },
+    /// The variable's value is invalid.
+    Invalid {
+        /// The name of the variable.
+        name: &'static str,
+        /// The invalid value.
+        value: String,
+    },
+}
+
+/// An error setting a default header with [`Client::set_default_header`], because the client
+/// sets the header itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservedHeader(pub HeaderName);
+
+impl Display for ReservedHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "the {} header can't be set by default", self.0)
+    }
+}
+
+impl StdError for ReservedHeader {}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Var { name, error } => write!(f, "environment variable {}: {}", name, error),
+            Self::Invalid { name, value } => {
+                write!(
+                    f,
+                    "environment variable {} has invalid value {:?}",
+                    name, value
+                )
+            }
+        }
+    }
+}
+
+impl StdError for ConfigError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Var { error, .. } => Some(error),
+            Self::Invalid { .. } => None,
+        }
+    }
+}
+
+/// An error caused by the [`Client::redirected`] function.
+#[derive(Debug)]
+pub enum RedirectedError {
+    /// The URL is malformed.
+    InvalidUrl(url::ParseError),
+    /// The URL has no state parameter, or the state parameter was incorrect.
+    IncorrectState,
+    /// The user has not accepted the request or an error occured in Spotify.
+    ///
+    /// This contains the string returned by Spotify in the `error` parameter.
+    AuthFailed(String),
+    /// An error occurred getting the access token.
+    Token(Error),
+}
+
+impl From<url::ParseError> for RedirectedError {
+    fn from(error: url::ParseError) -> Self {
+        Self::InvalidUrl(error)
+    }
+}
+impl From<Error> for RedirectedError {
+    fn from(error: Error) -> Self {
+        Self::Token(error)
+    }
+}
+
+impl Display for RedirectedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl(_) => f.write_str("malformed redirect URL"),
+            Self::IncorrectState => f.write_str("state parameter not found or is incorrect"),
+            Self::AuthFailed(_) => f.write_str("authorization failed"),
+            Self::Token(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl StdError for RedirectedError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(match self {
+            Self::InvalidUrl(e) => e,
+            Self::Token(e) => e,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "grant_type", rename_all = "snake_case")]
+enum TokenRequest<'a> {
+    RefreshToken {
+        refresh_token: &'a String,
+    },
+    ClientCredentials,
+    AuthorizationCode {
+        code: &'a str,
+        redirect_uri: &'a str,
+        /// Spotify requires the client ID in the body when using PKCE.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_id: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code_verifier: Option<&'a str>,
+    },
+}
+
+impl Debug for TokenRequest<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RefreshToken { refresh_token } => f
+                .debug_struct("RefreshToken")
+                .field("refresh_token", &Secret(refresh_token, false))
+                .finish(),
+            Self::ClientCredentials => f.write_str("ClientCredentials"),
+            Self::AuthorizationCode {
+                code,
+                redirect_uri,
+                client_id,
+                code_verifier,
+            } => f
+                .debug_struct("AuthorizationCode")
+                .field("code", &Secret(code, false))
+                .field("redirect_uri", redirect_uri)
+                .field("client_id", client_id)
+                .field("code_verifier", &code_verifier.map(|v| Secret(v, false)))
+                .finish(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    scope: String,
+}
+
+impl Debug for TokenResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenResponse")
+            .field("access_token", &Secret(&self.access_token, false))
+            .field("expires_in", &self.expires_in)
+            .field(
+                "refresh_token",
+                &self.refresh_token.as_deref().map(|t| Secret(t, false)),
+            )
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+struct AccessToken {
+    token: String,
+    expires: Instant,
+    refresh_token: Option<String>,
+    scope: String,
+}
+
+impl AccessToken {
+    fn new(refresh_token: Option<String>) -> Self {
+        Self {
+            token: String::new(),
+            expires: Instant::now(),
+            refresh_token,
+            scope: String::new(),
+        }
+    }
+    fn from_snapshot(token: SerializableToken, now: Instant) -> Self {
+        let remaining = token
+            .expires_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        Self {
+            token: token.access_token,
+            expires: now + remaining,
+            refresh_token: token.refresh_token,
+            scope: token.scope,
+        }
+    }
+    /// Whether the token needs to be refreshed. An empty token has never been obtained, so it is
+    /// always expired.
+    fn is_expired(&self, now: Instant) -> bool {
+        self.token.is_empty() || now >= self.expires
+    }
+}
+
+impl Drop for AccessToken {
+    fn drop(&mut self) {
+        wipe(&mut self.token);
+        if let Some(refresh_token) = &mut self.refresh_token {
+            wipe(refresh_token);
+        }
+    }
+}
+
+impl Debug for AccessToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("token", &Secret(&self.token, false))
+            .field("expires", &self.expires)
+            .field(
+                "refresh_token",
+                &self
+                    .refresh_token
+                    .as_ref()
+                    .map(|token| Secret(token, false)),
+            )
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+/// Get the contents of a request body as a string. This is only used for debugging purposes.
+fn body_str(req: &reqwest::Request) -> Option<&str> {
+    req.body().map(|body| {
+        body.as_bytes().map_or("stream", |bytes| {
+            std::str::from_utf8(bytes).unwrap_or("opaque bytes")
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant, SystemTime};
+
+    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+    use reqwest::Url;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    use super::{
+        body_str, rebase_url, Client, ClientCredentials, Clock, HeaderName, HeaderValue,
+        ReservedHeader, SerializableToken, TokenEvent, TokenRequest, TokenResponse,
+    };
+    use crate::{util, Error, RequestId};
+
+    #[derive(Debug)]
+    struct ManualClock(Mutex<Instant>);
+
+    impl ManualClock {
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_dropped_fields() {
+        let original = json!({
+            "name": "Seaside",
+            "unknown": 5,
+            "missing": null,
+            "artists": [{ "name": "The Kooks", "extra": true }],
+        });
+        let reserialized = json!({
+            "name": "Seaside",
+            "artists": [{ "name": "The Kooks" }],
+        });
+        assert_eq!(
+            util::dropped_fields(&original, &reserialized),
+            ["artists[0].extra", "unknown"]
+        );
+        assert!(util::dropped_fields(&original, &original).is_empty());
+    }
+
+    #[test]
+    fn test_pkce_token_request() {
+        let form = |params: &TokenRequest<'_>| {
+            let request = reqwest::Client::new()
+                .post("https://accounts.spotify.com/api/token")
+                .form(params)
+                .build()
+                .unwrap();
+            body_str(&request).unwrap().to_owned()
+        };
+        assert_eq!(
+            form(&TokenRequest::AuthorizationCode {
+                code: "abc",
+                redirect_uri: "http://localhost/callback",
+                client_id: None,
+                code_verifier: None,
+            }),
+            "grant_type=authorization_code&code=abc&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"
+        );
+        assert_eq!(
+            form(&TokenRequest::AuthorizationCode {
+                code: "abc",
+                redirect_uri: "http://localhost/callback",
+                client_id: Some("id"),
+                code_verifier: Some("verifier"),
+            }),
+            "grant_type=authorization_code&code=abc&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback\
+             &client_id=id&code_verifier=verifier"
+        );
+    }
+
+    #[test]
+    fn test_token_debug_redacted() {
+        let refresh_token = "refresh".to_owned();
+        let requests = format!(
+            "{:?} {:?}",
+            TokenRequest::RefreshToken {
+                refresh_token: &refresh_token,
+            },
+            TokenRequest::AuthorizationCode {
+                code: "code",
+                redirect_uri: "http://localhost/callback",
+                client_id: Some("id"),
+                code_verifier: Some("verifier"),
+            },
+        );
+        assert!(requests.contains("http://localhost/callback"));
+        for secret in &["\"refresh\"", "\"code\"", "\"verifier\""] {
+            assert!(
+                !requests.contains(secret),
+                "{} contains {}",
+                requests,
+                secret
+            );
+        }
+
+        let response: TokenResponse = serde_json::from_value(json!({
+            "access_token": "access",
+            "expires_in": 3600,
+            "refresh_token": "refresh",
+            "scope": "user-read-private",
+        }))
+        .unwrap();
+        let response = format!("{:?}", response);
+        assert!(response.contains("user-read-private"));
+        assert!(!response.contains("\"access\""));
+        assert!(!response.contains("\"refresh\""));
+    }
+
+    #[tokio::test]
+    async fn test_pause_requests() {
+        let client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        assert!(!client.requests_paused());
+        client.wait_until_resumed().await;
+
+        client.pause_requests();
+        assert!(client.requests_paused());
+        let waiting = client.wait_until_resumed();
+        tokio::pin!(waiting);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), &mut waiting)
+                .await
+                .is_err()
+        );
+
+        client.resume_requests();
+        assert!(!client.requests_paused());
+        tokio::time::timeout(Duration::from_millis(50), waiting)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_redacted_debug() {
+        let credentials = ClientCredentials {
+            id: "id".to_owned(),
+            secret: "hunter2".to_owned(),
+        };
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("\"id\""));
+        assert!(!debug.contains("hunter2"));
+        assert!(format!("{:?}", credentials.reveal()).contains("hunter2"));
+
+        let client = Client::with_refresh(credentials, "refresh".to_owned());
+        assert!(!format!("{:?}", client).contains("hunter2"));
+        assert!(!format!("{:?}", client).contains("refresh\""));
+    }
+
+    #[test]
+    fn test_rebase_url() {
+        let base = Url::parse("http://localhost:8080/spotify/").unwrap();
+        assert_eq!(
+            rebase_url(
+                &Url::parse("https://api.spotify.com/v1/me/tracks?limit=50").unwrap(),
+                &base
+            )
+            .unwrap()
+            .as_str(),
+            "http://localhost:8080/spotify/v1/me/tracks?limit=50"
+        );
+        assert_eq!(
+            rebase_url(&Url::parse("https://i.scdn.co/image/abc").unwrap(), &base),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_file() {
+        let path = std::env::temp_dir().join(format!("aspotify-refresh-{}", std::process::id()));
+        std::fs::write(&path, "old token\n").unwrap();
+
+        let client = Client::with_refresh_file(
+            ClientCredentials {
+                id: String::new(),
+                secret: String::new(),
+            },
+            &path,
+        )
+        .await
+        .unwrap();
+        assert_eq!(client.refresh_token().await.as_deref(), Some("old token"));
+
+        client.set_refresh_token(Some("new token".to_owned())).await;
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new token");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+            client
+                .set_refresh_token(Some("newer token".to_owned()))
+                .await;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clock() {
+        let clock = Arc::new(ManualClock(Mutex::new(Instant::now())));
+        let mut client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        client.set_clock(clock.clone());
+
+        client
+            .set_current_access_token("token".to_owned(), clock.now() + Duration::from_secs(60))
+            .await;
+        assert!(!client.cache.lock().await.is_expired(clock.now()));
+        let expires_at = client.token_snapshot().await.expires_at;
+        assert!(expires_at > SystemTime::now() + Duration::from_secs(50));
+
+        clock.advance(Duration::from_secs(61));
+        assert!(client.cache.lock().await.is_expired(clock.now()));
+        let expires_at = client.token_snapshot().await.expires_at;
+        assert!(expires_at <= SystemTime::now());
+    }
+
+    #[test]
+    fn default_headers() {
+        let mut client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        let gateway = HeaderName::from_static("x-gateway-key");
+        client
+            .set_default_header(gateway.clone(), HeaderValue::from_static("key"))
+            .unwrap();
+        client
+            .set_default_header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+            .unwrap();
+        assert_eq!(
+            client.set_default_header(AUTHORIZATION, HeaderValue::from_static("Bearer x")),
+            Err(ReservedHeader(AUTHORIZATION))
+        );
+        assert_eq!(client.default_headers().len(), 2);
+
+        let mut request = client
+            .client
+            .post("https://api.spotify.com/v1/me")
+            .bearer_auth("token")
+            .json(&json!({}))
+            .build()
+            .unwrap();
+        client.apply_default_headers(&mut request);
+        assert_eq!(request.headers()[&gateway], "key");
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer token");
+
+        client.remove_default_header(&gateway);
+        assert_eq!(client.default_headers().len(), 1);
+    }
+
+    /// Accept a single HTTP request on a local port and reply to it with `response`, returning the
+    /// URL to send the request to and the body of the request once it is received.
+    async fn serve_once(response: String) -> (Url, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/api/token",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            let body = loop {
+                let read = stream.read(&mut buf).await.unwrap();
+                assert_ne!(read, 0, "the request ended early");
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len = head
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                        .map_or(0, |(_, len)| len.trim().parse().unwrap());
+                    if body.len() >= len {
+                        break body.to_owned();
+                    }
+                }
+            };
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+            body
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_failure() {
+        let error = r#"{"error":"invalid_grant","error_description":"Invalid refresh token"}"#;
+        let (token_url, server) = serve_once(format!(
+            "HTTP/1.1 400 Bad Request\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            error.len(),
+            error
+        ))
+        .await;
+
+        let clock = Arc::new(ManualClock(Mutex::new(Instant::now())));
+        let mut client = Client::from_token_snapshot(
+            ClientCredentials {
+                id: String::new(),
+                secret: String::new(),
+            },
+            SerializableToken {
+                access_token: "token".to_owned(),
+                expires_at: SystemTime::now() + Duration::from_secs(60),
+                refresh_token: Some("refresh".to_owned()),
+                scope: String::new(),
+            },
+        );
+        client.set_clock(clock.clone());
+        client.token_url = token_url;
+        let mut events = client.token_events();
+
+        // The token hasn't expired, so it isn't refreshed.
+        assert_eq!(
+            client.access_token(RequestId::next()).await.unwrap().token,
+            "token"
+        );
+        assert!(events.try_recv().is_err());
+
+        clock.advance(Duration::from_secs(61));
+        let error = client.access_token(RequestId::next()).await.unwrap_err();
+        assert!(matches!(error, Error::Auth(e) if e.error == "invalid_grant"));
+        assert_eq!(
+            server.await.unwrap(),
+            "grant_type=refresh_token&refresh_token=refresh"
+        );
+        assert!(matches!(
+            events.try_recv(),
+            Ok(TokenEvent::TokenRefreshFailed { .. })
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(TokenEvent::AuthorizationRevoked)
+        ));
+        // The refresh token is kept, so the client doesn't fall back to the client credentials
+        // flow.
+        assert_eq!(client.refresh_token().await.as_deref(), Some("refresh"));
+    }
+}
@@ -1,23 +1,26 @@
 //! Endpoint functions relating to albums.
 
 use std::fmt::Display;
+use std::time::Instant;
 
 use itertools::Itertools as _;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::chunked_sequence;
-use crate::{Album, Client, Error, Market, Page, Response, TrackSimplified};
+use super::{all_pages, chunked_sequence, remaining_pages};
+use crate::{
+    Album, AlbumSimplified, AsClient, Client, Error, Market, Page, Response, TrackSimplified,
+};
 
 /// Album-related endpoints.
 #[derive(Debug, Clone, Copy)]
-pub struct Albums<'a>(pub &'a Client);
+pub struct Albums<C>(pub C);
 
-impl Albums<'_> {
+impl<C: AsClient> Albums<C> {
     /// Get information about an album.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/albums/get-album/).
     pub async fn get_album(
-        self,
+        &self,
         id: &str,
         market: Option<Market>,
     ) -> Result<Response<Album>, Error> {
@@ -35,14 +38,14 @@ impl Albums<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/albums/get-several-albums/).
     pub async fn get_albums<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
         market: Option<Market>,
     ) -> Result<Response<Vec<Album>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Albums {
             albums: Vec<Album>,
         }
@@ -65,7 +68,7 @@ impl Albums<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/albums/get-albums-tracks/).
     pub async fn get_album_tracks(
-        self,
+        &self,
         id: &str,
         limit: usize,
         offset: usize,
@@ -84,11 +87,83 @@ impl Albums<'_> {
             )
             .await
     }
+
+    /// Find albums by their [UPC](https://en.wikipedia.org/wiki/Universal_Product_Code).
+    ///
+    /// This searches for albums with the given UPC, which may contain hyphens or spaces. Usually
+    /// there is only one match, but all of them are returned.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
+    pub async fn get_by_upc(
+        &self,
+        upc: &str,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<AlbumSimplified>>, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct Results {
+            albums: Page<AlbumSimplified>,
+        }
+
+        let upc: String = upc.chars().filter(char::is_ascii_digit).collect();
+
+        Ok(self
+            .0
+            .send_json::<Results>(self.0.client.get(endpoint!("/v1/search")).query(&(
+                ("q", format!("upc:{}", upc)),
+                ("type", "album"),
+                ("limit", "50"),
+                market.map(Market::query),
+            )))
+            .await?
+            .map(|res| res.albums.items))
+    }
+
+    /// Get all of an album's tracks.
+    ///
+    /// Unlike [`get_album_tracks`](Self::get_album_tracks), this gets every track in the album,
+    /// making multiple requests if necessary.
+    pub async fn get_album_all_tracks(
+        &self,
+        id: &str,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<TrackSimplified>>, Error> {
+        all_pages(50, |limit, offset| {
+            self.get_album_tracks(id, limit, offset, market)
+        })
+        .await
+    }
+}
+
+impl Album {
+    /// Get all of the album's tracks.
+    ///
+    /// The album contains only the first page of its tracks; this returns the tracks in that page
+    /// followed by the tracks in all the remaining pages, which are fetched using `client`.
+    ///
+    /// This function is only available when the `albums` feature of this library is activated,
+    /// and it is activated by default.
+    pub async fn all_tracks(
+        &self,
+        client: &Client,
+        market: Option<Market>,
+    ) -> Result<Vec<TrackSimplified>, Error> {
+        let first = Response {
+            data: self.tracks.clone(),
+            expires: Instant::now(),
+        };
+        let albums = client.albums();
+        Ok(remaining_pages(first, 50, |limit, offset| {
+            albums.get_album_tracks(&self.id, limit, offset, market)
+        })
+        .await?
+        .data)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::endpoints::client;
+    use crate::{Country, Market};
 
     #[tokio::test]
     async fn test_get_album() {
@@ -103,6 +178,19 @@ mod tests {
         assert_eq!(album.artists[0].name, "The Kooks");
         assert_eq!(album.tracks.total, 14);
         assert_eq!(album.tracks.items[0].name, "Seaside");
+        assert_eq!(album.is_playable, None);
+
+        let album = client()
+            .albums()
+            .get_album(
+                "03JPFQvZRnHHysSZrSFmKY",
+                Some(Market::Country(Country::new("GB").unwrap())),
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(album.is_playable, Some(true));
+        assert_eq!(album.restrictions, None);
     }
 
     #[tokio::test]
@@ -134,4 +222,60 @@ mod tests {
         assert_eq!(tracks.items[1].name, "I Won't Hold You Back");
         assert_eq!(tracks.items[2].name, "Good for You");
     }
+
+    #[tokio::test]
+    async fn test_get_by_upc() {
+        let client = client();
+        let album = client
+            .albums()
+            .get_album("03JPFQvZRnHHysSZrSFmKY", None)
+            .await
+            .unwrap()
+            .data;
+        let upc = &album.external_ids["upc"];
+
+        let albums = client.albums().get_by_upc(upc, None).await.unwrap().data;
+        assert!(albums
+            .iter()
+            .any(|found| found.id.as_deref() == Some("03JPFQvZRnHHysSZrSFmKY")));
+    }
+
+    #[tokio::test]
+    async fn test_get_album_all_tracks() {
+        let client = client();
+        let tracks = client
+            .albums()
+            .get_album_all_tracks("62U7xIHcID94o20Of5ea4D", None)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(tracks.len(), 10);
+        assert_eq!(tracks[1].name, "Make Believe");
+
+        let album = client
+            .albums()
+            .get_album("62U7xIHcID94o20Of5ea4D", None)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(album.all_tracks(&client, None).await.unwrap(), tracks);
+    }
+
+    #[tokio::test]
+    async fn test_get_album_cover() {
+        let client = client();
+        let album = client
+            .albums()
+            .get_album("03JPFQvZRnHHysSZrSFmKY", None)
+            .await
+            .unwrap()
+            .data;
+        let cover = client
+            .get_image_for(&album.images, 300)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cover.content_type.as_deref(), Some("image/jpeg"));
+        assert!(!cover.bytes.is_empty());
+    }
 }
@@ -1,22 +1,29 @@
 //! Endpoint functions relating to artists.
 
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::hash::BuildHasher;
+use std::time::Instant;
 
+use futures_util::stream::{self, StreamExt};
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::chunked_sequence;
-use crate::{AlbumGroup, Artist, ArtistsAlbum, Client, Error, Market, Page, Response, Track};
+use crate::{
+    AlbumGroup, Artist, ArtistFollowers, ArtistsAlbum, AsClient, Country, Error, Followers, Market,
+    Page, Response, Track,
+};
 
 /// Artist-related endpoints.
 #[derive(Debug, Clone, Copy)]
-pub struct Artists<'a>(pub &'a Client);
+pub struct Artists<C>(pub C);
 
-impl Artists<'_> {
+impl<C: AsClient> Artists<C> {
     /// Get information about an artist.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-artist/).
-    pub async fn get_artist(self, id: &str) -> Result<Response<Artist>, Error> {
+    pub async fn get_artist(&self, id: &str) -> Result<Response<Artist>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/artists/{}", id)))
             .await
@@ -25,11 +32,11 @@ impl Artists<'_> {
     /// Get information about several artists.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-several-artists/).
-    pub async fn get_artists<I: IntoIterator>(self, ids: I) -> Result<Response<Vec<Artist>>, Error>
+    pub async fn get_artists<I: IntoIterator>(&self, ids: I) -> Result<Response<Vec<Artist>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Artists {
             artists: Vec<Artist>,
         }
@@ -51,6 +58,60 @@ impl Artists<'_> {
         .await
     }
 
+    /// Get the number of followers and popularity of several artists.
+    ///
+    /// This is like [`get_artists`](Self::get_artists), but only the numbers of followers and
+    /// popularities of the artists are kept, in a map from artist ID. This is cheaper when
+    /// tracking those values over time.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-several-artists/).
+    pub async fn get_follower_counts<I: IntoIterator>(
+        &self,
+        ids: I,
+    ) -> Result<Response<HashMap<String, ArtistFollowers>>, Error>
+    where
+        I::Item: Display,
+    {
+        #[derive(Deserialize)]
+        struct Artist {
+            id: String,
+            followers: Followers,
+            popularity: u32,
+        }
+        #[derive(Deserialize)]
+        struct Artists {
+            artists: Vec<Artist>,
+        }
+
+        Ok(chunked_sequence(ids, 50, |mut ids| {
+            let req = self
+                .0
+                .client
+                .get(endpoint!("/v1/artists"))
+                .query(&(("ids", ids.join(",")),));
+            async move {
+                Ok(self
+                    .0
+                    .send_json_unverified::<Artists>(req)
+                    .await?
+                    .map(|res| res.artists))
+            }
+        })
+        .await?
+        .map(|artists| {
+            artists
+                .into_iter()
+                .map(|artist| {
+                    let followers = ArtistFollowers {
+                        followers: artist.followers.total,
+                        popularity: artist.popularity,
+                    };
+                    (artist.id, followers)
+                })
+                .collect()
+        }))
+    }
+
     /// Get an artist's albums.
     ///
     /// The `include_groups` parameter can specify which groups to include (`album`, `single`,
@@ -62,7 +123,7 @@ impl Artists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-artists-albums/).
     pub async fn get_artist_albums(
-        self,
+        &self,
         id: &str,
         include_groups: Option<&[AlbumGroup]>,
         limit: usize,
@@ -83,7 +144,7 @@ impl Artists<'_> {
                                 groups.iter().map(|group| group.as_str()).join(","),
                             )
                         }),
-                        country.map(|m| ("country", m.as_str())),
+                        country.map(|m| ("country", m)),
                     )),
             )
             .await
@@ -96,11 +157,11 @@ impl Artists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-artists-top-tracks/).
     pub async fn get_artist_top(
-        self,
+        &self,
         id: &str,
         market: Market,
     ) -> Result<Response<Vec<Track>>, Error> {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Tracks {
             tracks: Vec<Track>,
         }
@@ -111,19 +172,49 @@ impl Artists<'_> {
                 self.0
                     .client
                     .get(endpoint!("/v1/artists/{}/top-tracks", id))
-                    .query(&(("country", market.as_str()),)),
+                    .query(&(("country", market),)),
             )
             .await?
             .map(|res| res.tracks))
     }
 
+    /// Get an artist's top tracks in each of several markets.
+    ///
+    /// This requests the top tracks once for every market, with up to 8 requests at a time. The
+    /// results can be compared with [`market_exclusive_tracks`].
+    pub async fn get_artist_top_multi(
+        &self,
+        id: &str,
+        markets: &[Country],
+    ) -> Result<Response<HashMap<Country, Vec<Track>>>, Error> {
+        let mut responses = stream::iter(markets)
+            .map(|&market| async move {
+                let response = self.get_artist_top(id, Market::Country(market)).await?;
+                Ok::<_, Error>(response.map(|tracks| (market, tracks)))
+            })
+            .buffer_unordered(8);
+
+        let mut response = Response {
+            data: HashMap::with_capacity(markets.len()),
+            expires: Instant::now(),
+        };
+
+        while let Some(r) = responses.next().await.transpose()? {
+            let (market, tracks) = r.data;
+            response.data.insert(market, tracks);
+            response.expires = r.expires;
+        }
+
+        Ok(response)
+    }
+
     /// Get an artist's related artists.
     ///
     /// These artists are similar in style to the given artist.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/artists/get-related-artists/).
-    pub async fn get_related_artists(self, id: &str) -> Result<Response<Vec<Artist>>, Error> {
-        #[derive(Deserialize)]
+    pub async fn get_related_artists(&self, id: &str) -> Result<Response<Vec<Artist>>, Error> {
+        #[derive(Deserialize, Serialize)]
         struct Artists {
             artists: Vec<Artist>,
         }
@@ -140,12 +231,50 @@ impl Artists<'_> {
     }
 }
 
+/// Find the tracks that are only among the top tracks of one market, from the results of
+/// [`Artists::get_artist_top_multi`].
+///
+/// Tracks are compared by their ID, or by the ID of the original track if they were relinked.
+/// The exclusive tracks of each market are returned in their order in its top tracks; markets
+/// without any are left out.
+#[must_use]
+pub fn market_exclusive_tracks<S: BuildHasher + Default>(
+    top: &HashMap<Country, Vec<Track>, S>,
+) -> HashMap<Country, Vec<&Track>, S> {
+    fn original_id(track: &Track) -> Option<&str> {
+        match &track.linked_from {
+            Some(link) => Some(&link.id),
+            None => track.id.as_deref(),
+        }
+    }
+
+    let mut markets_per_track = HashMap::<&str, usize>::new();
+    for id in top
+        .values()
+        .flat_map(|tracks| tracks.iter().filter_map(original_id).unique())
+    {
+        *markets_per_track.entry(id).or_insert(0) += 1;
+    }
+
+    top.iter()
+        .map(|(&market, tracks)| {
+            let exclusive = tracks
+                .iter()
+                .filter(|track| original_id(track).is_some_and(|id| markets_per_track[id] == 1))
+                .collect::<Vec<_>>();
+            (market, exclusive)
+        })
+        .filter(|(_, exclusive)| !exclusive.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use isocountry::CountryCode;
+    use std::collections::HashMap;
 
+    use super::market_exclusive_tracks;
     use crate::endpoints::client;
-    use crate::{AlbumGroup, Market};
+    use crate::{AlbumGroup, Country, Market, Track, TrackLink};
 
     #[tokio::test]
     async fn test_get_artist() {
@@ -172,6 +301,19 @@ mod tests {
         assert_eq!(artists[1].name, "Rick Astley");
     }
 
+    #[tokio::test]
+    async fn test_get_follower_counts() {
+        let counts = client()
+            .artists()
+            .get_follower_counts(&["0L8ExT028jH3ddEcZwqJJ5", "0gxyHStUsqpMadRV0Di1Qt"])
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(counts.len(), 2);
+        assert!(counts["0L8ExT028jH3ddEcZwqJJ5"].followers > 0);
+        assert!(counts["0gxyHStUsqpMadRV0Di1Qt"].popularity <= 100);
+    }
+
     #[tokio::test]
     async fn test_get_artist_albums() {
         let albums = client()
@@ -181,7 +323,7 @@ mod tests {
                 Some(&[AlbumGroup::Single]),
                 2,
                 1,
-                Some(Market::Country(CountryCode::GBR)),
+                Some(Market::Country(Country::new("GB").unwrap())),
             )
             .await
             .unwrap()
@@ -203,7 +345,10 @@ mod tests {
     async fn test_get_artist_top() {
         let top = client()
             .artists()
-            .get_artist_top("0L8ExT028jH3ddEcZwqJJ5", Market::Country(CountryCode::GBR))
+            .get_artist_top(
+                "0L8ExT028jH3ddEcZwqJJ5",
+                Market::Country(Country::new("GB").unwrap()),
+            )
             .await
             .unwrap()
             .data;
@@ -212,4 +357,44 @@ mod tests {
             .iter()
             .any(|artist| artist.name == "Red Hot Chili Peppers")));
     }
+
+    #[tokio::test]
+    async fn test_get_artist_top_multi() {
+        let markets = [Country::new("GB").unwrap(), Country::new("JP").unwrap()];
+        let top = client()
+            .artists()
+            .get_artist_top_multi("0L8ExT028jH3ddEcZwqJJ5", &markets)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(top.len(), 2);
+        assert!(top.values().all(|tracks| !tracks.is_empty()));
+        let exclusive = market_exclusive_tracks(&top);
+        assert!(exclusive.keys().all(|market| top.contains_key(market)));
+    }
+
+    #[test]
+    fn test_market_exclusive_tracks() {
+        let shared: Track =
+            serde_json::from_str(include_str!("../../tests/fixtures/track.json")).unwrap();
+        let mut exclusive = shared.clone();
+        exclusive.id = Some("0000000000000000000000".to_owned());
+        let mut relinked = shared.clone();
+        relinked.id = Some("1111111111111111111111".to_owned());
+        relinked.linked_from = Some(TrackLink {
+            external_urls: HashMap::new(),
+            id: shared.id.clone().unwrap(),
+            item_type: shared.item_type,
+        });
+
+        let gb = Country::new("GB").unwrap();
+        let jp = Country::new("JP").unwrap();
+        let mut top = HashMap::new();
+        top.insert(gb, vec![shared, exclusive.clone()]);
+        top.insert(jp, vec![relinked]);
+
+        let exclusives = market_exclusive_tracks(&top);
+        assert_eq!(exclusives.len(), 1);
+        assert_eq!(exclusives[&gb], [&exclusive]);
+    }
 }
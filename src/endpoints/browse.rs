@@ -1,22 +1,31 @@
+use std::collections::HashSet;
 use std::fmt::Display;
+use std::iter;
+#[cfg(all(feature = "follow", feature = "artists"))]
+use std::{cmp::Reverse, slice, time::Instant};
 
+#[cfg(all(feature = "follow", feature = "artists"))]
+use chrono::NaiveDate;
 use chrono::{DateTime, Utc};
-use isocountry::CountryCode;
-use isolanguage_1::LanguageCode;
+#[cfg(all(feature = "follow", feature = "artists"))]
+use futures_util::stream::{self, StreamExt};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use super::all_pages;
+#[cfg(all(feature = "follow", feature = "artists"))]
+use crate::{AlbumGroup, ArtistsAlbum};
 use crate::{
-    AlbumSimplified, Category, Client, Error, FeaturedPlaylists, Market, Page, PlaylistSimplified,
-    Recommendations, Response,
+    AlbumSimplified, AsClient, AudioFeatures, Category, CategoryPlaylists, Country, Error,
+    FeaturedPlaylists, Genre, Language, Market, Page, Recommendations, Response,
 };
 
 /// Endpoint functions related to categories, featured playlists, recommendations, and new
 /// releases.
 #[derive(Debug, Clone, Copy)]
-pub struct Browse<'a>(pub &'a Client);
+pub struct Browse<C>(pub C);
 
-impl Browse<'_> {
+impl<C: AsClient> Browse<C> {
     /// Get information about a category.
     ///
     /// If no locale is given or Spotify does not support the given locale, then it will default to
@@ -24,10 +33,10 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-category/).
     pub async fn get_category(
-        self,
+        &self,
         name: &str,
-        locale: Option<(LanguageCode, CountryCode)>,
-        country: Option<CountryCode>,
+        locale: Option<(Language, Country)>,
+        country: Option<Country>,
     ) -> Result<Response<Category>, Error> {
         self.0
             .send_json(
@@ -36,7 +45,7 @@ impl Browse<'_> {
                     .get(endpoint!("/v1/browse/categories/{}", name))
                     .query(&(
                         locale.map(|locale| ("locale", format_language(locale))),
-                        country.map(|c| ("country", c.alpha2())),
+                        country.map(|c| ("country", c)),
                     )),
             )
             .await
@@ -50,13 +59,13 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-list-categories/).
     pub async fn get_categories(
-        self,
+        &self,
         limit: usize,
         offset: usize,
-        locale: Option<(LanguageCode, CountryCode)>,
-        country: Option<CountryCode>,
+        locale: Option<(Language, Country)>,
+        country: Option<Country>,
     ) -> Result<Response<Page<Category>>, Error> {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct CategoryPage {
             categories: Page<Category>,
         }
@@ -68,7 +77,7 @@ impl Browse<'_> {
                     ("limit", limit.to_string()),
                     ("offset", offset.to_string()),
                     locale.map(|l| ("locale", format_language(l))),
-                    country.map(|c| ("country", c.alpha2())),
+                    country.map(|c| ("country", c)),
                 ),
             ))
             .await?
@@ -81,31 +90,24 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-categorys-playlists/).
     pub async fn get_category_playlists(
-        self,
+        &self,
         name: &str,
         limit: usize,
         offset: usize,
-        country: Option<CountryCode>,
-    ) -> Result<Response<Page<PlaylistSimplified>>, Error> {
-        #[derive(Deserialize)]
-        struct Playlists {
-            playlists: Page<PlaylistSimplified>,
-        }
-
-        Ok(self
-            .0
-            .send_json::<Playlists>(
+        country: Option<Country>,
+    ) -> Result<Response<CategoryPlaylists>, Error> {
+        self.0
+            .send_json(
                 self.0
                     .client
                     .get(endpoint!("/v1/browse/categories/{}/playlists", name))
                     .query(&(
                         ("limit", limit.to_string()),
                         ("offset", offset.to_string()),
-                        country.map(|c| ("country", c.alpha2())),
+                        country.map(|c| ("country", c)),
                     )),
             )
-            .await?
-            .map(|res| res.playlists))
+            .await
     }
 
     /// Get featured playlists.
@@ -115,12 +117,12 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-list-featured-playlists/).
     pub async fn get_featured_playlists(
-        self,
+        &self,
         limit: usize,
         offset: usize,
-        locale: Option<(LanguageCode, CountryCode)>,
+        locale: Option<(Language, Country)>,
         time: Option<DateTime<Utc>>,
-        country: Option<CountryCode>,
+        country: Option<Country>,
     ) -> Result<Response<FeaturedPlaylists>, Error> {
         self.0
             .send_json(
@@ -132,7 +134,7 @@ impl Browse<'_> {
                         ("offset", offset.to_string()),
                         locale.map(|l| ("locale", format_language(l))),
                         time.map(|t| ("timestamp", t.to_rfc3339())),
-                        country.map(|c| ("country", c.alpha2())),
+                        country.map(|c| ("country", c)),
                     )),
             )
             .await
@@ -145,12 +147,12 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-list-new-releases/).
     pub async fn get_new_releases(
-        self,
+        &self,
         limit: usize,
         offset: usize,
-        country: Option<CountryCode>,
+        country: Option<Country>,
     ) -> Result<Response<Page<AlbumSimplified>>, Error> {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct NewReleases {
             albums: Page<AlbumSimplified>,
         }
@@ -164,17 +166,120 @@ impl Browse<'_> {
                     .query(&(
                         ("limit", limit.to_string()),
                         ("offset", offset.to_string()),
-                        country.map(|c| ("country", c.alpha2())),
+                        country.map(|c| ("country", c)),
                     )),
             )
             .await?
             .map(|res| res.albums))
     }
 
+    /// Get all new releases.
+    ///
+    /// Unlike [`get_new_releases`](Self::get_new_releases), this gets every new release, making
+    /// multiple requests if necessary. The result can be compared with an earlier one using
+    /// [`new_releases_since`].
+    pub async fn get_all_new_releases(
+        &self,
+        country: Option<Country>,
+    ) -> Result<Response<Vec<AlbumSimplified>>, Error> {
+        all_pages(50, |limit, offset| {
+            self.get_new_releases(limit, offset, country)
+        })
+        .await
+    }
+
+    /// Get the albums and singles released by the artists the current user follows after a date.
+    ///
+    /// Requires `user-follow-read`. This gets all of the followed artists, making multiple
+    /// requests if necessary, and then the 50 most recent albums and 50 most recent singles of
+    /// each artist, for up to 8 artists at a time. Releases on `since` itself are not included,
+    /// and releases by several followed artists are only included once. The releases are sorted
+    /// newest first.
+    ///
+    /// This function is only available when the `follow` and `artists` features of this library
+    /// are activated, and they are activated by default.
+    #[cfg(all(feature = "follow", feature = "artists"))]
+    pub async fn new_releases_from_followed(
+        &self,
+        since: NaiveDate,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<ArtistsAlbum>>, Error> {
+        let mut artists = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self
+                .0
+                .follow()
+                .get_followed_artists(50, after.as_deref())
+                .await?
+                .data;
+            let done = page.items.is_empty() || page.cursors.after.is_none();
+            artists.extend(page.items.into_iter().map(|artist| artist.id));
+            if done {
+                break;
+            }
+            after = page.cursors.after;
+        }
+
+        let artists_endpoints = self.0.artists();
+        let mut responses = stream::iter(
+            artists
+                .iter()
+                .cartesian_product(&[AlbumGroup::Album, AlbumGroup::Single]),
+        )
+        .map(|(artist, group)| {
+            artists_endpoints.get_artist_albums(artist, Some(slice::from_ref(group)), 50, 0, market)
+        })
+        .buffer_unordered(8);
+
+        let mut ids = HashSet::new();
+        let mut response = Response {
+            data: Vec::new(),
+            expires: Instant::now(),
+        };
+        while let Some(r) = responses.next().await.transpose()? {
+            response.data.extend(
+                r.data
+                    .items
+                    .into_iter()
+                    .filter(|album| album.release_date > since && ids.insert(album.id.clone())),
+            );
+            response.expires = r.expires;
+        }
+        response
+            .data
+            .sort_by_key(|album| Reverse(album.release_date));
+
+        Ok(response)
+    }
+
+    /// Get the genres that can be used as seeds for
+    /// [`get_recommendations`](Self::get_recommendations).
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-recommendation-genres/).
+    pub async fn get_genre_seeds(&self) -> Result<Response<Vec<Genre>>, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct Genres {
+            genres: Vec<Genre>,
+        }
+
+        Ok(self
+            .0
+            .send_json::<Genres>(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/recommendations/available-genre-seeds")),
+            )
+            .await?
+            .map(|res| res.genres))
+    }
+
     /// Get recommendations.
     ///
     /// Up to 5 seed values may be provided, that can be distributed in `seed_artists`,
-    /// `seed_genres` and `seed_tracks` in any way. Limit must be in the range [1..100] and this
+    /// `seed_genres` and `seed_tracks` in any way. Genres can be given as [`Genre`]s, such as
+    /// those of an artist; see [`get_genre_seeds`](Self::get_genre_seeds) for the genres that are
+    /// accepted. Limit must be in the range [1..100] and this
     /// target number of tracks may not always be met.
     ///
     /// `attributes` must serialize to a string to string map or sequence of key-value tuples. See
@@ -182,7 +287,7 @@ impl Browse<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-recommendations/).
     pub async fn get_recommendations<AI: IntoIterator, GI: IntoIterator, TI: IntoIterator>(
-        self,
+        &self,
         seed_artists: AI,
         seed_genres: GI,
         seed_tracks: TI,
@@ -211,20 +316,123 @@ impl Browse<'_> {
             )
             .await
     }
+
+    /// Get a "radio" of tracks similar to a track.
+    ///
+    /// This gets the track's audio features, and then gets recommendations seeded with the track
+    /// that target those features, with limited ranges of acousticness, danceability, energy,
+    /// valence and tempo around the track's own. Limit must be in the range [1..100].
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/browse/get-recommendations/).
+    pub async fn radio_for_track(
+        &self,
+        track_id: &str,
+        limit: usize,
+        market: Option<Market>,
+    ) -> Result<Response<Recommendations>, Error> {
+        let features: AudioFeatures = self
+            .0
+            .send_json(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/audio-features/{}", track_id)),
+            )
+            .await?
+            .data;
+
+        self.get_recommendations(
+            iter::empty::<&str>(),
+            iter::empty::<&str>(),
+            iter::once(track_id),
+            &radio_attributes(&features),
+            limit,
+            market,
+        )
+        .await
+    }
+}
+
+/// Build the recommendation attributes for a radio around a track with the given audio features.
+fn radio_attributes(features: &AudioFeatures) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut range = |name: &str, value: f64, min: f64, max: f64| {
+        attributes.push((format!("min_{}", name), min.to_string()));
+        attributes.push((format!("target_{}", name), value.to_string()));
+        attributes.push((format!("max_{}", name), max.to_string()));
+    };
+
+    for &(name, value) in &[
+        ("acousticness", features.acousticness),
+        ("danceability", features.danceability),
+        ("energy", features.energy),
+        ("valence", features.valence),
+    ] {
+        range(
+            name,
+            value,
+            (value - RADIO_RANGE).max(0.0),
+            (value + RADIO_RANGE).min(1.0),
+        );
+    }
+    range(
+        "tempo",
+        features.tempo,
+        features.tempo * (1.0 - RADIO_TEMPO_RANGE),
+        features.tempo * (1.0 + RADIO_TEMPO_RANGE),
+    );
+
+    attributes
+}
+
+/// How far a radio's tracks' acousticness, danceability, energy and valence can be from the seed
+/// track's.
+const RADIO_RANGE: f64 = 0.15;
+/// How far a radio's tracks' tempo can be from the seed track's, as a proportion of its tempo.
+const RADIO_TEMPO_RANGE: f64 = 0.1;
+
+/// Compare two snapshots of the new releases, returning the albums in `new` that are not in `old`.
+///
+/// Albums are compared by their id, and are returned in the order they appear in `new`. This is
+/// useful for detecting newly released albums by periodically calling
+/// [`Browse::get_all_new_releases`].
+#[must_use]
+pub fn new_releases_since<'a>(
+    old: &[AlbumSimplified],
+    new: &'a [AlbumSimplified],
+) -> Vec<&'a AlbumSimplified> {
+    let old_ids: HashSet<_> = old.iter().filter_map(|album| album.id.as_deref()).collect();
+    new.iter()
+        .filter(|album| album.id.as_deref().is_none_or(|id| !old_ids.contains(id)))
+        .collect()
 }
 
-fn format_language(locale: (LanguageCode, CountryCode)) -> String {
-    format!("{}_{}", locale.0.code(), locale.1.alpha2())
+fn format_language(locale: (Language, Country)) -> String {
+    format!("{}_{}", locale.0, locale.1)
 }
 
 #[cfg(test)]
 mod tests {
     use chrono::DateTime;
-    use isocountry::CountryCode;
-    use isolanguage_1::LanguageCode;
 
-    use crate::endpoints::client;
-    use crate::{Market, SeedType};
+    use super::radio_attributes;
+    use crate::endpoints::{client, new_releases_since};
+    use crate::{Album, AudioFeatures, Country, Genre, Language, Market, SeedType};
+
+    #[cfg(all(feature = "follow", feature = "artists"))]
+    #[tokio::test]
+    async fn test_new_releases_from_followed() {
+        let since = chrono::Utc::now().date_naive() - chrono::Duration::days(365);
+        let releases = client()
+            .browse()
+            .new_releases_from_followed(since, Some(Market::FromToken))
+            .await
+            .unwrap()
+            .data;
+        assert!(releases.iter().all(|album| album.release_date > since));
+        assert!(releases
+            .windows(2)
+            .all(|albums| albums[0].release_date >= albums[1].release_date));
+    }
 
     #[tokio::test]
     async fn test_get_category() {
@@ -232,8 +440,8 @@ mod tests {
             .browse()
             .get_category(
                 "pop",
-                Some((LanguageCode::En, CountryCode::GBR)),
-                Some(CountryCode::GBR),
+                Some((Language::new("en").unwrap(), Country::new("GB").unwrap())),
+                Some(Country::new("GB").unwrap()),
             )
             .await
             .unwrap()
@@ -259,10 +467,11 @@ mod tests {
     async fn test_get_category_playlists() {
         let playlists = client()
             .browse()
-            .get_category_playlists("chill", 1, 3, Some(CountryCode::GBR))
+            .get_category_playlists("chill", 1, 3, Some(Country::new("GB").unwrap()))
             .await
             .unwrap()
-            .data;
+            .data
+            .playlists;
         assert_eq!(playlists.limit, 1);
         assert_eq!(playlists.offset, 3);
         assert!(playlists.items.len() <= 1);
@@ -305,6 +514,73 @@ mod tests {
         assert!(releases.items.len() <= 1);
     }
 
+    #[tokio::test]
+    async fn test_get_all_new_releases() {
+        let releases = client()
+            .browse()
+            .get_all_new_releases(Some(Country::new("GB").unwrap()))
+            .await
+            .unwrap()
+            .data;
+        assert!(new_releases_since(&releases, &releases).is_empty());
+        assert_eq!(new_releases_since(&[], &releases).len(), releases.len());
+    }
+
+    #[test]
+    fn test_new_releases_since() {
+        let album: Album =
+            serde_json::from_str(include_str!("../../tests/fixtures/album.json")).unwrap();
+        let old = album.simplify();
+        let mut new = old.clone();
+        new.id = Some("0000000000000000000000".to_owned());
+
+        let since = new_releases_since(std::slice::from_ref(&old), &[old.clone(), new.clone()])
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(since, [new]);
+    }
+
+    #[test]
+    fn test_radio_attributes() {
+        let features: AudioFeatures =
+            serde_json::from_str(include_str!("../../tests/fixtures/audio_features.json")).unwrap();
+        let attributes = radio_attributes(&features);
+        assert_eq!(attributes.len(), 15);
+        for (name, value) in &attributes {
+            let value: f64 = value.parse().unwrap();
+            if !name.ends_with("tempo") {
+                assert!((0.0..=1.0).contains(&value), "{} is {}", name, value);
+            }
+        }
+        assert_eq!(
+            attributes[13],
+            ("target_tempo".to_owned(), features.tempo.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_radio_for_track() {
+        // "Time" by Pink Floyd
+        let radio = client()
+            .browse()
+            .radio_for_track("3TO7bbrUKrOSPGRTB5MeCz", 10, None)
+            .await
+            .unwrap()
+            .data;
+        assert!(radio.tracks.len() <= 10);
+        assert_eq!(radio.seeds.len(), 1);
+        assert_eq!(radio.seeds[0].id, "3TO7bbrUKrOSPGRTB5MeCz");
+    }
+
+    #[tokio::test]
+    async fn test_get_genre_seeds() {
+        let seeds = client().browse().get_genre_seeds().await.unwrap().data;
+        assert!(Genre::new("Hip Hop").is_seed(&seeds));
+        assert!(Genre::new("rock").is_seed(&seeds));
+        assert!(!Genre::new("not a genre").is_seed(&seeds));
+    }
+
     #[tokio::test]
     async fn test_get_recommendations() {
         let recommendations = client()
@@ -319,7 +595,7 @@ mod tests {
                     ("target_popularity", "100"),
                 ],
                 3,
-                Some(Market::Country(CountryCode::GBR)),
+                Some(Market::Country(Country::new("GB").unwrap())),
             )
             .await
             .unwrap()
@@ -1,10 +1,10 @@
 use std::fmt::Display;
 
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::chunked_sequence;
-use crate::{Client, CountryCode, Episode, Error, Response};
+use crate::{AsClient, Country, Episode, Error, Response};
 
 /// Endpoint functions relating to episodes.
 ///
@@ -12,25 +12,25 @@ use crate::{Client, CountryCode, Episode, Error, Response};
 /// provided. If a refresh token is provided and the market parameter is specified, the user's
 /// market will take precedence.
 #[derive(Debug, Clone, Copy)]
-pub struct Episodes<'a>(pub &'a Client);
+pub struct Episodes<C>(pub C);
 
-impl Episodes<'_> {
+impl<C: AsClient> Episodes<C> {
     /// Get information about an episode.
     ///
     /// Reading the user's playback points requires `user-read-playback-position`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/episodes/get-an-episode/).
     pub async fn get_episode(
-        self,
+        &self,
         id: &str,
-        market: Option<CountryCode>,
+        market: Option<Country>,
     ) -> Result<Response<Episode>, Error> {
         self.0
             .send_json(
                 self.0
                     .client
                     .get(endpoint!("/v1/episodes/{}", id))
-                    .query(&(market.map(|c| ("market", c.alpha2())),)),
+                    .query(&(market.map(|c| ("market", c)),)),
             )
             .await
     }
@@ -41,23 +41,24 @@ impl Episodes<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/episodes/get-several-episodes/).
     pub async fn get_episodes<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
-        market: Option<CountryCode>,
+        market: Option<Country>,
     ) -> Result<Response<Vec<Option<Episode>>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Episodes {
             episodes: Vec<Option<Episode>>,
         }
 
         chunked_sequence(ids, 50, |mut ids| {
-            let req = self.0.client.get(endpoint!("/v1/episodes")).query(&(
-                ("ids", ids.join(",")),
-                market.map(|m| ("market", m.alpha2())),
-            ));
+            let req = self
+                .0
+                .client
+                .get(endpoint!("/v1/episodes"))
+                .query(&(("ids", ids.join(",")), market.map(|m| ("market", m))));
             async move {
                 Ok(self
                     .0
@@ -72,15 +73,14 @@ impl Episodes<'_> {
 
 #[cfg(test)]
 mod tests {
-    use isocountry::CountryCode;
-
     use crate::endpoints::client;
+    use crate::Country;
 
     #[tokio::test]
     async fn test_get_episode() {
         let episode = client()
             .episodes()
-            .get_episode("512ojhOuo1ktJprKbVcKyQ", Some(CountryCode::ESP))
+            .get_episode("512ojhOuo1ktJprKbVcKyQ", Some(Country::new("ES").unwrap()))
             .await
             .unwrap()
             .data;
@@ -93,7 +93,7 @@ mod tests {
             .episodes()
             .get_episodes(
                 &["77o6BIVlYM3msb4MMIL1jH", "0Q86acNRm6V9GYx55SXKwf"],
-                Some(CountryCode::CHL),
+                Some(Country::new("CL").unwrap()),
             )
             .await
             .unwrap()
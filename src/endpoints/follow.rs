@@ -1,17 +1,22 @@
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::iter;
+use std::ops::ControlFlow;
 
 use itertools::Itertools;
 use reqwest::header;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{chunked_requests, chunked_sequence};
-use crate::{Artist, Client, CursorPage, Error, Response};
+use super::{
+    chunked_requests_with, chunked_sequence, cursor_pages, no_progress, Annotated, Bulk, Progress,
+};
+use crate::{Artist, AsClient, CursorPage, Error, Response};
 
 /// Endpoint functions relating to following and unfollowing artists, users and playlists.
 #[derive(Debug, Clone, Copy)]
-pub struct Follow<'a>(pub &'a Client);
+pub struct Follow<C>(pub C);
 
-impl Follow<'_> {
+impl<C: AsClient> Follow<C> {
     /// Check if the current user follows some artists.
     ///
     /// Returns vector of bools that is in the same order as the given ids. Requires
@@ -19,7 +24,7 @@ impl Follow<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/check-current-user-follows/).
     pub async fn user_follows_artists<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
     where
@@ -36,6 +41,28 @@ impl Follow<'_> {
         .await
     }
 
+    /// Annotate artists with whether the current user follows them.
+    ///
+    /// This checks all the artists with [`user_follows_artists`](Self::user_follows_artists), and
+    /// returns them in the same order. Requires `user-follow-read`.
+    pub async fn annotate_followed<I: IntoIterator<Item = Artist>>(
+        &self,
+        artists: I,
+    ) -> Result<Response<Vec<Annotated<Artist>>>, Error> {
+        let artists: Vec<Artist> = artists.into_iter().collect();
+        let followed = self
+            .user_follows_artists(artists.iter().map(|artist| &artist.id))
+            .await?;
+
+        Ok(followed.map(|followed| {
+            artists
+                .into_iter()
+                .zip(followed)
+                .map(|(item, annotation)| Annotated { item, annotation })
+                .collect()
+        }))
+    }
+
     /// Check if the current user follows some users.
     ///
     /// Returns vector of bools that is in the same order as the given ids. Requires
@@ -43,7 +70,7 @@ impl Follow<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/check-current-user-follows/).
     pub async fn user_follows_users<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
     where
@@ -68,7 +95,7 @@ impl Follow<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/check-user-following-playlist/).
     pub async fn users_follow_playlist<I: IntoIterator>(
-        self,
+        &self,
         id: &str,
         user_ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
@@ -86,16 +113,63 @@ impl Follow<'_> {
         .await
     }
 
+    /// Check if some users follow a playlist, returning a map from each user ID to whether they
+    /// follow it.
+    ///
+    /// This is the same as [`users_follow_playlist`](Self::users_follow_playlist), but the results
+    /// don't have to be matched up with the user IDs by position.
+    pub async fn users_follow_playlist_map<I: IntoIterator>(
+        &self,
+        id: &str,
+        user_ids: I,
+    ) -> Result<Response<HashMap<String, bool>>, Error>
+    where
+        I::Item: Display,
+    {
+        let user_ids: Vec<String> = user_ids.into_iter().map(|id| id.to_string()).collect();
+        Ok(self
+            .users_follow_playlist(id, &user_ids)
+            .await?
+            .map(|follows| user_ids.into_iter().zip(follows).collect()))
+    }
+
+    /// Check if the current user follows a playlist.
+    ///
+    /// The current user's ID is obtained with [`Client::current_user_id`](crate::Client::current_user_id).
+    /// Checking whether the user privately follows the playlist requires `playlist-read-private`.
+    pub async fn current_user_follows_playlist(&self, id: &str) -> Result<Response<bool>, Error> {
+        let user_id = self.0.current_user_id().await?;
+        Ok(self
+            .users_follow_playlist(id, iter::once(&user_id))
+            .await?
+            .map(|follows| follows.first() == Some(&true)))
+    }
+
     /// Follow artists.
     ///
     /// Requires `user-follow-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/follow-artists-users/).
-    pub async fn follow_artists<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn follow_artists<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.follow_artists_with(ids, Bulk::new()).await
+    }
+
+    /// Follow artists, controlling how the requests are made.
+    ///
+    /// This is like [`follow_artists`](Self::follow_artists), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn follow_artists_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -112,11 +186,26 @@ impl Follow<'_> {
     /// Requires `user-follow-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/follow-artists-users/).
-    pub async fn follow_users<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn follow_users<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        self.follow_users_with(ids, Bulk::new()).await
+    }
+
+    /// Follow users, controlling how the requests are made.
+    ///
+    /// This is like [`follow_users`](Self::follow_users), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn follow_users_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -133,7 +222,7 @@ impl Follow<'_> {
     /// Requires `playlist-modify-public`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/follow-playlist/).
-    pub async fn follow_playlist_public(self, id: &str) -> Result<(), Error> {
+    pub async fn follow_playlist_public(&self, id: &str) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -150,7 +239,7 @@ impl Follow<'_> {
     /// Requires `playlist-modify-private`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/follow-playlist/).
-    pub async fn follow_playlist_private(self, id: &str) -> Result<(), Error> {
+    pub async fn follow_playlist_private(&self, id: &str) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -169,11 +258,11 @@ impl Follow<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/get-followed/).
     pub async fn get_followed_artists(
-        self,
+        &self,
         limit: usize,
         after: Option<&str>,
     ) -> Result<Response<CursorPage<Artist>>, Error> {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Response {
             artists: CursorPage<Artist>,
         }
@@ -189,16 +278,71 @@ impl Follow<'_> {
             .map(|res| res.artists))
     }
 
+    /// Get the current user's followed artists, up to `max_items` of them.
+    ///
+    /// Unlike [`get_followed_artists`](Self::get_followed_artists), this follows the cursors to get
+    /// the pages after the first, one at a time.
+    ///
+    /// Requires `user-follow-read`.
+    pub async fn get_followed_artists_all(
+        &self,
+        max_items: usize,
+    ) -> Result<Response<Vec<Artist>>, Error> {
+        self.get_followed_artists_all_with_progress(max_items, no_progress)
+            .await
+    }
+
+    /// Get the current user's followed artists, up to `max_items` of them, reporting progress.
+    ///
+    /// This is like [`get_followed_artists_all`](Self::get_followed_artists_all), but
+    /// `on_progress` is called after each page of artists, and can cancel the operation by
+    /// returning [`ControlFlow::Break`]. If it is cancelled, the artists got so far are returned.
+    ///
+    /// Requires `user-follow-read`.
+    pub async fn get_followed_artists_all_with_progress(
+        &self,
+        max_items: usize,
+        on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Response<Vec<Artist>>, Error> {
+        cursor_pages(
+            self.0.as_client(),
+            max_items,
+            on_progress,
+            |limit, after| async move {
+                Ok(self
+                    .get_followed_artists(limit, after.as_deref())
+                    .await?
+                    .map(|page| (page.items, page.cursors.after)))
+            },
+        )
+        .await
+    }
+
     /// Unfollow artists.
     ///
     /// Requires `user-follow-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/unfollow-artists-users/).
-    pub async fn unfollow_artists<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn unfollow_artists<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        self.unfollow_artists_with(ids, Bulk::new()).await
+    }
+
+    /// Unfollow artists, controlling how the requests are made.
+    ///
+    /// This is like [`unfollow_artists`](Self::unfollow_artists), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn unfollow_artists_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -215,11 +359,26 @@ impl Follow<'_> {
     /// Requires `user-follow-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/unfollow-artists-users/).
-    pub async fn unfollow_users<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn unfollow_users<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.unfollow_users_with(ids, Bulk::new()).await
+    }
+
+    /// Unfollow users, controlling how the requests are made.
+    ///
+    /// This is like [`unfollow_users`](Self::unfollow_users), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn unfollow_users_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -237,7 +396,7 @@ impl Follow<'_> {
     /// privately you need `playlist-modiy-private`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/follow/unfollow-playlist/).
-    pub async fn unfollow_playlist(self, id: &str) -> Result<(), Error> {
+    pub async fn unfollow_playlist(&self, id: &str) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -284,6 +443,12 @@ mod tests {
 
         // Check by finding in list
         let followed = follow.get_followed_artists(50, None).await.unwrap().data;
+        let annotated = follow
+            .annotate_followed(followed.items.clone())
+            .await
+            .unwrap()
+            .data;
+        assert!(annotated.iter().all(|artist| artist.annotation));
         if followed.total <= 50 {
             for followed_artist in followed_artists {
                 assert!(followed
@@ -299,6 +464,19 @@ mod tests {
             }
         }
 
+        // Check by getting every followed artist
+        let all = follow
+            .get_followed_artists_all(usize::MAX)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(all.len(), followed.total);
+        for followed_artist in followed_artists {
+            assert!(all.iter().any(|artist| artist.id == *followed_artist));
+        }
+        let capped = follow.get_followed_artists_all(1).await.unwrap().data;
+        assert_eq!(capped.len(), 1);
+
         // Restore
         let mut old_followed = Vec::with_capacity(artists.len());
         let mut old_unfollowed = Vec::with_capacity(artists.len());
@@ -330,19 +508,27 @@ mod tests {
             .unwrap();
 
         // Check whether following playlist
-        let id = client
-            .users_profile()
-            .get_current_user()
-            .await
-            .unwrap()
-            .data
-            .id;
+        let id = client.current_user_id().await.unwrap();
         let followers = follow
             .users_follow_playlist("37i9dQZF1DWYBF1dYDPlHw", &["spotify", &id])
             .await
             .unwrap()
             .data;
         assert_eq!(followers, &[false, true]);
+        let followers = follow
+            .users_follow_playlist_map("37i9dQZF1DWYBF1dYDPlHw", &["spotify", &id])
+            .await
+            .unwrap()
+            .data;
+        assert!(!followers["spotify"]);
+        assert!(followers[&id]);
+        assert!(
+            follow
+                .current_user_follows_playlist("37i9dQZF1DWYBF1dYDPlHw")
+                .await
+                .unwrap()
+                .data
+        );
 
         // Unfollow
         follow
@@ -1,15 +1,126 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::ops::ControlFlow;
+use std::time::Instant;
 
+use futures_util::future::{try_join, try_join4};
 use itertools::Itertools;
 
-use super::{chunked_requests, chunked_sequence};
-use crate::{Client, Error, Market, Page, Response, SavedAlbum, SavedShow, SavedTrack};
+use super::{
+    all_pages_with_progress, chunked_requests_with, chunked_sequence, no_progress, Annotated, Bulk,
+    Progress,
+};
+use crate::{
+    Album, AsClient, Error, ItemType, Market, Page, Response, SavedAlbum, SavedShow, SavedTrack,
+    Track,
+};
+
+/// Sort saved albums from most to least recently saved, keeping only the first occurrence of each
+/// album.
+fn order_saved_albums(mut albums: Vec<SavedAlbum>) -> Vec<SavedAlbum> {
+    albums.sort_by_key(|saved| Reverse(saved.added_at));
+    albums
+        .into_iter()
+        .unique_by(|saved| saved.album.id.clone())
+        .collect()
+}
+
+/// Group saved tracks that are the same recording, either because they have the same ISRC or
+/// because they were relinked to the same track. Tracks that aren't duplicated aren't returned.
+fn group_duplicate_tracks(mut tracks: Vec<SavedTrack>) -> Vec<DuplicateTracks> {
+    tracks.sort_by_key(|saved| saved.added_at);
+
+    let mut groups: Vec<Vec<SavedTrack>> = Vec::new();
+    let mut keys: HashMap<(&'static str, String), usize> = HashMap::new();
+    for saved in tracks {
+        let track_keys: Vec<_> = saved
+            .track
+            .id
+            .clone()
+            .map(|id| ("id", id))
+            .into_iter()
+            .chain(
+                saved
+                    .track
+                    .external_ids
+                    .get("isrc")
+                    .map(|isrc| ("isrc", isrc.to_uppercase())),
+            )
+            .collect();
+        if track_keys.is_empty() {
+            continue;
+        }
+
+        let group = if let Some(&group) = track_keys.iter().find_map(|key| keys.get(key)) {
+            groups[group].push(saved);
+            group
+        } else {
+            groups.push(vec![saved]);
+            groups.len() - 1
+        };
+        for key in track_keys {
+            keys.entry(key).or_insert(group);
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            let duplicates = group.split_off(1);
+            DuplicateTracks {
+                original: group.remove(0),
+                duplicates,
+            }
+        })
+        .collect()
+}
+
+/// Get the ID a saved track was saved with, which is different to its ID if it was relinked.
+fn saved_track_id(saved: &SavedTrack) -> Option<&str> {
+    match &saved.track.linked_from {
+        Some(link) => Some(&link.id),
+        None => saved.track.id.as_deref(),
+    }
+}
+
+/// Whether none of an album is playable in the market it was requested for.
+fn album_unavailable(album: &Album) -> bool {
+    album.is_playable == Some(false)
+        || (!album.tracks.items.is_empty()
+            && album
+                .tracks
+                .items
+                .iter()
+                .all(|track| track.is_playable == Some(false) || track.restrictions.is_some()))
+}
+
+/// Saved tracks that are the same recording, found by [`Library::find_duplicate_tracks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateTracks {
+    /// The track that was saved first.
+    pub original: SavedTrack,
+    /// The other tracks, from least to most recently saved.
+    pub duplicates: Vec<SavedTrack>,
+}
+
+/// What was unsaved by [`Library::clean_up`], or what would have been if it was a dry run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// The duplicated tracks. Only the [`duplicates`](DuplicateTracks::duplicates) are unsaved.
+    pub duplicate_tracks: Vec<DuplicateTracks>,
+    /// The albums that are entirely unavailable.
+    pub unavailable_albums: Vec<SavedAlbum>,
+    /// Whether this was a dry run, in which case nothing was unsaved.
+    pub dry_run: bool,
+}
 
 /// Endpoints relating to saving albums and tracks.
 #[derive(Debug, Clone, Copy)]
-pub struct Library<'a>(pub &'a Client);
+pub struct Library<C>(pub C);
 
-impl Library<'_> {
+impl<C: AsClient> Library<C> {
     /// Check if the current user has saved some albums.
     ///
     /// Returns vector of bools that is in the same order as the given ids, telling whether the user
@@ -17,7 +128,7 @@ impl Library<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/check-users-saved-albums/).
     pub async fn user_saved_albums<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
     where
@@ -41,7 +152,7 @@ impl Library<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/check-users-saved-shows/).
     pub async fn user_saved_shows<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
     where
@@ -58,6 +169,120 @@ impl Library<'_> {
         .await
     }
 
+    /// Check if the current user has saved some episodes.
+    ///
+    /// Returns vector of bools that is in the same order as the given ids, telling whether the user
+    /// has saved each episode. Requires `user-library-read`.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/#endpoint-check-users-saved-episodes).
+    pub async fn user_saved_episodes<I: IntoIterator>(
+        &self,
+        ids: I,
+    ) -> Result<Response<Vec<bool>>, Error>
+    where
+        I::Item: Display,
+    {
+        chunked_sequence(ids, 50, |mut ids| {
+            let req = self
+                .0
+                .client
+                .get(endpoint!("/v1/me/episodes/contains"))
+                .query(&(("ids", ids.join(",")),));
+            async move { self.0.send_json(req).await }
+        })
+        .await
+    }
+
+    /// Check if the current user has saved some items of any type.
+    ///
+    /// This dispatches the items to [`user_saved_albums`](Self::user_saved_albums),
+    /// [`user_saved_episodes`](Self::user_saved_episodes),
+    /// [`user_saved_shows`](Self::user_saved_shows) and
+    /// [`user_saved_tracks`](Self::user_saved_tracks) depending on their type, making the requests
+    /// concurrently. The returned map tells whether the user has saved each item. Items of other
+    /// types can't be saved, so they are not included in the map. Requires `user-library-read`.
+    pub async fn contains<I: IntoIterator<Item = (ItemType, String)>>(
+        &self,
+        items: I,
+    ) -> Result<Response<HashMap<(ItemType, String), bool>>, Error> {
+        let mut albums = Vec::new();
+        let mut episodes = Vec::new();
+        let mut shows = Vec::new();
+        let mut tracks = Vec::new();
+        for (item_type, id) in items {
+            match item_type {
+                ItemType::Album => albums.push(id),
+                ItemType::Episode => episodes.push(id),
+                ItemType::Show => shows.push(id),
+                ItemType::Track => tracks.push(id),
+                ItemType::Artist
+                | ItemType::Playlist
+                | ItemType::Collection
+                | ItemType::User
+                | ItemType::Other(_) => {}
+            }
+        }
+
+        let (saved_albums, saved_episodes, saved_shows, saved_tracks) = try_join4(
+            self.user_saved_albums(&albums),
+            self.user_saved_episodes(&episodes),
+            self.user_saved_shows(&shows),
+            self.user_saved_tracks(&tracks),
+        )
+        .await?;
+
+        let expires = [&saved_albums, &saved_episodes, &saved_shows, &saved_tracks]
+            .iter()
+            .filter(|response| !response.data.is_empty())
+            .map(|response| response.expires)
+            .min()
+            .unwrap_or_else(Instant::now);
+
+        let mut saved = HashMap::new();
+        let mut insert = |item_type: ItemType, ids: Vec<String>, response: Response<Vec<bool>>| {
+            saved.extend(
+                ids.into_iter()
+                    .zip(response.data)
+                    .map(|(id, is_saved)| ((item_type.clone(), id), is_saved)),
+            );
+        };
+        insert(ItemType::Album, albums, saved_albums);
+        insert(ItemType::Episode, episodes, saved_episodes);
+        insert(ItemType::Show, shows, saved_shows);
+        insert(ItemType::Track, tracks, saved_tracks);
+
+        Ok(Response {
+            data: saved,
+            expires,
+        })
+    }
+
+    /// Annotate tracks with whether the current user has saved them.
+    ///
+    /// This checks all the tracks with [`user_saved_tracks`](Self::user_saved_tracks), and returns
+    /// them in the same order. Local tracks can't be saved, so they are always annotated with
+    /// `false`. Requires `user-library-read`.
+    pub async fn annotate_saved<I: IntoIterator<Item = Track>>(
+        &self,
+        tracks: I,
+    ) -> Result<Response<Vec<Annotated<Track>>>, Error> {
+        let tracks: Vec<Track> = tracks.into_iter().collect();
+        let saved = self
+            .user_saved_tracks(tracks.iter().filter_map(|track| track.id.as_ref()))
+            .await?;
+
+        Ok(saved.map(|saved| {
+            let mut saved = saved.into_iter();
+            tracks
+                .into_iter()
+                .map(|track| Annotated {
+                    annotation: track.id.is_some() && saved.next().unwrap_or(false),
+                    item: track,
+                })
+                .collect()
+        }))
+    }
+
     /// Check if the current user has saved some tracks.
     ///
     /// Returns vector of bools that is in the same order as the given ids, telling whether the user
@@ -65,7 +290,7 @@ impl Library<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/check-users-saved-tracks/).
     pub async fn user_saved_tracks<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<bool>>, Error>
     where
@@ -84,31 +309,187 @@ impl Library<'_> {
 
     /// Get the current user's saved albums.
     ///
-    /// Requires `user-library-read`. Limit must be in the range [1..50].
+    /// Requires `user-library-read`. Limit must be in the range [1..50]. If the market is a
+    /// country, each album's [`market`](SavedAlbum::market) is set to it.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/get-users-saved-albums/).
     pub async fn get_saved_albums(
-        self,
+        &self,
         limit: usize,
         offset: usize,
         market: Option<Market>,
     ) -> Result<Response<Page<SavedAlbum>>, Error> {
-        self.0
-            .send_json(self.0.client.get(endpoint!("/v1/me/albums")).query(&(
+        let country = match market {
+            Some(Market::Country(country)) => Some(country),
+            _ => None,
+        };
+        Ok(self
+            .0
+            .send_json::<Page<SavedAlbum>>(self.0.client.get(endpoint!("/v1/me/albums")).query(&(
                 ("limit", limit.to_string()),
                 ("offset", offset.to_string()),
                 market.map(Market::query),
             )))
+            .await?
+            .map(|mut page| {
+                for saved in &mut page.items {
+                    saved.market = country;
+                }
+                page
+            }))
+    }
+
+    /// Get all of the current user's saved albums.
+    ///
+    /// Unlike [`get_saved_albums`](Self::get_saved_albums), this gets every saved album, making
+    /// multiple requests if necessary. The albums are ordered from most to least recently saved,
+    /// and if the library changes while the pages are being requested any album that appears in
+    /// more than one page is only returned once.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn get_saved_albums_all(
+        &self,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<SavedAlbum>>, Error> {
+        self.get_saved_albums_all_with_progress(market, no_progress)
+            .await
+    }
+
+    /// Get all of the current user's saved albums, reporting progress.
+    ///
+    /// This is like [`get_saved_albums_all`](Self::get_saved_albums_all), but `on_progress` is
+    /// called after each page of albums, and can cancel the operation by returning
+    /// [`ControlFlow::Break`]. If it is cancelled, the albums got so far are returned.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn get_saved_albums_all_with_progress(
+        &self,
+        market: Option<Market>,
+        on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Response<Vec<SavedAlbum>>, Error> {
+        Ok(all_pages_with_progress(50, on_progress, |limit, offset| {
+            self.get_saved_albums(limit, offset, market)
+        })
+        .await?
+        .map(order_saved_albums))
+    }
+
+    /// Get all of the current user's saved tracks.
+    ///
+    /// Unlike [`get_saved_tracks`](Self::get_saved_tracks), this gets every saved track, making
+    /// multiple requests if necessary.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn get_saved_tracks_all(
+        &self,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<SavedTrack>>, Error> {
+        self.get_saved_tracks_all_with_progress(market, no_progress)
             .await
     }
 
+    /// Get all of the current user's saved tracks, reporting progress.
+    ///
+    /// This is like [`get_saved_tracks_all`](Self::get_saved_tracks_all), but `on_progress` is
+    /// called after each page of tracks, and can cancel the operation by returning
+    /// [`ControlFlow::Break`]. If it is cancelled, the tracks got so far are returned.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn get_saved_tracks_all_with_progress(
+        &self,
+        market: Option<Market>,
+        on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Response<Vec<SavedTrack>>, Error> {
+        all_pages_with_progress(50, on_progress, |limit, offset| {
+            self.get_saved_tracks(limit, offset, market)
+        })
+        .await
+    }
+
+    /// Find saved tracks that are the same recording as other saved tracks.
+    ///
+    /// Tracks are the same recording if they have the same
+    /// [ISRC](https://en.wikipedia.org/wiki/International_Standard_Recording_Code), or if [track
+    /// relinking](https://developer.spotify.com/documentation/general/guides/track-relinking-guide/)
+    /// in the market replaced them with the same track. Local tracks are never duplicates.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn find_duplicate_tracks(
+        &self,
+        market: Market,
+    ) -> Result<Response<Vec<DuplicateTracks>>, Error> {
+        Ok(self
+            .get_saved_tracks_all(Some(market))
+            .await?
+            .map(group_duplicate_tracks))
+    }
+
+    /// Find saved albums that are entirely unavailable in a market.
+    ///
+    /// An album is unavailable if Spotify says it isn't playable, or none of its tracks are
+    /// playable. Only the first page of each album's tracks is checked, which is the first 50
+    /// tracks.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn find_unavailable_albums(
+        &self,
+        market: Market,
+    ) -> Result<Response<Vec<SavedAlbum>>, Error> {
+        Ok(self
+            .get_saved_albums_all(Some(market))
+            .await?
+            .map(|albums| {
+                albums
+                    .into_iter()
+                    .filter(|saved| album_unavailable(&saved.album))
+                    .collect()
+            }))
+    }
+
+    /// Unsave duplicate tracks and unavailable albums from the current user's library.
+    ///
+    /// This finds the tracks with [`find_duplicate_tracks`](Self::find_duplicate_tracks) and the
+    /// albums with [`find_unavailable_albums`](Self::find_unavailable_albums), and then unsaves
+    /// them unless `dry_run` is set. Of each group of duplicated tracks, the one that was saved
+    /// first is kept.
+    ///
+    /// Requires `user-library-read`, and `user-library-modify` if it isn't a dry run.
+    pub async fn clean_up(&self, market: Market, dry_run: bool) -> Result<CleanupReport, Error> {
+        let (duplicate_tracks, unavailable_albums) = try_join(
+            self.find_duplicate_tracks(market),
+            self.find_unavailable_albums(market),
+        )
+        .await?;
+        let report = CleanupReport {
+            duplicate_tracks: duplicate_tracks.data,
+            unavailable_albums: unavailable_albums.data,
+            dry_run,
+        };
+
+        if !dry_run {
+            let tracks = report
+                .duplicate_tracks
+                .iter()
+                .flat_map(|group| &group.duplicates)
+                .filter_map(saved_track_id)
+                .unique();
+            let albums = report
+                .unavailable_albums
+                .iter()
+                .map(|saved| &saved.album.id);
+            try_join(self.unsave_tracks(tracks), self.unsave_albums(albums)).await?;
+        }
+
+        Ok(report)
+    }
+
     /// Get the current user's saved shows.
     ///
     /// Requires `user-library-read`. Limit must be in the range [1..50].
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/get-users-saved-shows/).
     pub async fn get_saved_shows(
-        self,
+        &self,
         limit: usize,
         offset: usize,
     ) -> Result<Response<Page<SavedShow>>, Error> {
@@ -128,7 +509,7 @@ impl Library<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/get-users-saved-tracks/).
     pub async fn get_saved_tracks(
-        self,
+        &self,
         limit: usize,
         offset: usize,
         market: Option<Market>,
@@ -147,11 +528,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/remove-albums-user/).
-    pub async fn unsave_albums<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn unsave_albums<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.unsave_albums_with(ids, Bulk::new()).await
+    }
+
+    /// Unsave some of the current user's saved albums, controlling how the requests are made.
+    ///
+    /// This is like [`unsave_albums`](Self::unsave_albums), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn unsave_albums_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -168,11 +564,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/remove-shows-user/).
-    pub async fn unsave_shows<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn unsave_shows<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.unsave_shows_with(ids, Bulk::new()).await
+    }
+
+    /// Unsave some of the current user's saved shows, controlling how the requests are made.
+    ///
+    /// This is like [`unsave_shows`](Self::unsave_shows), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn unsave_shows_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -189,11 +600,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/remove-tracks-user/).
-    pub async fn unsave_tracks<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn unsave_tracks<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.unsave_tracks_with(ids, Bulk::new()).await
+    }
+
+    /// Unsave some of the current user's saved tracks, controlling how the requests are made.
+    ///
+    /// This is like [`unsave_tracks`](Self::unsave_tracks), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn unsave_tracks_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -210,11 +636,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/save-albums-user/).
-    pub async fn save_albums<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn save_albums<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        self.save_albums_with(ids, Bulk::new()).await
+    }
+
+    /// Save albums for the current user, controlling how the requests are made.
+    ///
+    /// This is like [`save_albums`](Self::save_albums), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn save_albums_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -231,11 +672,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/save-shows-user/).
-    pub async fn save_shows<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn save_shows<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.save_shows_with(ids, Bulk::new()).await
+    }
+
+    /// Save shows for the current user, controlling how the requests are made.
+    ///
+    /// This is like [`save_shows`](Self::save_shows), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn save_shows_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -252,11 +708,26 @@ impl Library<'_> {
     /// Requires `user-library-modify`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/library/save-albums-user/).
-    pub async fn save_tracks<I: IntoIterator>(self, ids: I) -> Result<(), Error>
+    pub async fn save_tracks<I: IntoIterator>(&self, ids: I) -> Result<(), Error>
     where
         I::Item: Display,
     {
-        chunked_requests(ids, 50, |mut ids| {
+        self.save_tracks_with(ids, Bulk::new()).await
+    }
+
+    /// Save tracks for the current user, controlling how the requests are made.
+    ///
+    /// This is like [`save_tracks`](Self::save_tracks), but the requests are made
+    /// as configured by `bulk`, which can limit how many are made at once and report progress.
+    pub async fn save_tracks_with<I: IntoIterator>(
+        &self,
+        ids: I,
+        bulk: Bulk<'_>,
+    ) -> Result<(), Error>
+    where
+        I::Item: Display,
+    {
+        chunked_requests_with(ids, 50, bulk, |mut ids| {
             let req = self
                 .0
                 .client
@@ -271,7 +742,156 @@ impl Library<'_> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::iter;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{album_unavailable, group_duplicate_tracks, order_saved_albums, saved_track_id};
     use crate::endpoints::client;
+    use crate::{
+        Album, Country, ItemType, Market, SavedAlbum, SavedTrack, Timestamp, Track, TrackLink,
+        TypeTrack,
+    };
+
+    /// Get midnight of a day in January 2020.
+    fn day(day: u32) -> Timestamp {
+        Utc.with_ymd_and_hms(2020, 1, day + 1, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn saved_albums_order() {
+        let album: Album =
+            serde_json::from_str(include_str!("../../tests/fixtures/album.json")).unwrap();
+        let mut other = album.clone();
+        other.id = "other".to_owned();
+        let saved = |album: &Album, days| SavedAlbum {
+            added_at: day(days),
+            album: album.clone(),
+            market: None,
+        };
+
+        let ordered =
+            order_saved_albums(vec![saved(&album, 1), saved(&other, 2), saved(&album, 0)]);
+        assert_eq!(ordered, vec![saved(&other, 2), saved(&album, 1)]);
+    }
+
+    #[test]
+    fn duplicate_tracks() {
+        let track: Track =
+            serde_json::from_str(include_str!("../../tests/fixtures/track.json")).unwrap();
+        let saved = |track: &Track, days| SavedTrack {
+            added_at: day(days),
+            track: track.clone(),
+        };
+
+        // Same ISRC, different ID.
+        let mut remaster = track.clone();
+        remaster.id = Some("remaster".to_owned());
+        // Relinked to the same track as the original.
+        let mut relinked = track.clone();
+        relinked.external_ids.clear();
+        relinked.linked_from = Some(TrackLink {
+            external_urls: HashMap::new(),
+            id: "relinked".to_owned(),
+            item_type: TypeTrack,
+        });
+        // Unrelated.
+        let mut other = track.clone();
+        other.id = Some("other".to_owned());
+        other.external_ids.clear();
+        // Local tracks are never duplicates.
+        let mut local = other.clone();
+        local.id = None;
+
+        let groups = group_duplicate_tracks(vec![
+            saved(&relinked, 3),
+            saved(&remaster, 1),
+            saved(&track, 0),
+            saved(&other, 2),
+            saved(&local, 4),
+            saved(&local, 5),
+        ]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].original, saved(&track, 0));
+        assert_eq!(
+            groups[0].duplicates,
+            vec![saved(&remaster, 1), saved(&relinked, 3)]
+        );
+        let ids: Vec<_> = groups[0]
+            .duplicates
+            .iter()
+            .filter_map(saved_track_id)
+            .collect();
+        assert_eq!(ids, ["remaster", "relinked"]);
+    }
+
+    #[test]
+    fn unavailable_albums() {
+        let mut album: Album =
+            serde_json::from_str(include_str!("../../tests/fixtures/album.json")).unwrap();
+        album.is_playable = None;
+        for track in &mut album.tracks.items {
+            track.is_playable = Some(true);
+            track.restrictions = None;
+        }
+        assert!(!album_unavailable(&album));
+
+        album.tracks.items[0].is_playable = Some(false);
+        assert!(!album_unavailable(&album));
+
+        for track in &mut album.tracks.items {
+            track.is_playable = Some(false);
+        }
+        assert!(album_unavailable(&album));
+
+        album.tracks.items.clear();
+        assert!(!album_unavailable(&album));
+        album.is_playable = Some(false);
+        assert!(album_unavailable(&album));
+    }
+
+    #[tokio::test]
+    async fn test_clean_up_dry_run() {
+        let client = client();
+        let library = client.library();
+        let market = Market::Country(Country::new("GB").unwrap());
+
+        let saved = library.get_saved_tracks_all(None).await.unwrap().data;
+        let report = library.clean_up(market, true).await.unwrap();
+        assert!(report.dry_run);
+        for group in &report.duplicate_tracks {
+            assert!(!group.duplicates.is_empty());
+            for duplicate in &group.duplicates {
+                assert!(duplicate.added_at >= group.original.added_at);
+            }
+        }
+
+        // Nothing was unsaved.
+        let after = library.get_saved_tracks_all(None).await.unwrap().data;
+        assert_eq!(saved.len(), after.len());
+    }
+
+    #[tokio::test]
+    async fn test_get_saved_albums_all() {
+        let client = client();
+        let library = client.library();
+        let country = Country::new("GB").unwrap();
+
+        let all = library
+            .get_saved_albums_all(Some(Market::Country(country)))
+            .await
+            .unwrap()
+            .data;
+        assert!(all
+            .windows(2)
+            .all(|pair| pair[0].added_at >= pair[1].added_at));
+        assert!(all.iter().all(|saved| saved.market == Some(country)));
+
+        let page = library.get_saved_albums(50, 0, None).await.unwrap().data;
+        assert_eq!(page.total, all.len());
+        assert!(page.items.iter().all(|saved| saved.market.is_none()));
+    }
 
     #[tokio::test]
     async fn test_save_albums() {
@@ -298,6 +918,25 @@ mod tests {
         let check = library.user_saved_albums(albums).await.unwrap().data;
         let (save_check, unsave_check) = check.split_at(split);
         assert!(save_check.iter().all(|&saved| saved));
+
+        // Check with the combined endpoint
+        let contains = library
+            .contains(
+                albums
+                    .iter()
+                    .map(|&id| (ItemType::Album, id.to_owned()))
+                    .chain(iter::once((
+                        ItemType::Artist,
+                        "0PFtn5NtBbbUNbU9EAmIWF".to_owned(),
+                    ))),
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(contains.len(), albums.len());
+        for (&id, &saved) in albums.iter().zip(&check) {
+            assert_eq!(contains[&(ItemType::Album, id.to_owned())], saved);
+        }
         assert!(unsave_check.iter().all(|&saved| !saved));
 
         // Check by finding in list
@@ -413,6 +1052,15 @@ mod tests {
 
         // Check by finding in list, only if it has them all
         let saved = library.get_saved_tracks(50, 0, None).await.unwrap().data;
+        let annotated = library
+            .annotate_saved(saved.items.iter().map(|saved| saved.track.clone()))
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(annotated.len(), saved.items.len());
+        assert!(annotated
+            .iter()
+            .all(|track| track.annotation || track.item.is_local));
         if saved.total <= 50 {
             for saved_track in saved_tracks {
                 assert!(saved
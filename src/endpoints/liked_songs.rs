@@ -0,0 +1,132 @@
+use std::fmt::Display;
+
+use super::{all_pages, Library};
+#[cfg(feature = "player")]
+use super::{Play, Player};
+use crate::{util, AsClient, Client, Error, ItemType, Market, Page, Response, SavedTrack};
+
+/// The current user's Liked Songs, obtained from [`Library::liked_songs`].
+///
+/// Liked Songs behaves like a playlist in Spotify's apps, but it isn't one: it can't be got with
+/// the playlist endpoints, and its items are the user's saved tracks. It can still be played as a
+/// playback context, of type [`ItemType::Collection`]. This brings the endpoints and the context
+/// together.
+#[derive(Debug, Clone, Copy)]
+pub struct LikedSongs<C>(pub C);
+
+impl<C: AsClient> Library<C> {
+    /// Get the current user's Liked Songs.
+    #[must_use]
+    pub fn liked_songs(&self) -> LikedSongs<&Client> {
+        LikedSongs(self.0.as_client())
+    }
+}
+
+impl<C: AsClient> LikedSongs<C> {
+    /// Get a page of the songs, from most to least recently liked.
+    ///
+    /// Requires `user-library-read`. Limit must be in the range [1..50]. This is the same as
+    /// [`Library::get_saved_tracks`].
+    pub async fn items(
+        &self,
+        limit: usize,
+        offset: usize,
+        market: Option<Market>,
+    ) -> Result<Response<Page<SavedTrack>>, Error> {
+        Library(self.0.as_client())
+            .get_saved_tracks(limit, offset, market)
+            .await
+    }
+
+    /// Get all of the songs, from most to least recently liked.
+    ///
+    /// Requires `user-library-read`. This is the same as [`Library::get_saved_tracks_all`].
+    pub async fn items_all(
+        &self,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<SavedTrack>>, Error> {
+        all_pages(50, |limit, offset| self.items(limit, offset, market)).await
+    }
+
+    /// Get the number of songs.
+    ///
+    /// Requires `user-library-read`.
+    pub async fn total(&self) -> Result<Response<usize>, Error> {
+        Ok(self.items(1, 0, None).await?.map(|page| page.total))
+    }
+
+    /// Check whether some tracks are liked.
+    ///
+    /// Requires `user-library-read`. This is the same as [`Library::user_saved_tracks`].
+    pub async fn contains<I: IntoIterator>(&self, ids: I) -> Result<Response<Vec<bool>>, Error>
+    where
+        I::Item: Display,
+    {
+        Library(self.0.as_client()).user_saved_tracks(ids).await
+    }
+
+    /// Get the URI of the songs as a playback context, such as
+    /// `spotify:user:wizzler:collection`.
+    ///
+    /// This gets the current user's ID with [`Client::current_user_id`].
+    pub async fn uri(&self) -> Result<String, Error> {
+        let user_id = self.0.current_user_id().await?;
+        Ok(util::Uri::context(&ItemType::Collection, &user_id).to_string())
+    }
+
+    /// Start playing the songs, at the 0-indexed position `offset`.
+    ///
+    /// Requires `user-modify-playback-state`. This action completes asynchronously, like
+    /// [`Player::play`]. It is only available when the `player` feature of this library is
+    /// activated.
+    #[cfg(feature = "player")]
+    pub async fn play(&self, offset: usize, device_id: Option<&str>) -> Result<(), Error> {
+        let user_id = self.0.current_user_id().await?;
+        Player(self.0.as_client())
+            .play(
+                Some(Play::<'_, &[u8]>::Context(
+                    ItemType::Collection,
+                    &user_id,
+                    offset,
+                )),
+                None,
+                device_id,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::endpoints::client;
+
+    #[tokio::test]
+    async fn test() {
+        let client = client();
+        let library = client.library();
+        let liked_songs = library.liked_songs();
+
+        let total = liked_songs.total().await.unwrap().data;
+        let page = library.get_saved_tracks(50, 0, None).await.unwrap().data;
+        assert_eq!(total, page.total);
+        assert_eq!(
+            liked_songs
+                .items(50, 0, None)
+                .await
+                .unwrap()
+                .data
+                .items
+                .len(),
+            page.items.len()
+        );
+
+        let uri = liked_songs.uri().await.unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "spotify:user:{}:collection",
+                client.current_user_id().await.unwrap()
+            )
+        );
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! These types are transparent, short-lived wrappers around `Client`. They avoid having an
 //! enormous number of methods on the `Client` itself. They can be created from methods on
-//! `Client`, so you generally won't ever need to name them.
+//! `Client`, so you generally won't ever need to name them. If you need to store one, it can
+//! own an `Arc<Client>` instead of borrowing the client; see [`AsClient`].
 //!
 //! # Common Parameters
 //!
@@ -13,33 +14,75 @@
 //! | `id(s)` | The [Spotify ID(s)](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids) of the required resource. |
 //! | `country` | Limits the request to one particular country, so that resources not available in the country will not appear in the results. |
 //! | `market` | Limits the request to one particular country, and applies [Track Relinking](https://developer.spotify.com/documentation/general/guides/track-relinking-guide/). |
-//! | `locale` | The language of the response. It consists of an ISO-639 language code and an ISO-3166 country code (for, example, En and GBR is British English). |
+//! | `locale` | The language of the response. It consists of an ISO-639 language code and an ISO-3166 country code (for, example, en and GB is British English). |
 //! | `limit` | When the function returns a [`Page`](crate::Page), [`CursorPage`](crate::CursorPage) or [`TwoWayCursorPage`](crate::TwoWayCursorPage), this determines the maximum length of the page. |
 //! | `offset` | When the function returns a [`Page`](crate::Page), this determines what index in the larger list the page starts at. |
 //! | `cursor`, `before` and `after` | When the function returns a [`CursorPage`](crate::CursorPage) or [`TwoWayCursorPage`](crate::TwoWayCursorPage), this determines to give the next (`cursor` or `after`) or previous (`before`) page. |
 #![allow(clippy::missing_errors_doc)]
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
 use std::future::Future;
 use std::iter;
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "follow",
+    feature = "library",
+    feature = "player",
+    feature = "playlists",
+    feature = "search"
+))]
+use std::ops::ControlFlow;
+use std::ops::Deref;
+use std::str::FromStr;
 use std::time::Instant;
 
-use futures_util::stream::{FuturesOrdered, FuturesUnordered, StreamExt, TryStreamExt};
-use isocountry::CountryCode;
+use futures_util::stream::{self, FuturesOrdered, FuturesUnordered, StreamExt, TryStreamExt};
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::{Client, Error, Response};
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "library",
+    feature = "playlists",
+    feature = "search"
+))]
+use crate::Page;
+use crate::{
+    util, Album, AnyItem, Client, Context, Country, Episode, Error, ItemType, ParseEnumError,
+    Playlist, Response, Show,
+};
 
+#[cfg(feature = "albums")]
 pub use albums::*;
+#[cfg(feature = "artists")]
 pub use artists::*;
+#[cfg(feature = "browse")]
 pub use browse::*;
+#[cfg(feature = "episodes")]
 pub use episodes::*;
+#[cfg(feature = "follow")]
 pub use follow::*;
+#[cfg(feature = "library")]
 pub use library::*;
+#[cfg(feature = "library")]
+pub use liked_songs::*;
+#[cfg(feature = "personalization")]
 pub use personalization::*;
+#[cfg(feature = "player")]
 pub use player::*;
+#[cfg(feature = "playlists")]
 pub use playlists::*;
+#[cfg(feature = "search")]
 pub use search::*;
+#[cfg(feature = "shows")]
 pub use shows::*;
+#[cfg(feature = "tracks")]
 pub use tracks::*;
+#[cfg(feature = "users-profile")]
 pub use users_profile::*;
 
 macro_rules! endpoint {
@@ -51,121 +94,389 @@ macro_rules! endpoint {
     };
 }
 
+#[cfg(feature = "albums")]
 mod albums;
+#[cfg(feature = "artists")]
 mod artists;
+#[cfg(feature = "browse")]
 mod browse;
+#[cfg(feature = "episodes")]
 mod episodes;
+#[cfg(feature = "follow")]
 mod follow;
+#[cfg(feature = "library")]
 mod library;
+#[cfg(feature = "library")]
+mod liked_songs;
+#[cfg(feature = "personalization")]
 mod personalization;
+#[cfg(feature = "player")]
 mod player;
+#[cfg(feature = "playlists")]
 mod playlists;
+#[cfg(feature = "search")]
 mod search;
+#[cfg(feature = "shows")]
 mod shows;
+#[cfg(feature = "tracks")]
 mod tracks;
+#[cfg(feature = "users-profile")]
 mod users_profile;
 
 /// Endpoint function namespaces.
 impl Client {
     /// Album-related endpoints.
+    #[cfg(feature = "albums")]
     #[must_use]
-    pub const fn albums(&self) -> Albums<'_> {
+    pub const fn albums(&self) -> Albums<&Self> {
         Albums(self)
     }
 
     /// Artist-related endpoints.
+    #[cfg(feature = "artists")]
     #[must_use]
-    pub const fn artists(&self) -> Artists<'_> {
+    pub const fn artists(&self) -> Artists<&Self> {
         Artists(self)
     }
 
     /// Endpoint functions related to categories, featured playlists, recommendations, and new
     /// releases.
+    #[cfg(feature = "browse")]
     #[must_use]
-    pub const fn browse(&self) -> Browse<'_> {
+    pub const fn browse(&self) -> Browse<&Self> {
         Browse(self)
     }
 
     /// Episode-related endpoints.
+    #[cfg(feature = "episodes")]
     #[must_use]
-    pub const fn episodes(&self) -> Episodes<'_> {
+    pub const fn episodes(&self) -> Episodes<&Self> {
         Episodes(self)
     }
 
     /// Endpoint functions related to following and unfollowing artists, users and playlists.
+    #[cfg(feature = "follow")]
     #[must_use]
-    pub const fn follow(&self) -> Follow<'_> {
+    pub const fn follow(&self) -> Follow<&Self> {
         Follow(self)
     }
 
     /// Endpoints relating to saving albums and tracks.
+    #[cfg(feature = "library")]
     #[must_use]
-    pub const fn library(&self) -> Library<'_> {
+    pub const fn library(&self) -> Library<&Self> {
         Library(self)
     }
 
     /// Endpoint functions relating to a user's top artists and tracks.
+    #[cfg(feature = "personalization")]
     #[must_use]
-    pub const fn personalization(&self) -> Personalization<'_> {
+    pub const fn personalization(&self) -> Personalization<&Self> {
         Personalization(self)
     }
 
     /// Endpoint functions related to controlling what is playing on the current user's Spotify
     /// account. (Beta)
+    #[cfg(feature = "player")]
     #[must_use]
-    pub const fn player(&self) -> Player<'_> {
+    pub const fn player(&self) -> Player<&Self> {
         Player(self)
     }
 
     /// Endpoint functions related to playlists.
+    #[cfg(feature = "playlists")]
     #[must_use]
-    pub const fn playlists(&self) -> Playlists<'_> {
+    pub const fn playlists(&self) -> Playlists<&Self> {
         Playlists(self)
     }
 
     /// Endpoint functions related to searches.
+    #[cfg(feature = "search")]
     #[must_use]
-    pub const fn search(&self) -> Search<'_> {
+    pub const fn search(&self) -> Search<&Self> {
         Search(self)
     }
 
     /// Endpoint functions related to shows.
+    #[cfg(feature = "shows")]
     #[must_use]
-    pub const fn shows(&self) -> Shows<'_> {
+    pub const fn shows(&self) -> Shows<&Self> {
         Shows(self)
     }
 
     /// Endpoint functions related to tracks and audio analysis.
+    #[cfg(feature = "tracks")]
     #[must_use]
-    pub const fn tracks(&self) -> Tracks<'_> {
+    pub const fn tracks(&self) -> Tracks<&Self> {
         Tracks(self)
     }
 
     /// Endpoint functions related to users' profiles.
+    #[cfg(feature = "users-profile")]
     #[must_use]
-    pub const fn users_profile(&self) -> UsersProfile<'_> {
+    pub const fn users_profile(&self) -> UsersProfile<&Self> {
         UsersProfile(self)
     }
 }
 
+/// A handle to a [`Client`] that endpoint namespaces such as [`Albums`] can be created from.
+///
+/// The namespaces returned by [`Client`]'s methods borrow it, but a namespace can also own an
+/// [`Arc<Client>`](std::sync::Arc) or [`Rc<Client>`](std::rc::Rc), so that it can be stored
+/// without a lifetime. This is implemented for every type that dereferences to a [`Client`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "tracks")]
+/// # {
+/// use std::sync::Arc;
+///
+/// use aspotify::{Client, ClientCredentials, Tracks};
+///
+/// struct MyService {
+///     tracks: Tracks<Arc<Client>>,
+/// }
+///
+/// let client = Arc::new(Client::new(ClientCredentials {
+///     id: String::new(),
+///     secret: String::new(),
+/// }));
+/// let service = MyService {
+///     tracks: Tracks(Arc::clone(&client)),
+/// };
+/// # }
+/// ```
+pub trait AsClient: Deref<Target = Client> {
+    /// Get the client.
+    fn as_client(&self) -> &Client {
+        self
+    }
+}
+
+impl<T: Deref<Target = Client>> AsClient for T {}
+
+impl Client {
+    /// Get the item that a playback context refers to, such as the album or playlist that is being
+    /// played. Albums, playlists, shows and episodes are returned simplified.
+    ///
+    /// This requires the same scopes as getting the item itself; playlists that are private
+    /// require `playlist-read-private`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails or the item doesn't exist, and fails with
+    /// [`Error::UnresolvableContext`] without sending a request if the context isn't an item, such
+    /// as a user's collection.
+    pub async fn resolve_context(
+        &self,
+        context: &Context,
+        market: Option<Market>,
+    ) -> Result<Response<AnyItem>, Error> {
+        let id = &context.id;
+        let query = (market.map(Market::query),);
+
+        Ok(match &context.context_type {
+            ItemType::Album => self
+                .send_json::<Album>(
+                    self.client
+                        .get(endpoint!("/v1/albums/{}", id))
+                        .query(&query),
+                )
+                .await?
+                .map(|album| AnyItem::Album(album.simplify())),
+            ItemType::Artist => self
+                .send_json(self.client.get(endpoint!("/v1/artists/{}", id)))
+                .await?
+                .map(AnyItem::Artist),
+            ItemType::Playlist => self
+                .send_json::<Playlist>(
+                    self.client
+                        .get(endpoint!("/v1/playlists/{}", id))
+                        .query(&query),
+                )
+                .await?
+                .map(|playlist| AnyItem::Playlist(playlist.simplify())),
+            ItemType::Track => self
+                .send_json(
+                    self.client
+                        .get(endpoint!("/v1/tracks/{}", id))
+                        .query(&query),
+                )
+                .await?
+                .map(AnyItem::Track),
+            ItemType::Show => self
+                .send_json::<Show>(self.client.get(endpoint!("/v1/shows/{}", id)).query(&query))
+                .await?
+                .map(|show| AnyItem::Show(show.simplify())),
+            ItemType::Episode => self
+                .send_json::<Episode>(
+                    self.client
+                        .get(endpoint!("/v1/episodes/{}", id))
+                        .query(&query),
+                )
+                .await?
+                .map(|episode| AnyItem::Episode(episode.simplify())),
+            ItemType::Collection | ItemType::User | ItemType::Other(_) => {
+                return Err(Error::UnresolvableContext(context.context_type.clone()))
+            }
+        })
+    }
+
+    /// Get a page of items from a link given by Spotify, such as the
+    /// [`next`](crate::Page::next) link of a [`Page`], [`CursorPage`](crate::CursorPage) or
+    /// [`TwoWayCursorPage`](crate::TwoWayCursorPage).
+    ///
+    /// The links contain all the parameters of the original request, so this can be used to get
+    /// the pages of any endpoint. `P` is the type of the page; pages that Spotify returns inside
+    /// an object, such as search results, are unwrapped.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::UnexpectedUrl`] without sending a request if the link isn't to the
+    /// Spotify API or the client's [API base URL](Client::set_api_base_url), so that the access
+    /// token isn't sent anywhere else.
+    pub async fn get_page<P: DeserializeOwned>(&self, url: &str) -> Result<Response<P>, Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum MaybeWrapped<P> {
+            Page(P),
+            Wrapped(HashMap<String, P>),
+        }
+
+        let to_base_url = self.api_base_url.as_ref().is_some_and(|base| {
+            url.strip_prefix(base.as_str().trim_end_matches('/'))
+                .is_some_and(|path| path.starts_with('/'))
+        });
+        if !url.starts_with(endpoint!("/")) && !to_base_url {
+            return Err(Error::UnexpectedUrl(url.to_owned()));
+        }
+
+        let response = self
+            .send_json_unverified::<MaybeWrapped<P>>(self.client.get(url))
+            .await?;
+        let expires = response.expires;
+        let data = match response.data {
+            MaybeWrapped::Page(page) => page,
+            MaybeWrapped::Wrapped(map) if map.len() == 1 => map.into_iter().next().unwrap().1,
+            MaybeWrapped::Wrapped(_) => {
+                return Err(Error::Parse(de::Error::custom(
+                    "expected a page or an object containing one page",
+                )))
+            }
+        };
+        Ok(Response { data, expires })
+    }
+
+    /// Get any Spotify endpoint with custom query parameters.
+    ///
+    /// This is an escape hatch for parameters that this library doesn't support yet, such as the
+    /// `fields` filter of some endpoints. `path` is the path of the endpoint, such as
+    /// `/v1/playlists/{id}`, and `T` is the type to parse the response as; use
+    /// [`serde_json::Value`] to get the response as Spotify sent it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example(client: &aspotify::Client) -> Result<(), aspotify::Error> {
+    /// let playlist: serde_json::Value = client
+    ///     .get_with_query(
+    ///         "/v1/playlists/37i9dQZF1DXcBWIGoYBM5M",
+    ///         &[("fields", "name,followers.total")],
+    ///     )
+    ///     .await?
+    ///     .data;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::UnexpectedUrl`] without sending a request if the path doesn't start
+    /// with `/`.
+    pub async fn get_with_query<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Response<T>, Error> {
+        if !path.starts_with('/') {
+            return Err(Error::UnexpectedUrl(path.to_owned()));
+        }
+        self.send_json_unverified(self.client.get(endpoint!("{}", path)).query(query))
+            .await
+    }
+}
+
 /// A market in which to limit the request to.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Market {
     /// A country code.
-    Country(CountryCode),
+    Country(Country),
     /// Deduce the current country from the access token. Requires `user-read-private`.
     FromToken,
 }
 
 impl Market {
-    fn as_str(self) -> &'static str {
+    fn as_str(&self) -> &str {
         match self {
-            Market::Country(code) => code.alpha2(),
+            Market::Country(code) => code.as_str(),
             Market::FromToken => "from_token",
         }
     }
-    fn query(self) -> (&'static str, &'static str) {
-        ("market", self.as_str())
+    fn query(self) -> (&'static str, Self) {
+        ("market", self)
+    }
+}
+
+impl From<Country> for Market {
+    fn from(country: Country) -> Self {
+        Self::Country(country)
+    }
+}
+
+impl Serialize for Market {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// An item along with whether something is true of it, such as whether the user has saved it.
+///
+/// This is returned by functions like [`Library::annotate_saved`] and
+/// [`Follow::annotate_followed`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Annotated<T> {
+    /// The item.
+    pub item: T,
+    /// Whether the annotation applies to the item.
+    pub annotation: bool,
+}
+
+/// A change to an attribute that can be removed, such as a playlist's description.
+///
+/// This distinguishes leaving the attribute as it is from removing it, which an [`Option`] can't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Update<T> {
+    /// Leave the attribute unchanged.
+    #[default]
+    Keep,
+    /// Remove the attribute.
+    Clear,
+    /// Set the attribute to a new value.
+    Set(T),
+}
+
+impl<T> Update<T> {
+    /// Whether the attribute is left unchanged.
+    #[must_use]
+    pub const fn is_keep(&self) -> bool {
+        matches!(self, Self::Keep)
+    }
+}
+
+impl<T> From<T> for Update<T> {
+    fn from(value: T) -> Self {
+        Self::Set(value)
     }
 }
 
@@ -181,7 +492,15 @@ pub enum TimeRange {
 }
 
 impl TimeRange {
-    fn as_str(self) -> &'static str {
+    /// Get the time range as a string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::TimeRange::Short.as_str(), "short_term");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
         match self {
             Self::Long => "long_term",
             Self::Medium => "medium_term",
@@ -190,9 +509,106 @@ impl TimeRange {
     }
 }
 
-type Chunk<'a, I> = iter::Take<&'a mut iter::Peekable<I>>;
+impl FromStr for TimeRange {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(
+            s,
+            "time range",
+            &[Self::Short, Self::Medium, Self::Long],
+            Self::as_str,
+        )
+    }
+}
+impl TryFrom<&str> for TimeRange {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A type of item that a user's top items can be calculated for.
+///
+/// Spotify's `/me/top` endpoint only accepts artists and tracks; it doesn't support shows or
+/// episodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TopItemType {
+    /// The user's top artists.
+    Artists,
+    /// The user's top tracks.
+    Tracks,
+}
+
+impl TopItemType {
+    /// Get the type as a string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::TopItemType::Artists.as_str(), "artists");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Artists => "artists",
+            Self::Tracks => "tracks",
+        }
+    }
+}
+
+impl FromStr for TopItemType {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(
+            s,
+            "top item type",
+            &[Self::Artists, Self::Tracks],
+            Self::as_str,
+        )
+    }
+}
+impl TryFrom<&str> for TopItemType {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A chunk of items passed to the request function of [`chunked_sequence`], [`chunked_requests`]
+/// and [`chunked_requests_with`].
+pub type Chunk<'a, I> = iter::Take<&'a mut iter::Peekable<I>>;
 
-async fn chunked_sequence<I: IntoIterator, Fut, T>(
+/// Split items into chunks and make a request for each chunk, concatenating the results.
+///
+/// Spotify limits how many items can be passed to many endpoints at once; this function removes
+/// that limit by calling `f` with chunks of at most `chunk_size` items, running all the requests
+/// concurrently and concatenating their results in order. The returned response expires when the
+/// last chunk's response expires.
+///
+/// # Examples
+///
+/// ```
+/// # async {
+/// use std::time::Instant;
+///
+/// use aspotify::endpoints::chunked_sequence;
+/// use aspotify::{Error, Response};
+///
+/// let sizes = chunked_sequence(0..120, 50, |chunk| {
+///     let chunk: Vec<_> = chunk.collect();
+///     async move {
+///         Ok::<_, Error>(Response {
+///             data: vec![chunk.len()],
+///             expires: Instant::now(),
+///         })
+///     }
+/// })
+/// .await
+/// .unwrap();
+/// assert_eq!(sizes.data, [50, 50, 20]);
+/// # };
+/// ```
+pub async fn chunked_sequence<I: IntoIterator, Fut, T>(
     items: I,
     chunk_size: usize,
     mut f: impl FnMut(Chunk<'_, I::IntoIter>) -> Fut,
@@ -204,7 +620,7 @@ where
     let mut futures = FuturesOrdered::new();
 
     while items.peek().is_some() {
-        futures.push(f(items.by_ref().take(chunk_size)));
+        futures.push_back(f(items.by_ref().take(chunk_size)));
     }
 
     let mut response = Response {
@@ -220,7 +636,11 @@ where
     Ok(response)
 }
 
-async fn chunked_requests<I: IntoIterator, Fut>(
+/// Split items into chunks and make a request for each chunk.
+///
+/// This is like [`chunked_sequence`], but for requests that don't return anything. The requests
+/// are run concurrently, and the first error encountered is returned.
+pub async fn chunked_requests<I: IntoIterator, Fut>(
     items: I,
     chunk_size: usize,
     mut f: impl FnMut(Chunk<'_, I::IntoIter>) -> Fut,
@@ -238,8 +658,340 @@ where
     futures.try_collect().await
 }
 
+/// How to make the requests of a bulk operation, such as following hundreds of artists at once.
+///
+/// Bulk operations split their items into chunks and make a request for each chunk. By default
+/// all the requests are made at once, which can cause Spotify to rate limit a large operation;
+/// limiting the [concurrency](Self::concurrency) avoids that. A
+/// [progress callback](Self::on_chunk_done) can be used to display a progress bar.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example(client: &aspotify::Client, ids: &[&str]) -> Result<(), aspotify::Error> {
+/// use aspotify::Bulk;
+///
+/// client
+///     .follow()
+///     .follow_artists_with(
+///         ids,
+///         Bulk::sequential().on_chunk_done(|done, total| println!("{}/{}", done, total)),
+///     )
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Bulk<'a> {
+    concurrency: Option<usize>,
+    on_chunk_done: Option<Box<dyn FnMut(usize, usize) + Send + 'a>>,
+}
+
+impl<'a> Bulk<'a> {
+    /// Make all the requests at once, with no progress callback.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            concurrency: None,
+            on_chunk_done: None,
+        }
+    }
+    /// Make the requests one at a time.
+    #[must_use]
+    pub fn sequential() -> Self {
+        Self::new().concurrency(1)
+    }
+    /// Make at most this many requests at once. A limit of 0 is treated as 1.
+    #[must_use]
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = Some(limit.max(1));
+        self
+    }
+    /// Call a function each time a chunk's request succeeds, with the number of items done so far
+    /// and the total number of items.
+    #[must_use]
+    pub fn on_chunk_done(mut self, f: impl FnMut(usize, usize) + Send + 'a) -> Self {
+        self.on_chunk_done = Some(Box::new(f));
+        self
+    }
+}
+
+impl Default for Bulk<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Bulk<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bulk")
+            .field("concurrency", &self.concurrency)
+            .field("on_chunk_done", &self.on_chunk_done.is_some())
+            .finish()
+    }
+}
+
+/// Split items into chunks and make a request for each chunk, as configured by a [`Bulk`].
+///
+/// This is like [`chunked_requests`], but limits how many requests are made at once and reports
+/// progress as each one completes. The first error encountered is returned, and no more requests
+/// are started after it.
+///
+/// # Examples
+///
+/// ```
+/// # async {
+/// use aspotify::endpoints::chunked_requests_with;
+/// use aspotify::{Bulk, Error};
+///
+/// let mut progress = Vec::new();
+/// chunked_requests_with(
+///     0..120,
+///     50,
+///     Bulk::sequential().on_chunk_done(|done, total| progress.push((done, total))),
+///     |_| async { Ok::<_, Error>(()) },
+/// )
+/// .await
+/// .unwrap();
+/// assert_eq!(progress, [(50, 120), (100, 120), (120, 120)]);
+/// # };
+/// ```
+pub async fn chunked_requests_with<I: IntoIterator, Fut>(
+    items: I,
+    chunk_size: usize,
+    mut bulk: Bulk<'_>,
+    mut f: impl FnMut(Chunk<'_, std::vec::IntoIter<I::Item>>) -> Fut,
+) -> Result<(), Error>
+where
+    Fut: Future<Output = Result<(), Error>>,
+{
+    let items: Vec<I::Item> = items.into_iter().collect();
+    let total = items.len();
+    let mut items = items.into_iter().peekable();
+
+    let mut requests = Vec::new();
+    while items.peek().is_some() {
+        let len = items.len().min(chunk_size);
+        let request = f(items.by_ref().take(chunk_size));
+        requests.push(async move { request.await.map(|()| len) });
+    }
+
+    let concurrency = bulk.concurrency.unwrap_or(requests.len()).max(1);
+    let mut responses = stream::iter(requests).buffer_unordered(concurrency);
+
+    let mut done = 0;
+    while let Some(len) = responses.next().await.transpose()? {
+        done += len;
+        if let Some(on_chunk_done) = &mut bulk.on_chunk_done {
+            on_chunk_done(done, total);
+        }
+    }
+
+    Ok(())
+}
+
+/// The progress of an operation that gets many items, such as
+/// [`Library::get_saved_tracks_all_with_progress`].
+///
+/// The progress callbacks of those operations are called with this each time a page of items
+/// has been got, and can return [`ControlFlow::Break`] to cancel the operation. A cancelled
+/// operation returns the items it got before it was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Progress {
+    /// The number of items got so far.
+    pub done: usize,
+    /// The total number of items, if it is known.
+    pub total: Option<usize>,
+}
+
+/// A progress callback that never cancels.
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "follow",
+    feature = "library",
+    feature = "player",
+    feature = "playlists",
+    feature = "search"
+))]
+fn no_progress(_: Progress) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
+
+/// Get every item of a paginated endpoint.
+///
+/// `f` is called with the limit and offset of each page to get. The first page is requested
+/// first, and then all the remaining pages are requested concurrently.
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "library",
+    feature = "playlists"
+))]
+async fn all_pages<T, Fut>(
+    limit: usize,
+    f: impl FnMut(usize, usize) -> Fut,
+) -> Result<Response<Vec<T>>, Error>
+where
+    Fut: Future<Output = Result<Response<Page<T>>, Error>>,
+{
+    all_pages_with_progress(limit, no_progress, f).await
+}
+
+/// Get every item of a paginated endpoint like [`all_pages`], calling `on_progress` after each
+/// page.
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "library",
+    feature = "playlists"
+))]
+async fn all_pages_with_progress<T, Fut>(
+    limit: usize,
+    on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    mut f: impl FnMut(usize, usize) -> Fut,
+) -> Result<Response<Vec<T>>, Error>
+where
+    Fut: Future<Output = Result<Response<Page<T>>, Error>>,
+{
+    let first = f(limit, 0).await?;
+    remaining_pages_with_progress(first, limit, on_progress, f).await
+}
+
+/// Get the items of a cursor-paginated endpoint until there are no more pages or `max_items` have
+/// been got.
+///
+/// `f` is called with the limit and cursor of each page to get, and returns the page's items and
+/// the cursor of the page after it. The pages are requested one at a time, waiting for any rate
+/// limit to reset before each one. `on_progress` is called after each page, and stops getting
+/// pages if it breaks.
+#[cfg(any(feature = "follow", feature = "player"))]
+async fn cursor_pages<T, Fut>(
+    client: &Client,
+    max_items: usize,
+    mut on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    mut f: impl FnMut(usize, Option<String>) -> Fut,
+) -> Result<Response<Vec<T>>, Error>
+where
+    Fut: Future<Output = Result<Response<(Vec<T>, Option<String>)>, Error>>,
+{
+    let mut response = Response {
+        data: Vec::new(),
+        expires: Instant::now(),
+    };
+    let mut cursor = None;
+
+    while response.data.len() < max_items {
+        client.wait_for_rate_limit().await;
+        let limit = (max_items - response.data.len()).min(50);
+        let page = f(limit, cursor.take()).await?;
+        response.expires = page.expires;
+
+        let (mut items, next) = page.data;
+        let empty = items.is_empty();
+        response.data.append(&mut items);
+        response.data.truncate(max_items);
+
+        let progress = Progress {
+            done: response.data.len(),
+            total: None,
+        };
+        if on_progress(progress).is_break() {
+            break;
+        }
+        match next {
+            Some(next) if !empty => cursor = Some(next),
+            _ => break,
+        }
+    }
+
+    Ok(response)
+}
+
+/// The largest offset requested when getting every page of an endpoint.
+///
+/// This is not a limit from Spotify, which doesn't document a maximum offset for its paginated
+/// endpoints other than search. It is a guard far above the size of the largest playlists and
+/// libraries, so that a wrong `total` in a response can't cause an unbounded number of requests.
+/// None of the paginated endpoints can order their items differently either, so items past it
+/// can't be reached another way and [`Error::OffsetLimitExceeded`] is returned instead.
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "library",
+    feature = "playlists",
+    feature = "search"
+))]
+const MAX_OFFSET: usize = 100_000;
+
+/// Get the items in a page followed by every item after it, using `f` like [`all_pages`].
+///
+/// If getting every item would require an offset larger than [`MAX_OFFSET`], this fails with
+/// [`Error::OffsetLimitExceeded`] before making any more requests.
+#[cfg(any(feature = "albums", feature = "playlists", feature = "search"))]
+async fn remaining_pages<T, Fut>(
+    first: Response<Page<T>>,
+    limit: usize,
+    f: impl FnMut(usize, usize) -> Fut,
+) -> Result<Response<Vec<T>>, Error>
+where
+    Fut: Future<Output = Result<Response<Page<T>>, Error>>,
+{
+    remaining_pages_with_progress(first, limit, no_progress, f).await
+}
+
+/// Get the items in a page followed by every item after it like [`remaining_pages`], calling
+/// `on_progress` after each page.
+///
+/// The pages are still requested concurrently, but they are reported in order; if `on_progress`
+/// breaks, the pages that haven't been reported yet are discarded.
+#[cfg(any(
+    feature = "albums",
+    feature = "browse",
+    feature = "library",
+    feature = "playlists",
+    feature = "search"
+))]
+async fn remaining_pages_with_progress<T, Fut>(
+    first: Response<Page<T>>,
+    limit: usize,
+    mut on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    mut f: impl FnMut(usize, usize) -> Fut,
+) -> Result<Response<Vec<T>>, Error>
+where
+    Fut: Future<Output = Result<Response<Page<T>>, Error>>,
+{
+    let total = first.data.total;
+    let mut progress = |done| {
+        on_progress(Progress {
+            done,
+            total: Some(total),
+        })
+    };
+    if progress(first.data.items.len()).is_break() {
+        return Ok(first.map(|page| page.items));
+    }
+
+    let start = first.data.offset + first.data.items.len();
+    let offsets = (start..total).step_by(limit);
+    if offsets.clone().any(|offset| offset > MAX_OFFSET) {
+        return Err(Error::OffsetLimitExceeded(MAX_OFFSET));
+    }
+    let mut futures: FuturesOrdered<_> = offsets.map(|offset| f(limit, offset)).collect();
+
+    let mut response = first.map(|page| page.items);
+    while let Some(mut r) = futures.next().await.transpose()? {
+        response.data.append(&mut r.data.items);
+        response.expires = r.expires;
+        if progress(response.data.len()).is_break() {
+            break;
+        }
+    }
+
+    Ok(response)
+}
+
 #[cfg(test)]
-fn client() -> crate::Client {
+pub(crate) fn client() -> crate::Client {
     dotenv::dotenv().unwrap();
     let mut client = crate::Client::with_refresh(
         crate::ClientCredentials::from_env().unwrap(),
@@ -248,3 +1000,308 @@ fn client() -> crate::Client {
     client.debug = true;
     client
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use serde::Deserialize;
+    use tokio::time;
+
+    use super::{chunked_requests_with, Bulk};
+    use crate::endpoints::client;
+    use crate::{
+        AlbumSimplified, Client, ClientCredentials, Context, Error, ItemType, Market, Page,
+        TrackSimplified,
+    };
+
+    #[cfg(any(feature = "albums", feature = "playlists", feature = "search"))]
+    #[tokio::test]
+    async fn pages() {
+        use std::ops::ControlFlow;
+        use std::time::Instant;
+
+        use futures_util::future;
+
+        use super::{remaining_pages, remaining_pages_with_progress, Progress, MAX_OFFSET};
+        use crate::Response;
+
+        fn page(offset: usize, len: usize, total: usize) -> Response<Page<usize>> {
+            Response {
+                data: Page {
+                    href: None,
+                    items: (offset..(offset + len).min(total)).collect(),
+                    limit: len,
+                    next: None,
+                    offset,
+                    previous: None,
+                    total,
+                },
+                expires: Instant::now(),
+            }
+        }
+
+        let items = remaining_pages(page(0, 3, 10), 3, |limit, offset| {
+            future::ok(page(offset, limit, 10))
+        })
+        .await
+        .unwrap()
+        .data;
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+
+        let total = MAX_OFFSET + 1000;
+        let res = remaining_pages(
+            page(0, 100, total),
+            100,
+            |_, _| -> future::Ready<Result<_, Error>> { panic!("a page was requested") },
+        )
+        .await;
+        assert!(matches!(res, Err(Error::OffsetLimitExceeded(MAX_OFFSET))));
+
+        // Cancel after the second page.
+        let mut reported = Vec::new();
+        let items = remaining_pages_with_progress(
+            page(0, 3, 10),
+            3,
+            |progress| {
+                reported.push(progress);
+                if reported.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+            |limit, offset| future::ok(page(offset, limit, 10)),
+        )
+        .await
+        .unwrap()
+        .data;
+        assert_eq!(items, (0..6).collect::<Vec<_>>());
+        assert_eq!(
+            reported,
+            [
+                Progress {
+                    done: 3,
+                    total: Some(10)
+                },
+                Progress {
+                    done: 6,
+                    total: Some(10)
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_requests() {
+        async fn request(running: &AtomicUsize, max_running: &AtomicUsize) -> Result<(), Error> {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            time::sleep(Duration::from_millis(10)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let mut progress = Vec::new();
+        chunked_requests_with(
+            0..250,
+            50,
+            Bulk::new()
+                .concurrency(2)
+                .on_chunk_done(|done, total| progress.push((done, total))),
+            |chunk| {
+                chunk.for_each(drop);
+                request(&running, &max_running)
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(progress.len(), 5);
+        assert_eq!(progress.last(), Some(&(250, 250)));
+
+        max_running.store(0, Ordering::SeqCst);
+        chunked_requests_with(0..250, 50, Bulk::new(), |chunk| {
+            chunk.for_each(drop);
+            request(&running, &max_running)
+        })
+        .await
+        .unwrap();
+        assert_eq!(max_running.load(Ordering::SeqCst), 5);
+
+        // No more requests are started after one fails.
+        let started = AtomicUsize::new(0);
+        let res = chunked_requests_with(0..250, 50, Bulk::sequential(), |chunk| {
+            chunk.for_each(drop);
+            async {
+                started.fetch_add(1, Ordering::SeqCst);
+                Err(Error::Timeout(Duration::from_secs(1)))
+            }
+        })
+        .await;
+        assert!(matches!(res, Err(Error::Timeout(_))));
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(any(feature = "follow", feature = "player"))]
+    #[tokio::test]
+    async fn drain_cursor_pages() {
+        use std::ops::ControlFlow;
+        use std::time::Instant;
+
+        use futures_util::future;
+
+        use super::{cursor_pages, no_progress, Progress};
+        use crate::Response;
+
+        let client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        // Pages of 0..25, where the cursor is the start of the page.
+        let get = |limit: usize, cursor: Option<String>| {
+            let start = cursor.map_or(0, |cursor| cursor.parse().unwrap());
+            let end = (start + limit).min(25);
+            future::ok::<_, Error>(Response {
+                data: ((start..end).collect(), Some(end.to_string())),
+                expires: Instant::now(),
+            })
+        };
+
+        let items = cursor_pages(&client, 7, no_progress, get)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(items, (0..7).collect::<Vec<_>>());
+        let items = cursor_pages(&client, 120, no_progress, get)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(items, (0..25).collect::<Vec<_>>());
+        let items = cursor_pages(&client, 0, no_progress, get)
+            .await
+            .unwrap()
+            .data;
+        assert!(items.is_empty());
+
+        // Cancel after the second page.
+        let mut reported = Vec::new();
+        let items = cursor_pages(
+            &client,
+            120,
+            |progress: Progress| {
+                reported.push(progress.done);
+                if reported.len() == 2 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+            |_, cursor| get(5, cursor),
+        )
+        .await
+        .unwrap()
+        .data;
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert_eq!(reported, [5, 10]);
+    }
+
+    #[tokio::test]
+    async fn get_with_query() {
+        let client = client();
+
+        let page = client
+            .get_with_query::<Page<TrackSimplified>>(
+                "/v1/albums/3lBPyXvg1hhoJ1REnw80fZ/tracks",
+                &[("limit", "2"), ("offset", "1")],
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(page.limit, 2);
+        assert_eq!(page.offset, 1);
+        assert_eq!(page.items.len(), 2);
+
+        // The response type doesn't need to be serializable.
+        #[derive(Deserialize)]
+        struct Name {
+            name: String,
+        }
+        let album = client
+            .get_with_query::<Name>("/v1/albums/3lBPyXvg1hhoJ1REnw80fZ", &[])
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(album.name, "RELAXER");
+
+        assert!(matches!(
+            client
+                .get_with_query::<serde_json::Value>(".example.com/", &[])
+                .await,
+            Err(Error::UnexpectedUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_page() {
+        let client = client();
+
+        let page = client
+            .get_page::<Page<TrackSimplified>>(
+                "https://api.spotify.com/v1/albums/3lBPyXvg1hhoJ1REnw80fZ/tracks?limit=2",
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(page.items.len(), 2);
+        let next = client
+            .get_page::<Page<TrackSimplified>>(page.next.as_deref().unwrap())
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(next.offset, 2);
+        assert_eq!(next.previous, page.href);
+
+        // New releases are returned inside an object.
+        let page = client
+            .get_page::<Page<AlbumSimplified>>(
+                "https://api.spotify.com/v1/browse/new-releases?limit=2",
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(page.limit, 2);
+
+        let client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        let res = client
+            .get_page::<Page<TrackSimplified>>("https://api.spotify.com.example.com/v1/me/tracks")
+            .await;
+        assert!(matches!(res, Err(Error::UnexpectedUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn resolve_context() {
+        let client = client();
+
+        let context = Context {
+            context_type: ItemType::Album,
+            external_urls: HashMap::new(),
+            id: "3lBPyXvg1hhoJ1REnw80fZ".to_owned(),
+        };
+        let item = client
+            .resolve_context(&context, Some(Market::FromToken))
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(item.item_type(), ItemType::Album);
+        assert_eq!(item.id(), Some("3lBPyXvg1hhoJ1REnw80fZ"));
+        assert_eq!(item.name(), "RELAXER");
+    }
+}
@@ -1,27 +1,42 @@
-use crate::{Artist, Client, Error, Page, Response, TimeRange, Track};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{AnyItem, Artist, AsClient, Error, Page, Response, TimeRange, TopItemType, Track};
 
 /// Endpoint functions relating to a user's top artists and tracks.
 #[derive(Debug, Clone, Copy)]
-pub struct Personalization<'a>(pub &'a Client);
+pub struct Personalization<C>(pub C);
+
+impl<C: AsClient> Personalization<C> {
+    /// Get a user's top items of a type.
+    ///
+    /// The items are returned as [`AnyItem`]s, so that one function can get either type. Spotify
+    /// only calculates top artists and tracks, so there are no top shows or episodes. Requires
+    /// `user-top-read`.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/personalization/get-users-top-artists-and-tracks/).
+    pub async fn get_top_items(
+        &self,
+        item_type: TopItemType,
+        limit: usize,
+        offset: usize,
+        time_range: TimeRange,
+    ) -> Result<Response<Page<AnyItem>>, Error> {
+        self.get_top(item_type, limit, offset, time_range).await
+    }
 
-impl Personalization<'_> {
     /// Get a user's top artists.
     ///
     /// Requires `user-top-read`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/personalization/get-users-top-artists-and-tracks/).
     pub async fn get_top_artists(
-        self,
+        &self,
         limit: usize,
         offset: usize,
         time_range: TimeRange,
     ) -> Result<Response<Page<Artist>>, Error> {
-        self.0
-            .send_json(self.0.client.get(endpoint!("/v1/me/top/artists")).query(&(
-                ("limit", limit.to_string()),
-                ("offset", offset.to_string()),
-                ("time_range", time_range.as_str()),
-            )))
+        self.get_top(TopItemType::Artists, limit, offset, time_range)
             .await
     }
 
@@ -31,17 +46,33 @@ impl Personalization<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/personalization/get-users-top-tracks-and-tracks/).
     pub async fn get_top_tracks(
-        self,
+        &self,
         limit: usize,
         offset: usize,
         time_range: TimeRange,
     ) -> Result<Response<Page<Track>>, Error> {
+        self.get_top(TopItemType::Tracks, limit, offset, time_range)
+            .await
+    }
+
+    async fn get_top<T: DeserializeOwned + Serialize>(
+        &self,
+        item_type: TopItemType,
+        limit: usize,
+        offset: usize,
+        time_range: TimeRange,
+    ) -> Result<Response<Page<T>>, Error> {
         self.0
-            .send_json(self.0.client.get(endpoint!("/v1/me/top/tracks")).query(&(
-                ("limit", limit.to_string()),
-                ("offset", offset.to_string()),
-                ("time_range", time_range.as_str()),
-            )))
+            .send_json(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/me/top/{}", item_type.as_str()))
+                    .query(&(
+                        ("limit", limit.to_string()),
+                        ("offset", offset.to_string()),
+                        ("time_range", time_range.as_str()),
+                    )),
+            )
             .await
     }
 }
@@ -49,7 +80,7 @@ impl Personalization<'_> {
 #[cfg(test)]
 mod tests {
     use crate::endpoints::client;
-    use crate::TimeRange;
+    use crate::{ItemType, TimeRange, TopItemType};
 
     #[tokio::test]
     async fn test() {
@@ -73,5 +104,18 @@ mod tests {
         assert_eq!(top.limit, 2);
         assert_eq!(top.offset, 8);
         assert!(top.items.len() <= 2);
+
+        let top = personalization
+            .get_top_items(TopItemType::Tracks, 3, 0, TimeRange::Medium)
+            .await
+            .unwrap()
+            .data;
+        assert!(top.href.is_some());
+        assert!(top.items.len() <= 3);
+        assert!(top.items.len() <= top.total);
+        assert!(top
+            .items
+            .iter()
+            .all(|item| item.item_type() == ItemType::Track));
     }
 }
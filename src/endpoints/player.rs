@@ -1,11 +1,16 @@
 use std::fmt::Display;
+use std::ops::ControlFlow;
 use std::time::Duration;
 
+use futures_util::future;
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
 
+use super::{cursor_pages, no_progress, Progress};
 use crate::{
-    Client, CurrentPlayback, CurrentlyPlaying, Device, Error, ItemType, Market, PlayHistory,
-    RepeatState, Response, TwoWayCursorPage,
+    util, Actions, AsClient, CurrentPlayback, CurrentlyPlaying, Device, Disallow, Error, ItemType,
+    Market, PlayHistory, PlaybackPosition, PlayingType, RepeatState, Response, TwoWayCursorPage,
+    Volume,
 };
 
 /// Endpoint functions related to controlling what is playing on the current user's Spotify account.
@@ -16,16 +21,16 @@ use crate::{
 /// The `device_id` parameter seen in this module is the device to perform the request on. If not
 /// specified, it will default to the current user's currenttly active device.
 #[derive(Debug, Clone, Copy)]
-pub struct Player<'a>(pub &'a Client);
+pub struct Player<C>(pub C);
 
-impl Player<'_> {
+impl<C: AsClient> Player<C> {
     /// Get the current user's available devices (Beta).
     ///
     /// Requires `user-read-playback-state`
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/get-a-users-available-devices/).
-    pub async fn get_devices(self) -> Result<Response<Vec<Device>>, Error> {
-        #[derive(Deserialize)]
+    pub async fn get_devices(&self) -> Result<Response<Vec<Device>>, Error> {
+        #[derive(Deserialize, Serialize)]
         struct Devices {
             devices: Vec<Device>,
         }
@@ -37,13 +42,26 @@ impl Player<'_> {
             .map(|res| res.devices))
     }
 
+    /// Get the current user's active device, or [`None`] if no device is active (Beta).
+    ///
+    /// Requires `user-read-playback-state`. Note that the active device may still be restricted,
+    /// meaning it won't accept commands.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/get-a-users-available-devices/).
+    pub async fn get_active_device(&self) -> Result<Response<Option<Device>>, Error> {
+        Ok(self
+            .get_devices()
+            .await?
+            .map(|devices| devices.into_iter().find(|device| device.is_active)))
+    }
+
     /// Get information about the current user's current playback (Beta).
     ///
     /// Requires `user-read-playback-state`. Returns None if nothing is currently playing.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/get-information-about-the-users-current-playback/).
     pub async fn get_playback(
-        self,
+        &self,
         market: Option<Market>,
     ) -> Result<Response<Option<CurrentPlayback>>, Error> {
         self.0
@@ -66,7 +84,7 @@ impl Player<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/get-recently-played/).
     pub async fn get_recently_played(
-        self,
+        &self,
         limit: usize,
         after: Option<String>,
         before: Option<String>,
@@ -85,6 +103,48 @@ impl Player<'_> {
             .await
     }
 
+    /// Get the current user's recently played tracks, up to `max_items` of them, most recent first
+    /// (Beta).
+    ///
+    /// Unlike [`get_recently_played`](Self::get_recently_played), this follows the cursors to get
+    /// the pages before the first, one at a time. Requires `user-read-recently-played`. Nothing is
+    /// returned if a private session is enabled.
+    pub async fn get_recently_played_all(
+        &self,
+        max_items: usize,
+    ) -> Result<Response<Vec<PlayHistory>>, Error> {
+        self.get_recently_played_all_with_progress(max_items, no_progress)
+            .await
+    }
+
+    /// Get the current user's recently played tracks, up to `max_items` of them, reporting
+    /// progress (Beta).
+    ///
+    /// This is like [`get_recently_played_all`](Self::get_recently_played_all), but
+    /// `on_progress` is called after each page of tracks, and can cancel the operation by
+    /// returning [`ControlFlow::Break`]. If it is cancelled, the tracks got so far are returned.
+    pub async fn get_recently_played_all_with_progress(
+        &self,
+        max_items: usize,
+        on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Response<Vec<PlayHistory>>, Error> {
+        cursor_pages(
+            self.0.as_client(),
+            max_items,
+            on_progress,
+            |limit, before| async move {
+                Ok(self
+                    .get_recently_played(limit, None, before)
+                    .await?
+                    .map(|page| match page {
+                        Some(page) => (page.items, page.cursors.before),
+                        None => (Vec::new(), None),
+                    }))
+            },
+        )
+        .await
+    }
+
     /// Get the current user's currently playing track (Beta).
     ///
     /// Requires `user-read-currently-playing` and/or `user-read-playback-state`. Returns None if no
@@ -92,7 +152,7 @@ impl Player<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/get-the-users-currently-playing-track/).
     pub async fn get_playing_track(
-        self,
+        &self,
         market: Option<Market>,
     ) -> Result<Response<Option<CurrentlyPlaying>>, Error> {
         self.0
@@ -114,7 +174,7 @@ impl Player<'_> {
     /// not know if it succeeded unless you check.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/pause-a-users-playback/).
-    pub async fn pause(self, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn pause(&self, device_id: Option<&str>) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -129,35 +189,96 @@ impl Player<'_> {
     /// Seek to position in currently playing track (Beta).
     ///
     /// Requires `user-modify-playback-state`. This action completes asynchronously, meaning you will
-    /// not know if it succeeded unless you check.
+    /// not know if it succeeded unless you check. A position past the end of the track skips to the
+    /// next track; use [`PlaybackPosition::within`] to check it first.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/seek-to-position-in-currently-playing-track/).
-    pub async fn seek(self, position: Duration, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn seek(
+        &self,
+        position: PlaybackPosition,
+        device_id: Option<&str>,
+    ) -> Result<(), Error> {
         self.0
-            .send_empty(
-                self.0
-                    .client
-                    .put(endpoint!("/v1/me/player/seek"))
-                    .query(&(
-                        device_id.map(device_query),
-                        ("position_ms", position.as_millis().to_string()),
-                    ))
-                    .body("{}"),
-            )
+            .send_empty(self.seek_request(position, device_id))
             .await
     }
 
+    fn seek_request(&self, position: PlaybackPosition, device_id: Option<&str>) -> RequestBuilder {
+        self.0
+            .client
+            .put(endpoint!("/v1/me/player/seek"))
+            .query(&(
+                device_id.map(device_query),
+                ("position_ms", position.as_millis()),
+            ))
+            .body("{}")
+    }
+
+    /// Seek forwards or backwards in the currently playing track (Beta).
+    ///
+    /// Requires `user-read-playback-state` and `user-modify-playback-state`. `delta` is the number
+    /// of milliseconds to seek by, and can be negative to seek backwards; the resulting position
+    /// is clamped to the bounds of the track. This action completes asynchronously, meaning you
+    /// will not know if it succeeded unless you check.
+    ///
+    /// Returns the position that was seeked to, or `None` if nothing is currently playing (in
+    /// which case nothing is done). If seeking is disallowed in the current context,
+    /// [`Error::ActionDisallowed`] is returned instead of sending the request.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/seek-to-position-in-currently-playing-track/).
+    pub async fn seek_relative(
+        &self,
+        delta: i64,
+        device_id: Option<&str>,
+    ) -> Result<Option<PlaybackPosition>, Error> {
+        let playback = match self.get_playback(None).await?.data {
+            Some(playback) => playback.currently_playing,
+            None => return Ok(None),
+        };
+        let (Some(progress), Some(duration)) = (
+            playback.progress,
+            playback.item.as_ref().and_then(PlayingType::duration),
+        ) else {
+            return Ok(None);
+        };
+
+        playback.actions.check(Disallow::Seeking)?;
+
+        let progress = PlaybackPosition::truncating(progress).as_millis();
+        let duration = PlaybackPosition::truncating(duration).as_millis();
+        let position = PlaybackPosition::from_millis(if delta < 0 {
+            progress.saturating_sub(delta.unsigned_abs())
+        } else {
+            progress.saturating_add(delta.unsigned_abs()).min(duration)
+        });
+
+        self.seek(position, device_id).await?;
+        Ok(Some(position))
+    }
+
     /// Set repeat mode on current playback (Beta).
     ///
     /// Requires `user-modify-playback-state`. This action complete asynchronously, meaning you will
     /// not know if it succeeded unless you check.
     ///
+    /// `actions` are the latest known [`Actions`] of the current playback, if any. If they disallow
+    /// toggling the requested repeat mode, [`Error::ActionDisallowed`] is returned instead of
+    /// sending the request.
+    ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/set-repeat-mode-on-users-playback/).
     pub async fn set_repeat(
-        self,
+        &self,
         state: RepeatState,
+        actions: Option<&Actions>,
         device_id: Option<&str>,
     ) -> Result<(), Error> {
+        if let Some(actions) = actions {
+            actions.check(match state {
+                RepeatState::Track => Disallow::TogglingRepeatTrack,
+                RepeatState::Off | RepeatState::Context => Disallow::TogglingRepeatContext,
+            })?;
+        }
+
         self.0
             .send_empty(
                 self.0
@@ -174,14 +295,8 @@ impl Player<'_> {
     /// Requires `user-modify-playback-state`. This action complete asynchronously, meaning you will
     /// not know if it succeeded unless you check.
     ///
-    /// `volume_percent` is the volume as a percentage, from 0 to 100 inclusive.
-    ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/set-volume-for-users-playback/).
-    pub async fn set_volume(
-        self,
-        volume_percent: i32,
-        device_id: Option<&str>,
-    ) -> Result<(), Error> {
+    pub async fn set_volume(&self, volume: Volume, device_id: Option<&str>) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -189,7 +304,7 @@ impl Player<'_> {
                     .put(endpoint!("/v1/me/player/volume"))
                     .query(&(
                         device_id.map(device_query),
-                        ("volume_percent", volume_percent.to_string()),
+                        ("volume_percent", volume.percent().to_string()),
                     ))
                     .body("{}"),
             )
@@ -204,7 +319,7 @@ impl Player<'_> {
     /// After a successful skip operation, playback will automatically start.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/skip-users-playback-to-next-track/).
-    pub async fn skip_next(self, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn skip_next(&self, device_id: Option<&str>) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -226,7 +341,7 @@ impl Player<'_> {
     /// the track, use [`seek`](Self::seek).
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/skip-users-playback-to-previous-track/).
-    pub async fn skip_prev(self, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn skip_prev(&self, device_id: Option<&str>) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -245,14 +360,14 @@ impl Player<'_> {
     ///
     /// `play`, when set, controls what to play, and what offset in the context to start playing at.
     /// `position` controls how far into the current track to play; if it is longer than the current
-    /// track, then the next track will play. To keep the existing content and position, use
-    /// [`resume`](Self::resume).
+    /// track, then the next track will play, so use [`PlaybackPosition::within`] to check it first.
+    /// To keep the existing content and position, use [`resume`](Self::resume).
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/start-a-users-playback/).
     pub async fn play<I: IntoIterator>(
-        self,
+        &self,
         play: Option<Play<'_, I>>,
-        position: Option<Duration>,
+        position: Option<PlaybackPosition>,
         device_id: Option<&str>,
     ) -> Result<(), Error>
     where
@@ -264,36 +379,88 @@ impl Player<'_> {
         }
 
         #[derive(Serialize)]
-        struct Body {
-            context_uri: Option<String>,
+        struct Body<'a> {
+            context_uri: Option<util::Uri<'a>>,
             offset: Option<Offset>,
-            uris: Option<Vec<String>>,
-            position_ms: Option<u128>,
+            uris: Option<Vec<util::Uri<'a>>>,
+            position_ms: Option<PlaybackPosition>,
         }
 
-        let mut body = Body {
-            context_uri: None,
-            offset: None,
-            uris: None,
-            position_ms: position.map(|duration| duration.as_millis()),
-        };
-
-        if let Some(play) = play {
-            match play {
-                Play::Context(context_type, id, position) => {
-                    body.context_uri = Some(format!("spotify:{}:{}", context_type.as_str(), id));
-                    body.offset = Some(Offset { position });
-                }
-                Play::Tracks(ids) => {
-                    body.uris = Some(
-                        ids.into_iter()
-                            .map(|s| format!("spotify:track:{}", s))
-                            .collect(),
-                    );
+        let (context, ids) = match play {
+            Some(Play::Context(context_type, id, position)) => {
+                if !context_type.is_context() {
+                    return Err(Error::InvalidContextType(context_type));
                 }
+                (Some((context_type, id, position)), None)
             }
+            Some(Play::Tracks(ids)) => (None, Some(ids.into_iter().collect::<Vec<_>>())),
+            None => (None, None),
+        };
+
+        let body = Body {
+            context_uri: context
+                .as_ref()
+                .map(|(context_type, id, _)| util::Uri::context(context_type, id)),
+            offset: context
+                .as_ref()
+                .map(|&(_, _, position)| Offset { position }),
+            uris: ids
+                .as_ref()
+                .map(|ids| ids.iter().map(|id| util::Uri::new("track", id)).collect()),
+            position_ms: position,
+        };
+
+        self.0
+            .send_empty(
+                self.0
+                    .client
+                    .put(endpoint!("/v1/me/player/play"))
+                    .query(&(device_id.map(device_query)))
+                    .body(serde_json::to_string(&body)?),
+            )
+            .await
+    }
+
+    /// Start playing a context at a specific item in it (Beta).
+    ///
+    /// Requires `user-modify-playback-state`. This action complete asynchronously, meaning you will
+    /// not know if it succeeded unless you check.
+    ///
+    /// This is like [`play`](Self::play) with [`Play::Context`], but the item to start playing at
+    /// is given by its URI (for example `spotify:track:4iV5W9uYEdYUVa79Axb7Rh`) instead of its
+    /// position in the context. `position` controls how far into the item to play.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/start-a-users-playback/).
+    pub async fn play_context_at_uri(
+        &self,
+        context_type: ItemType,
+        context_id: &str,
+        item_uri: &str,
+        position: Option<PlaybackPosition>,
+        device_id: Option<&str>,
+    ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        struct Offset<'a> {
+            uri: &'a str,
         }
 
+        #[derive(Serialize)]
+        struct Body<'a> {
+            context_uri: util::Uri<'a>,
+            offset: Offset<'a>,
+            position_ms: Option<PlaybackPosition>,
+        }
+
+        if !context_type.is_context() {
+            return Err(Error::InvalidContextType(context_type));
+        }
+
+        let body = Body {
+            context_uri: util::Uri::context(&context_type, &context_id),
+            offset: Offset { uri: item_uri },
+            position_ms: position,
+        };
+
         self.0
             .send_empty(
                 self.0
@@ -314,7 +481,7 @@ impl Player<'_> {
     /// [`play`](Self::play) instead.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/start-a-users-playback/).
-    pub async fn resume(self, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn resume(&self, device_id: Option<&str>) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -326,13 +493,57 @@ impl Player<'_> {
             .await
     }
 
+    /// Toggle between pausing and resuming playback (Beta).
+    ///
+    /// Requires `user-read-playback-state` and `user-modify-playback-state`. This reads the current
+    /// playback and pauses it if it is playing, or resumes it if it is paused. This action
+    /// completes asynchronously, meaning you will not know if it succeeded unless you check.
+    ///
+    /// Returns whether playback is now playing, or `None` if nothing is currently playing (in which
+    /// case nothing is done). If the action is disallowed in the current context,
+    /// [`Error::ActionDisallowed`] is returned instead of sending the request.
+    pub async fn toggle_playback(&self, device_id: Option<&str>) -> Result<Option<bool>, Error> {
+        let playback = match self.get_playback(None).await?.data {
+            Some(playback) => playback.currently_playing,
+            None => return Ok(None),
+        };
+
+        let (disallow, now_playing) = if playback.is_playing {
+            (Disallow::Pausing, false)
+        } else {
+            (Disallow::Resuming, true)
+        };
+        playback.actions.check(disallow)?;
+
+        if now_playing {
+            self.resume(device_id).await?;
+        } else {
+            self.pause(device_id).await?;
+        }
+
+        Ok(Some(now_playing))
+    }
+
     /// Enable or disable shuffle (Beta).
     ///
     /// Requires `user-modify-playback-state`. This action complete asynchronously, meaning you will
     /// not know if it succeeded unless you check.
     ///
+    /// `actions` are the latest known [`Actions`] of the current playback, if any. If they disallow
+    /// toggling shuffle, [`Error::ActionDisallowed`] is returned instead of sending the request.
+    /// Setting shuffle turns smart shuffle off.
+    ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/toggle-shuffle-for-users-playback/).
-    pub async fn set_shuffle(self, shuffle: bool, device_id: Option<&str>) -> Result<(), Error> {
+    pub async fn set_shuffle(
+        &self,
+        shuffle: bool,
+        actions: Option<&Actions>,
+        device_id: Option<&str>,
+    ) -> Result<(), Error> {
+        if let Some(actions) = actions {
+            actions.check(Disallow::TogglingShuffle)?;
+        }
+
         self.0
             .send_empty(
                 self.0
@@ -347,13 +558,59 @@ impl Player<'_> {
             .await
     }
 
+    /// Set several playback options at once (Beta).
+    ///
+    /// Requires `user-modify-playback-state`. This action complete asynchronously, meaning you will
+    /// not know if it succeeded unless you check.
+    ///
+    /// Each option that is set in `options` is set with [`set_repeat`](Self::set_repeat),
+    /// [`set_shuffle`](Self::set_shuffle) or [`set_volume`](Self::set_volume), concurrently. If any
+    /// of the requests fail, all the errors are returned.
+    pub async fn set_playback_options(
+        &self,
+        options: PlaybackOptions,
+        device_id: Option<&str>,
+    ) -> Result<(), Vec<Error>> {
+        let (repeat, shuffle, volume) = future::join3(
+            async {
+                match options.repeat {
+                    Some(repeat) => self.set_repeat(repeat, None, device_id).await,
+                    None => Ok(()),
+                }
+            },
+            async {
+                match options.shuffle {
+                    Some(shuffle) => self.set_shuffle(shuffle, None, device_id).await,
+                    None => Ok(()),
+                }
+            },
+            async {
+                match options.volume {
+                    Some(volume) => self.set_volume(volume, device_id).await,
+                    None => Ok(()),
+                }
+            },
+        )
+        .await;
+
+        let errors: Vec<_> = vec![repeat, shuffle, volume]
+            .into_iter()
+            .filter_map(Result::err)
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Transfer playback to another device (Beta).
     ///
     /// Requires `user-modify-playback-state`. When `play == true`, playback will happen on the new
     /// device. When `play == false`, playback will continue in its current state.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/player/transfer-a-users-playback/).
-    pub async fn transfer(self, id: &str, play: bool) -> Result<(), Error> {
+    pub async fn transfer(&self, id: &str, play: bool) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -363,18 +620,77 @@ impl Player<'_> {
             )
             .await
     }
+
+    /// Transfer playback to another device and wait until it is active (Beta).
+    ///
+    /// Unlike [`transfer`](Self::transfer), which returns before the transfer has happened, this
+    /// polls the user's devices until the device is active and returns the playback on it, which
+    /// is [`None`] if nothing is playing. Requires `user-modify-playback-state` and
+    /// `user-read-playback-state`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::Timeout`] if the device isn't active after `timeout`. A `timeout` too
+    /// large to be added to the current time, such as [`Duration::MAX`], waits forever.
+    pub async fn transfer_and_wait(
+        &self,
+        id: &str,
+        play: bool,
+        timeout: Duration,
+    ) -> Result<Response<Option<CurrentPlayback>>, Error> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+        let deadline = self.0.clock.now().checked_add(timeout);
+        self.transfer(id, play).await?;
+
+        loop {
+            let active = self
+                .get_devices()
+                .await?
+                .data
+                .into_iter()
+                .any(|device| device.is_active && device.id.as_deref() == Some(id));
+            if active {
+                return self.get_playback(None).await;
+            }
+
+            let wait = if let Some(deadline) = deadline {
+                let now = self.0.clock.now();
+                if now >= deadline {
+                    return Err(Error::Timeout(timeout));
+                }
+                POLL_INTERVAL.min(deadline - now)
+            } else {
+                POLL_INTERVAL
+            };
+            self.0.clock.sleep(wait).await;
+        }
+    }
 }
 
 /// Request to play something.
 #[derive(Debug, Clone)]
 pub enum Play<'c, I> {
-    /// Play from a context (must not be track) with a specified 0-indexed offset to start playing
-    /// at.
+    /// Play from a context with a specified 0-indexed offset to start playing at. The type must be
+    /// a [context type](ItemType::is_context), otherwise playing fails with
+    /// [`Error::InvalidContextType`].
     Context(ItemType, &'c str, usize),
     /// Play a list of tracks.
     Tracks(I),
 }
 
+/// Options to set on the current playback, used by
+/// [`Player::set_playback_options`]. Options that are [`None`] are left unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PlaybackOptions {
+    /// The repeat state.
+    pub repeat: Option<RepeatState>,
+    /// Whether shuffle is on.
+    pub shuffle: Option<bool>,
+    /// The volume.
+    pub volume: Option<Volume>,
+}
+
 fn device_query(device: &str) -> (&'static str, &str) {
     ("device_id", device)
 }
@@ -386,7 +702,27 @@ mod tests {
     use tokio::time;
 
     use crate::endpoints::client;
-    use crate::{ItemType, Market, Play, PlayingType, RepeatState};
+    use crate::{
+        Client, ClientCredentials, ItemType, Market, Play, PlaybackOptions, PlaybackPosition,
+        PlayingType, RepeatState, Volume,
+    };
+
+    #[test]
+    fn seek_query() {
+        let client = Client::new(ClientCredentials {
+            id: String::new(),
+            secret: String::new(),
+        });
+        let request = client
+            .player()
+            .seek_request(PlaybackPosition::from_millis(25_000), Some("device"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().query(),
+            Some("device_id=device&position_ms=25000")
+        );
+    }
 
     #[tokio::test]
     async fn test() {
@@ -405,8 +741,13 @@ mod tests {
         let id = &device.id.as_ref().unwrap();
         if !device.is_active {
             println!("Transferring device to {}...", device.name);
-            player.transfer(id, false).await.unwrap();
+            player
+                .transfer_and_wait(id, false, Duration::from_secs(5))
+                .await
+                .unwrap();
         }
+        let active = player.get_active_device().await.unwrap().data.unwrap();
+        assert_eq!(active.id, device.id);
 
         // Time to wait to assume that the operation has completed
         let wait_time = Duration::from_millis(300);
@@ -419,7 +760,7 @@ mod tests {
                     "3lBPyXvg1hhoJ1REnw80fZ",
                     2,
                 )),
-                Some(Duration::from_secs(10)),
+                Some(PlaybackPosition::from_millis(10_000)),
                 None,
             )
             .await
@@ -453,6 +794,34 @@ mod tests {
         assert_eq!(track.album.id.unwrap(), "3lBPyXvg1hhoJ1REnw80fZ");
         assert_eq!(track.track_number, 3);
 
+        // Seek backwards 5 seconds
+        let position = player.seek_relative(-5000, None).await.unwrap().unwrap();
+        assert!(position >= PlaybackPosition::from_millis(5_000));
+        assert!(position < PlaybackPosition::from_millis(10_000));
+
+        // Play the same track by its URI
+        player
+            .play_context_at_uri(
+                ItemType::Album,
+                "3lBPyXvg1hhoJ1REnw80fZ",
+                &format!("spotify:track:{}", track.id.unwrap()),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        time::sleep(wait_time).await;
+        let playing = player
+            .get_playing_track(Some(Market::FromToken))
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        match playing.item.unwrap() {
+            PlayingType::Track(item) => assert_eq!(item.track_number, 3),
+            _ => panic!(),
+        }
+
         // Play "I am a Paleontologist" and "Ten Tonne Skeleton"
         player
             .play(
@@ -482,7 +851,7 @@ mod tests {
 
         // Seek to 2ms before end
         player
-            .seek(Duration::from_millis(152_106 - 2), None)
+            .seek(PlaybackPosition::from_millis(152_106 - 2), None)
             .await
             .unwrap();
         time::sleep(wait_time).await;
@@ -503,9 +872,17 @@ mod tests {
         );
 
         // Repeat, shuffle, volume
-        player.set_repeat(RepeatState::Track, None).await.unwrap();
-        player.set_shuffle(true, None).await.unwrap();
-        player.set_volume(17, None).await.unwrap();
+        player
+            .set_playback_options(
+                PlaybackOptions {
+                    repeat: Some(RepeatState::Track),
+                    shuffle: Some(true),
+                    volume: Volume::new(17),
+                },
+                None,
+            )
+            .await
+            .unwrap();
         time::sleep(wait_time).await;
         let playback = player
             .get_playback(Some(Market::FromToken))
@@ -515,10 +892,20 @@ mod tests {
             .unwrap();
         assert_eq!(playback.repeat_state, RepeatState::Track);
         assert_eq!(playback.shuffle_state, true);
-        assert_eq!(playback.device.volume_percent.unwrap(), 17);
-        player.set_repeat(RepeatState::Context, None).await.unwrap();
-        player.set_shuffle(false, None).await.unwrap();
-        player.set_volume(73, None).await.unwrap();
+        assert_eq!(playback.device.volume_percent.unwrap().percent(), 17);
+        let actions = &playback.currently_playing.actions;
+        player
+            .set_repeat(RepeatState::Context, Some(actions), None)
+            .await
+            .unwrap();
+        player
+            .set_shuffle(false, Some(actions), None)
+            .await
+            .unwrap();
+        player
+            .set_volume(Volume::new(73).unwrap(), None)
+            .await
+            .unwrap();
         time::sleep(wait_time).await;
         let playback = player
             .get_playback(Some(Market::FromToken))
@@ -528,7 +915,7 @@ mod tests {
             .unwrap();
         assert_eq!(playback.repeat_state, RepeatState::Context);
         assert_eq!(playback.shuffle_state, false);
-        assert_eq!(playback.device.volume_percent.unwrap(), 73);
+        assert_eq!(playback.device.volume_percent.unwrap().percent(), 73);
 
         // Skip previous
         player.skip_prev(None).await.unwrap();
@@ -611,8 +998,16 @@ mod tests {
             .unwrap();
         assert!(playback.currently_playing.is_playing);
 
-        // Pause again
-        player.pause(None).await.unwrap();
+        // Toggle
+        assert_eq!(player.toggle_playback(None).await.unwrap(), Some(false));
+        time::sleep(wait_time).await;
+        let playback = player
+            .get_playback(Some(Market::FromToken))
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert!(!playback.currently_playing.is_playing);
     }
 
     #[tokio::test]
@@ -623,4 +1018,18 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_recent_all() {
+        let recent = client()
+            .player()
+            .get_recently_played_all(60)
+            .await
+            .unwrap()
+            .data;
+        assert!(recent.len() <= 60);
+        assert!(recent
+            .windows(2)
+            .all(|pair| pair[0].played_at >= pair[1].played_at));
+    }
 }
@@ -1,10 +1,16 @@
-use std::fmt::Display;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::ops::ControlFlow;
 
+use futures_util::future::try_join;
 use reqwest::header;
+use serde::{Deserialize, Serialize};
 
+use super::{all_pages, no_progress, remaining_pages, remaining_pages_with_progress, Progress};
 use crate::{
-    Client, Error, Image, Market, Page, Playlist, PlaylistItem, PlaylistItemType,
-    PlaylistSimplified, Response,
+    util, AsClient, Error, Followers, Image, Market, Page, Playlist, PlaylistExport, PlaylistItem,
+    PlaylistItemType, PlaylistItemsPartial, PlaylistPartial, PlaylistSimplified, Response,
+    SnapshotId, Update,
 };
 
 /// Endpoint functions relating to playlists.
@@ -22,9 +28,9 @@ use crate::{
 /// One feature of Spotify is that you cannot delete playlists; you can only unfollow them, hence
 /// there is no `delete_playlist` function.
 #[derive(Debug, Clone, Copy)]
-pub struct Playlists<'a>(pub &'a Client);
+pub struct Playlists<C>(pub C);
 
-impl Playlists<'_> {
+impl<C: AsClient> Playlists<C> {
     /// Add tracks to a playlist.
     ///
     /// Requires `playist-modify-public` if the playlist is public, and `playlist-modify-private` if it
@@ -36,48 +42,126 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/add-tracks-to-playlist/).
     pub async fn add_to_playlist<T: Display, E: Display>(
-        self,
+        &self,
         id: &str,
         tracks: impl IntoIterator<Item = PlaylistItemType<T, E>>,
         position: Option<usize>,
     ) -> Result<String, Error> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            uris: Vec<util::Uri<'a>>,
+            position: Option<usize>,
+        }
+
+        let tracks: Vec<_> = tracks.into_iter().collect();
+
         self.0
             .send_snapshot_id(
                 self.0
                     .client
                     .post(endpoint!("/v1/playlists/{}/tracks", id))
-                    .json(&serde_json::json!({
-                        "uris": tracks.into_iter().map(|track| track.uri()).collect::<Vec<_>>(),
-                        "position": position,
-                    })),
+                    .json(&Body {
+                        uris: tracks.iter().map(PlaylistItemType::uri_ref).collect(),
+                        position,
+                    }),
             )
             .await
     }
 
+    /// Add tracks and episodes to the end of a playlist, skipping the ones that are already in it.
+    ///
+    /// Requires `playlist-read-private` or `playlist-read-collaborative` if the playlist is private
+    /// or collaborative, as well as the scopes of [`add_to_playlist`](Self::add_to_playlist). This
+    /// gets all of the playlist's items, making multiple requests if necessary, and then adds the
+    /// items that aren't already present, 100 at a time. Items repeated in `items` are only added
+    /// once.
+    ///
+    /// Returns the `snapshot_id` of the playlist after the items were added, and the items that
+    /// were skipped.
+    pub async fn add_to_playlist_unique<T: Display, E: Display>(
+        &self,
+        id: &str,
+        items: impl IntoIterator<Item = PlaylistItemType<T, E>>,
+    ) -> Result<(String, Vec<PlaylistItemType<T, E>>), Error> {
+        let Response {
+            data: playlist,
+            expires,
+        } = self.get_playlist(id, None).await?;
+        let mut snapshot_id = playlist.snapshot_id;
+        let first = Response {
+            data: playlist.tracks,
+            expires,
+        };
+        let existing = remaining_pages(first, 100, |limit, offset| {
+            self.get_playlists_items(id, limit, offset, None)
+        })
+        .await?
+        .data;
+
+        let mut uris: HashSet<String> = PlaylistItem::ids(&existing)
+            .iter()
+            .map(PlaylistItemType::uri)
+            .collect();
+        let (new, skipped): (Vec<_>, Vec<_>) =
+            items.into_iter().partition(|item| uris.insert(item.uri()));
+
+        for chunk in new.chunks(100) {
+            let chunk = chunk.iter().map(|item| match item {
+                PlaylistItemType::Track(track) => PlaylistItemType::Track(track),
+                PlaylistItemType::Episode(episode) => PlaylistItemType::Episode(episode),
+            });
+            snapshot_id = self.add_to_playlist(id, chunk, None).await?;
+        }
+
+        Ok((snapshot_id, skipped))
+    }
+
     /// Change a playlist's details.
     ///
     /// Requires `playist-modify-public` if the playlist is public, and `playlist-modify-private` if it
-    /// is private. Each parameter, when Some, changes an attribute of the playlist. A playlist cannot
-    /// be both public and collaborative.
+    /// is private. Each parameter, when Some, changes an attribute of the playlist; the description
+    /// can also be removed with [`Update::Clear`]. A playlist cannot be both public and
+    /// collaborative.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/change-playlist-details/).
     pub async fn change_playlist(
-        self,
+        &self,
         id: &str,
         name: Option<&str>,
         public: Option<bool>,
         collaborative: Option<bool>,
-        description: Option<&str>,
+        description: Update<&str>,
     ) -> Result<(), Error> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            name: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            public: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            collaborative: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<&'a str>,
+        }
+
         self.0
-            .send_empty(self.0.client.put(endpoint!("/v1/playlists/{}", id)).json(
-                &serde_json::json!({
-                    "name": name,
-                    "public": public,
-                    "collaborative": collaborative,
-                    "description": description,
-                }),
-            ))
+            .send_empty(
+                self.0
+                    .client
+                    .put(endpoint!("/v1/playlists/{}", id))
+                    .json(&Body {
+                        name,
+                        public,
+                        collaborative,
+                        // Spotify ignores a null description, so it is removed by setting it to
+                        // the empty string.
+                        description: match description {
+                            Update::Keep => None,
+                            Update::Clear => Some(""),
+                            Update::Set(description) => Some(description),
+                        },
+                    }),
+            )
             .await
     }
 
@@ -88,7 +172,7 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/create-playlist/).
     pub async fn create_playlist(
-        self,
+        &self,
         name: &str,
         public: bool,
         collaborative: bool,
@@ -109,6 +193,88 @@ impl Playlists<'_> {
             .await
     }
 
+    /// Export a playlist and all of its items.
+    ///
+    /// This gets the playlist, and then all of its remaining items, making multiple requests if
+    /// necessary. The result can be stored and later recreated with [`import`](Self::import).
+    pub async fn export(
+        &self,
+        id: &str,
+        market: Option<Market>,
+    ) -> Result<Response<PlaylistExport>, Error> {
+        self.export_with_progress(id, market, no_progress).await
+    }
+
+    /// Export a playlist and all of its items, reporting progress.
+    ///
+    /// This is like [`export`](Self::export), but `on_progress` is called after each page of
+    /// items, and can cancel the export by returning [`ControlFlow::Break`]. If it is cancelled,
+    /// the export only contains the items got so far.
+    pub async fn export_with_progress(
+        &self,
+        id: &str,
+        market: Option<Market>,
+        on_progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Response<PlaylistExport>, Error> {
+        let Response {
+            data:
+                Playlist {
+                    name,
+                    description,
+                    public,
+                    collaborative,
+                    tracks,
+                    ..
+                },
+            expires,
+        } = self.get_playlist(id, market).await?;
+        let first = Response {
+            data: tracks,
+            expires,
+        };
+        let items = remaining_pages_with_progress(first, 100, on_progress, |limit, offset| {
+            self.get_playlists_items(id, limit, offset, market)
+        })
+        .await?;
+
+        Ok(items.map(|items| PlaylistExport {
+            name,
+            description,
+            public,
+            collaborative,
+            items,
+        }))
+    }
+
+    /// Recreate an exported playlist as a new playlist owned by the current user.
+    ///
+    /// Requires `playlist-modify-public` if creating a public playlist, requires
+    /// `playlist-modify-private` if creating a private one. The items are added in order, 100 at a
+    /// time. Local tracks can't be added to playlists through the API, so they are skipped, as are
+    /// items that Spotify returned as null.
+    ///
+    /// Returns the id of the new playlist.
+    pub async fn import(&self, playlist: &PlaylistExport) -> Result<String, Error> {
+        let id = self
+            .create_playlist(
+                &playlist.name,
+                playlist.public.unwrap_or(false),
+                playlist.collaborative,
+                playlist.description.as_deref().unwrap_or(""),
+            )
+            .await?
+            .data
+            .id;
+
+        let items = PlaylistItem::ids(&playlist.items);
+        for chunk in items.chunks(100) {
+            self.add_to_playlist(&id, chunk.iter().copied(), None)
+                .await?;
+        }
+
+        Ok(id)
+    }
+
     /// Get current user's playlists.
     ///
     /// Gets a list of playlists owned or followed by the current Spotify user. Requires
@@ -118,7 +284,7 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-a-list-of-current-users-playlists/).
     pub async fn current_users_playlists(
-        self,
+        &self,
         limit: usize,
         offset: usize,
     ) -> Result<Response<Page<PlaylistSimplified>>, Error> {
@@ -132,13 +298,35 @@ impl Playlists<'_> {
             .await
     }
 
+    /// Get the playlists the current user follows but doesn't own.
+    ///
+    /// This gets all of the current user's playlists, making multiple requests if necessary, and
+    /// removes the ones owned by the current user. It has the same scope requirements as
+    /// [`current_users_playlists`](Self::current_users_playlists).
+    pub async fn followed_playlists(&self) -> Result<Response<Vec<PlaylistSimplified>>, Error> {
+        let (user_id, playlists) = try_join(
+            self.0.current_user_id(),
+            all_pages(50, |limit, offset| {
+                self.current_users_playlists(limit, offset)
+            }),
+        )
+        .await?;
+
+        Ok(playlists.map(|playlists| {
+            playlists
+                .into_iter()
+                .filter(|playlist| playlist.owner.id != user_id)
+                .collect()
+        }))
+    }
+
     /// Get a user's playlists.
     ///
     /// Gets a list of playlists owned or followed by a Spotify user.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-list-users-playlists/).
     pub async fn get_users_playlists(
-        self,
+        &self,
         id: &str,
         limit: usize,
         offset: usize,
@@ -157,7 +345,7 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist/).
     pub async fn get_playlist(
-        self,
+        &self,
         id: &str,
         market: Option<Market>,
     ) -> Result<Response<Playlist>, Error> {
@@ -174,10 +362,84 @@ impl Playlists<'_> {
             .await
     }
 
+    /// Get only some fields of a playlist.
+    ///
+    /// This is useful for large playlists, where for example only the
+    /// [`snapshot_id`](PlaylistPartial::snapshot_id) and the total number of items may be needed.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist/).
+    pub async fn get_playlist_fields(
+        &self,
+        id: &str,
+        fields: &Fields,
+        market: Option<Market>,
+    ) -> Result<Response<PlaylistPartial>, Error> {
+        self.0
+            .send_json(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/playlists/{}", id))
+                    .query(&(
+                        ("fields", fields.to_string()),
+                        market.map(Market::query),
+                        ("additional_types", "track,episode"),
+                    )),
+            )
+            .await
+    }
+
+    /// Get only the current snapshot ID of a playlist.
+    ///
+    /// This is a cheap way to poll a playlist for changes, as nothing else about the playlist is
+    /// fetched.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist/).
+    pub async fn get_snapshot_id(&self, id: &str) -> Result<Response<SnapshotId>, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct Snapshot {
+            snapshot_id: SnapshotId,
+        }
+
+        Ok(self
+            .0
+            .send_json::<Snapshot>(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/playlists/{}", id))
+                    .query(&(("fields", "snapshot_id"),)),
+            )
+            .await?
+            .map(|snapshot| snapshot.snapshot_id))
+    }
+
+    /// Get only the number of followers of a playlist.
+    ///
+    /// Simplified playlists don't include their followers, so this can be used to fill in
+    /// [`PlaylistSimplified::followers`], for example to rank playlists by popularity.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist/).
+    pub async fn get_follower_count(&self, id: &str) -> Result<Response<usize>, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct FollowerCount {
+            followers: Followers,
+        }
+
+        Ok(self
+            .0
+            .send_json::<FollowerCount>(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/playlists/{}", id))
+                    .query(&(("fields", "followers.total"),)),
+            )
+            .await?
+            .map(|count| count.followers.total))
+    }
+
     /// Get a playlist's cover images.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist-cover/).
-    pub async fn get_playlists_images(self, id: &str) -> Result<Response<Vec<Image>>, Error> {
+    pub async fn get_playlists_images(&self, id: &str) -> Result<Response<Vec<Image>>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/playlists/{}/images", id)))
             .await
@@ -189,7 +451,7 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist-tracks/).
     pub async fn get_playlists_items(
-        self,
+        &self,
         id: &str,
         limit: usize,
         offset: usize,
@@ -210,6 +472,36 @@ impl Playlists<'_> {
             .await
     }
 
+    /// Get only some fields of a playlist's items.
+    ///
+    /// Limit must be in the range [1..100]. Paths in `fields` are relative to the page, for
+    /// example `items.track.name`.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/get-playlist-tracks/).
+    pub async fn get_playlists_items_fields(
+        &self,
+        id: &str,
+        fields: &Fields,
+        limit: usize,
+        offset: usize,
+        market: Option<Market>,
+    ) -> Result<Response<PlaylistItemsPartial>, Error> {
+        self.0
+            .send_json(
+                self.0
+                    .client
+                    .get(endpoint!("/v1/playlists/{}/tracks", id))
+                    .query(&(
+                        ("fields", fields.to_string()),
+                        ("limit", limit.to_string()),
+                        ("offset", offset.to_string()),
+                        market.map(Market::query),
+                        ("additional_types", "track,episode"),
+                    )),
+            )
+            .await
+    }
+
     /// Remove tracks from a playlist.
     ///
     /// Requires `playlist-modify-public` if the playlist is public, requires `playlist-modify-private`
@@ -225,13 +517,26 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/remove-tracks-playlist/).
     pub async fn remove_from_playlist<T: Display, E: Display>(
-        self,
+        &self,
         id: &str,
         items: impl IntoIterator<Item = (PlaylistItemType<T, E>, Option<&[usize]>)>,
         snapshot_id: &str,
     ) -> Result<String, Error> {
-        let mut items = items.into_iter().peekable();
-        if items.peek().is_none() {
+        #[derive(Serialize)]
+        struct Item<'a> {
+            uri: util::Uri<'a>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            positions: Option<&'a [usize]>,
+        }
+
+        #[derive(Serialize)]
+        struct Body<'a> {
+            tracks: Vec<Item<'a>>,
+            snapshot_id: &'a str,
+        }
+
+        let items: Vec<_> = items.into_iter().collect();
+        if items.is_empty() {
             return Ok(snapshot_id.to_owned());
         }
 
@@ -240,19 +545,16 @@ impl Playlists<'_> {
                 self.0
                     .client
                     .delete(endpoint!("/v1/playlists/{}/tracks", id))
-                    .json(&serde_json::json!({
-                        "tracks": items.map(|(item, positions)| if let Some(positions) = positions {
-                            serde_json::json!({
-                                "uri": item.uri(),
-                                "positions": positions,
+                    .json(&Body {
+                        tracks: items
+                            .iter()
+                            .map(|(item, positions)| Item {
+                                uri: item.uri_ref(),
+                                positions: *positions,
                             })
-                        } else {
-                            serde_json::json!({
-                                "uri": item.uri(),
-                            })
-                        }).collect::<Vec<_>>(),
-                        "snapshot_id": snapshot_id,
-                    })),
+                            .collect(),
+                        snapshot_id,
+                    }),
             )
             .await
     }
@@ -278,7 +580,7 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/reorder-playlists-tracks/).
     pub async fn reorder_playlist(
-        self,
+        &self,
         id: &str,
         range_start: usize,
         range_length: usize,
@@ -318,18 +620,25 @@ impl Playlists<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/replace-playlists-tracks/).
     pub async fn replace_playlists_items<T: Display, E: Display>(
-        self,
+        &self,
         id: &str,
         items: impl IntoIterator<Item = PlaylistItemType<T, E>>,
     ) -> Result<String, Error> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            uris: Vec<util::Uri<'a>>,
+        }
+
+        let items: Vec<_> = items.into_iter().collect();
+
         self.0
             .send_snapshot_id(
                 self.0
                     .client
                     .put(endpoint!("/v1/playlists/{}/tracks", id))
-                    .json(&serde_json::json!({
-                        "uris": items.into_iter().map(|id| id.uri()).collect::<Vec<_>>(),
-                    })),
+                    .json(&Body {
+                        uris: items.iter().map(PlaylistItemType::uri_ref).collect(),
+                    }),
             )
             .await
     }
@@ -344,7 +653,7 @@ impl Playlists<'_> {
     /// a filename, see [`upload_playlist_cover_file`](Self::upload_playlist_cover_file).
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/upload-custom-playlist-cover/).
-    pub async fn upload_playlist_cover(self, id: &str, image: String) -> Result<(), Error> {
+    pub async fn upload_playlist_cover(&self, id: &str, image: String) -> Result<(), Error> {
         self.0
             .send_empty(
                 self.0
@@ -368,7 +677,7 @@ impl Playlists<'_> {
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/playlists/upload-custom-playlist-cover/).
     #[cfg(feature = "base64")]
     pub async fn upload_playlist_cover_jpeg<T: ?Sized + AsRef<[u8]>>(
-        self,
+        &self,
         id: &str,
         image: &T,
     ) -> Result<(), Error> {
@@ -376,6 +685,54 @@ impl Playlists<'_> {
     }
 }
 
+/// A filter of the fields that Spotify returns, used by functions like
+/// [`Playlists::get_playlist_fields`].
+///
+/// Paths to nested fields are separated by dots, and excluded fields take priority over included
+/// ones. If no fields are included, every field that isn't excluded is returned.
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::Fields;
+///
+/// let fields = Fields::new()
+///     .include("snapshot_id")
+///     .include("tracks.total")
+///     .exclude("tracks.items");
+/// assert_eq!(fields.to_string(), "snapshot_id,tracks.total,!tracks.items");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Fields {
+    fields: Vec<String>,
+}
+
+impl Fields {
+    /// Create a filter that doesn't filter any fields.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Include a field, given by its path.
+    #[must_use]
+    pub fn include(mut self, path: &str) -> Self {
+        self.fields.push(path.to_owned());
+        self
+    }
+    /// Exclude a field, given by its path.
+    #[must_use]
+    pub fn exclude(mut self, path: &str) -> Self {
+        self.fields.push(format!("!{}", path));
+        self
+    }
+}
+
+impl Display for Fields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.fields.join(","))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "base64")]
@@ -385,7 +742,7 @@ mod tests {
     use tokio::time;
 
     use crate::endpoints::client;
-    use crate::{Client, Followers, PlaylistItemType};
+    use crate::{Client, Fields, Followers, PlaylistItemType, Update};
 
     #[tokio::test]
     async fn test() {
@@ -417,6 +774,8 @@ mod tests {
         if users_playlists.total <= 50 {
             assert!(users_playlists.items.iter().any(|p| p.id == playlist.id));
         }
+        let followed_playlists = playlists.followed_playlists().await.unwrap().data;
+        assert!(followed_playlists.iter().all(|p| p.id != playlist.id));
 
         playlists
             .change_playlist(
@@ -424,7 +783,7 @@ mod tests {
                 Some("New Name"),
                 Some(false),
                 Some(true),
-                Some("New Description"),
+                Update::Set("New Description"),
             )
             .await
             .unwrap();
@@ -436,11 +795,24 @@ mod tests {
         assert_eq!(playlist.name, "New Name");
         assert_eq!(playlist.public, Some(false));
         assert_eq!(playlist.collaborative, true);
-        assert_eq!(playlist.description.unwrap(), "New Description");
+        assert_eq!(playlist.description.as_deref(), Some("New Description"));
         assert_eq!(playlist.followers, Followers { total: 0 });
         assert!(playlist.images.is_empty());
         assert_eq!(playlist.tracks.total, 0);
 
+        playlists
+            .change_playlist(&playlist.id, None, None, None, Update::Clear)
+            .await
+            .unwrap();
+        let playlist = playlists
+            .get_playlist(&playlist.id, None)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(playlist.name, "New Name");
+        assert!(playlist.collaborative);
+        assert_eq!(playlist.description.as_deref().unwrap_or(""), "");
+
         // Add "Ten Tonne Skeleton" and "The Middle"
         let snapshot = playlists
             .add_to_playlist(
@@ -464,6 +836,50 @@ mod tests {
         assert_eq!(playlist.snapshot_id, snapshot);
         assert_eq!(playlist.tracks.total, 2);
 
+        let partial = playlists
+            .get_playlist_fields(
+                &playlist.id,
+                &Fields::new().include("snapshot_id").include("tracks.total"),
+                None,
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(partial.snapshot_id.as_deref(), Some(&*snapshot));
+        assert_eq!(partial.name, None);
+        let partial_tracks = partial.tracks.unwrap();
+        assert_eq!(partial_tracks.total, Some(2));
+        assert_eq!(partial_tracks.items, None);
+
+        let partial_items = playlists
+            .get_playlists_items_fields(
+                &playlist.id,
+                &Fields::new().include("items.track.id"),
+                2,
+                0,
+                None,
+            )
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(partial_items.total, None);
+        assert_eq!(
+            playlists.get_snapshot_id(&playlist.id).await.unwrap().data,
+            snapshot
+        );
+        assert_eq!(
+            playlists
+                .get_follower_count(&playlist.id)
+                .await
+                .unwrap()
+                .data,
+            0
+        );
+        assert_eq!(
+            partial_items.items.unwrap()[1]["track"]["id"],
+            "6GG73Jik4jUlQCkKg9JuGO"
+        );
+
         let tracks = playlists
             .get_playlists_items(&playlist.id, 1, 1, None)
             .await
@@ -480,6 +896,23 @@ mod tests {
         assert_eq!(track.is_local, false);
         assert_eq!(track.id.unwrap(), "6GG73Jik4jUlQCkKg9JuGO");
 
+        // Export and import
+        let export = playlists.export(&playlist.id, None).await.unwrap().data;
+        assert_eq!(export.name, "New Name");
+        assert_eq!(export.items.len(), 2);
+        let imported_id = playlists.import(&export).await.unwrap();
+        let imported = playlists.export(&imported_id, None).await.unwrap().data;
+        assert_eq!(imported.items.len(), 2);
+        for (imported, exported) in imported.items.iter().zip(&export.items) {
+            assert_eq!(imported.item, exported.item);
+        }
+        #[cfg(feature = "follow")]
+        client
+            .follow()
+            .unfollow_playlist(&imported_id)
+            .await
+            .unwrap();
+
         // "Blue", "Tredje rikets knarkande granskas", "Mr. Brightside"
         let items: &[PlaylistItemType<_, _>] = &[
             PlaylistItemType::Track("22wRQVOHzHAppfKsDs38nj"),
@@ -514,9 +947,19 @@ mod tests {
             );
         };
 
-        // Replace
+        // Replace, giving the items as URIs
         let mut snapshot = playlists
-            .replace_playlists_items(&playlist.id, items.iter().cloned())
+            .replace_playlists_items(
+                &playlist.id,
+                items.iter().map(|item| match *item {
+                    PlaylistItemType::Track(id) => {
+                        PlaylistItemType::Track(format!("spotify:track:{}", id))
+                    }
+                    PlaylistItemType::Episode(id) => {
+                        PlaylistItemType::Episode(format!("spotify:episode:{}", id))
+                    }
+                }),
+            )
             .await
             .unwrap();
         assert_playlist_order(&client, &playlist.id, &[items[0], items[1], items[2]]).await;
@@ -534,7 +977,7 @@ mod tests {
         assert_playlist_order(&client, &playlist.id, &[items[2], items[1], items[0]]).await;
 
         // Add
-        snapshot = playlists
+        playlists
             .add_to_playlist(&playlist.id, [items[0], items[1]].iter().cloned(), Some(1))
             .await
             .unwrap();
@@ -545,6 +988,25 @@ mod tests {
         )
         .await;
 
+        // Add unique, with "Ten Tonne Skeleton" as an ID and a URI
+        let skeleton = PlaylistItemType::Track("0vjYxBDAcflD0358arIVZG");
+        let skeleton_uri = PlaylistItemType::Track("spotify:track:0vjYxBDAcflD0358arIVZG");
+        let (new_snapshot, skipped) = playlists
+            .add_to_playlist_unique(
+                &playlist.id,
+                [items[1], skeleton, skeleton_uri].iter().copied(),
+            )
+            .await
+            .unwrap();
+        snapshot = new_snapshot;
+        assert_eq!(skipped, [items[1], skeleton_uri]);
+        assert_playlist_order(
+            &client,
+            &playlist.id,
+            &[items[2], items[0], items[1], items[1], items[0], skeleton],
+        )
+        .await;
+
         // Remove
         playlists
             .remove_from_playlist(
@@ -553,6 +1015,7 @@ mod tests {
                     (items[0], None),
                     (items[2], Some(&[0][..])),
                     (items[1], Some(&[2, 3][..])),
+                    (skeleton_uri, None),
                 ]
                 .iter()
                 .cloned(),
@@ -593,6 +1056,7 @@ mod tests {
         }
 
         // Unfollow playlist
+        #[cfg(feature = "follow")]
         client
             .follow()
             .unfollow_playlist(&playlist.id)
@@ -1,12 +1,84 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::time::Instant;
+
+use futures_util::future;
 use itertools::Itertools;
+use serde::de;
+
+use super::remaining_pages;
+use crate::{AnyItem, AsClient, Error, ItemType, Market, Page, Response, SearchResults, Track};
+
+/// The maximum number of results Spotify returns for a search.
+const MAX_SEARCH_DEPTH: usize = 1000;
+
+/// An item found by [`Search::search_markets`], along with the markets it was found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketSearchResult {
+    /// The item, as it was returned for the first market it was found in.
+    pub item: AnyItem,
+    /// The markets the item was found in, in the order they were given.
+    pub markets: Vec<Market>,
+}
+
+/// Merge the results of a search in several markets, used by [`Search::search_markets`].
+fn merge_market_results(
+    results: impl IntoIterator<Item = (Market, SearchResults)>,
+) -> Vec<MarketSearchResult> {
+    let mut merged: Vec<MarketSearchResult> = Vec::new();
+    let mut indices: HashMap<(ItemType, String), usize> = HashMap::new();
+
+    fn page_items<T: Into<AnyItem>>(page: Option<Page<T>>) -> impl Iterator<Item = AnyItem> {
+        page.into_iter().flat_map(|page| page.items).map(Into::into)
+    }
 
-use crate::{Client, Error, ItemType, Market, Response, SearchResults};
+    for (market, results) in results {
+        let SearchResults {
+            artists,
+            albums,
+            tracks,
+            playlists,
+            shows,
+            episodes,
+        } = results;
+        let items = page_items(albums)
+            .chain(page_items(artists))
+            .chain(page_items(playlists))
+            .chain(page_items(tracks))
+            .chain(page_items(shows))
+            .chain(page_items(episodes));
+
+        for item in items {
+            let Some(id) = item.id() else {
+                merged.push(MarketSearchResult {
+                    item,
+                    markets: vec![market],
+                });
+                continue;
+            };
+            let key = (item.item_type(), id.to_owned());
+            if let Some(&i) = indices.get(&key) {
+                if !merged[i].markets.contains(&market) {
+                    merged[i].markets.push(market);
+                }
+            } else {
+                indices.insert(key, merged.len());
+                merged.push(MarketSearchResult {
+                    item,
+                    markets: vec![market],
+                });
+            }
+        }
+    }
+
+    merged
+}
 
 /// Endpoint functions related to searches.
 #[derive(Debug, Clone, Copy)]
-pub struct Search<'a>(pub &'a Client);
+pub struct Search<C>(pub C);
 
-impl Search<'_> {
+impl<C: AsClient> Search<C> {
     /// Search for an item.
     ///
     /// `include_external` specifies whether to include audio content that is hosted externally.
@@ -28,7 +100,7 @@ impl Search<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
     pub async fn search(
-        self,
+        &self,
         query: &str,
         types: impl IntoIterator<Item = ItemType>,
         include_external: bool,
@@ -36,7 +108,7 @@ impl Search<'_> {
         offset: usize,
         market: Option<Market>,
     ) -> Result<Response<SearchResults>, Error> {
-        let types = types.into_iter().map(ItemType::as_str).join(",");
+        let types = types.into_iter().join(",");
         let types = if types.is_empty() {
             "album,artist,playlist,track,show,episode"
         } else {
@@ -58,12 +130,435 @@ impl Search<'_> {
             )))
             .await
     }
+
+    /// Search for items in several markets at once, to compare what is available in each.
+    ///
+    /// This makes a [`search`](Self::search) for each market concurrently with the same
+    /// parameters, and merges the results. Items found in more than one market are only returned
+    /// once, along with every market they were found in. The results are ordered by the first
+    /// market they were found in, and then by how Spotify ordered them.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
+    pub async fn search_markets(
+        &self,
+        query: &str,
+        types: impl IntoIterator<Item = ItemType>,
+        include_external: bool,
+        limit: usize,
+        offset: usize,
+        markets: impl IntoIterator<Item = Market>,
+    ) -> Result<Response<Vec<MarketSearchResult>>, Error> {
+        let types: Vec<ItemType> = types.into_iter().collect();
+        let markets: Vec<Market> = markets.into_iter().collect();
+
+        let responses = future::try_join_all(markets.iter().map(|&market| {
+            self.search(
+                query,
+                types.iter().cloned(),
+                include_external,
+                limit,
+                offset,
+                Some(market),
+            )
+        }))
+        .await?;
+
+        let expires = responses
+            .iter()
+            .map(|response| response.expires)
+            .min()
+            .unwrap_or_else(Instant::now);
+        let results = markets
+            .into_iter()
+            .zip(responses.into_iter().map(|response| response.data));
+
+        Ok(Response {
+            data: merge_market_results(results),
+            expires,
+        })
+    }
+
+    /// Get up to `cap` results of a search for one type of item, making multiple requests if
+    /// necessary.
+    ///
+    /// Use [`SearchResults::total`] to find out how many results a search has.
+    ///
+    /// # Errors
+    ///
+    /// Spotify only returns the first 1000 results of a search. If the search has more results
+    /// than that and `cap` is larger than it, this fails with [`Error::SearchDepthExceeded`]
+    /// after the first request instead of silently returning fewer results.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
+    pub async fn search_all(
+        &self,
+        query: &str,
+        item_type: ItemType,
+        cap: usize,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<AnyItem>>, Error> {
+        let item_type = &item_type;
+        let get_page = |limit, offset| async move {
+            let response = self
+                .search(query, Some(item_type.clone()), false, limit, offset, market)
+                .await?;
+            let expires = response.expires;
+            match response.data.into_items(item_type) {
+                Some(data) => Ok(Response { data, expires }),
+                None => Err(Error::Parse(de::Error::custom(format!(
+                    "search results are missing {} results",
+                    item_type.as_str()
+                )))),
+            }
+        };
+
+        let limit = cap.clamp(1, 50);
+        let mut first = get_page(limit, 0).await?;
+        let wanted = first.data.total.min(cap);
+        if wanted > MAX_SEARCH_DEPTH {
+            return Err(Error::SearchDepthExceeded(MAX_SEARCH_DEPTH));
+        }
+        first.data.total = wanted;
+
+        let mut response = remaining_pages(first, limit, get_page).await?;
+        response.data.truncate(wanted);
+        Ok(response)
+    }
+
+    /// Find the track that best matches an artist and title, such as those in the tags of a local
+    /// music file.
+    ///
+    /// This searches for a few tracks with the artist and title, and picks the one whose
+    /// normalized title and artists match them most closely, preferring more popular tracks if
+    /// there is a tie. It returns [`None`] if no track's title and artists both match.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
+    pub async fn find_track(
+        &self,
+        artist: &str,
+        title: &str,
+        market: Option<Market>,
+    ) -> Result<Response<Option<Track>>, Error> {
+        let query = SearchQuery::new().track(title).artist(artist).to_string();
+        Ok(self
+            .search(&query, Some(ItemType::Track), false, 10, 0, market)
+            .await?
+            .map(|results| {
+                let tracks = results.tracks.map_or_else(Vec::new, |page| page.items);
+                best_match(&tracks, artist, title).cloned()
+            }))
+    }
+}
+
+/// A search query, used by functions like [`Search::search`].
+///
+/// Each filter restricts the results to items whose field matches a value; text values are
+/// quoted, so they can contain spaces. Spotify doesn't allow double quotes inside them, so any
+/// are removed. Use the query's string form ([`Display`]) as the `query` parameter.
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::SearchQuery;
+///
+/// let query = SearchQuery::new()
+///     .keywords("remaster")
+///     .label("Sub Pop")
+///     .years(1988, 1994)
+///     .tag_new();
+/// assert_eq!(
+///     query.to_string(),
+///     "remaster label:\"Sub Pop\" year:1988-1994 tag:new"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SearchQuery {
+    terms: Vec<String>,
+}
+
+impl SearchQuery {
+    /// Create an empty query.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add free-text keywords, which match any field.
+    #[must_use]
+    pub fn keywords(mut self, keywords: &str) -> Self {
+        let keywords = keywords.trim();
+        if !keywords.is_empty() {
+            self.terms.push(keywords.to_owned());
+        }
+        self
+    }
+    /// Only match items whose field `name` matches a text value.
+    fn text(mut self, name: &str, value: &str) -> Self {
+        self.terms
+            .push(format!("{}:\"{}\"", name, value.replace('"', "").trim()));
+        self
+    }
+    /// Only match items with an identifier, which can't contain spaces or quotes.
+    fn code(mut self, name: &str, value: &str) -> Self {
+        let value: String = value
+            .chars()
+            .filter(|&c| !c.is_whitespace() && c != '"')
+            .collect();
+        self.terms.push(format!("{}:{}", name, value));
+        self
+    }
+    /// Only match albums, artists or tracks by an artist.
+    #[must_use]
+    pub fn artist(self, artist: &str) -> Self {
+        self.text("artist", artist)
+    }
+    /// Only match albums or tracks with an album name.
+    #[must_use]
+    pub fn album(self, album: &str) -> Self {
+        self.text("album", album)
+    }
+    /// Only match tracks with a name.
+    #[must_use]
+    pub fn track(self, track: &str) -> Self {
+        self.text("track", track)
+    }
+    /// Only match artists or tracks in a genre.
+    #[must_use]
+    pub fn genre(self, genre: &str) -> Self {
+        self.text("genre", genre)
+    }
+    /// Only match albums released by a record label.
+    #[must_use]
+    pub fn label(self, label: &str) -> Self {
+        self.text("label", label)
+    }
+    /// Only match tracks with an [International Standard Recording
+    /// Code](https://en.wikipedia.org/wiki/International_Standard_Recording_Code).
+    #[must_use]
+    pub fn isrc(self, isrc: &str) -> Self {
+        self.code("isrc", isrc)
+    }
+    /// Only match albums with a [Universal Product
+    /// Code](https://en.wikipedia.org/wiki/Universal_Product_Code).
+    #[must_use]
+    pub fn upc(self, upc: &str) -> Self {
+        self.code("upc", upc)
+    }
+    /// Only match albums, artists or tracks released in a year.
+    #[must_use]
+    pub fn year(mut self, year: u16) -> Self {
+        self.terms.push(format!("year:{}", year));
+        self
+    }
+    /// Only match albums, artists or tracks released in a range of years, inclusive.
+    #[must_use]
+    pub fn years(mut self, from: u16, to: u16) -> Self {
+        self.terms.push(format!("year:{}-{}", from, to));
+        self
+    }
+    /// Only match albums released in the past two weeks.
+    #[must_use]
+    pub fn tag_new(mut self) -> Self {
+        self.terms.push("tag:new".to_owned());
+        self
+    }
+    /// Only match albums with the lowest 10% popularity.
+    #[must_use]
+    pub fn tag_hipster(mut self) -> Self {
+        self.terms.push("tag:hipster".to_owned());
+        self
+    }
+}
+
+impl Display for SearchQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.terms.join(" "))
+    }
+}
+
+/// Normalize a title or artist for comparison, by lowercasing it and only keeping its letters and
+/// digits separated by single spaces.
+fn normalize(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Score how well two normalized strings match: 2 if they are equal, 1 if one contains the
+/// other and 0 otherwise.
+fn match_score(found: &str, wanted: &str) -> u32 {
+    if found == wanted {
+        2
+    } else {
+        u32::from(!found.is_empty() && (found.contains(wanted) || wanted.contains(found)))
+    }
+}
+
+/// Get the track that best matches an artist and title, used by
+/// [`Search::find_track`].
+fn best_match<'a>(tracks: &'a [Track], artist: &str, title: &str) -> Option<&'a Track> {
+    let artist = normalize(artist);
+    let title = normalize(title);
+
+    tracks
+        .iter()
+        .filter_map(|track| {
+            let title_score = match_score(&normalize(&track.name), &title);
+            let artist_score = track
+                .artists
+                .iter()
+                .map(|found| match_score(&normalize(&found.name), &artist))
+                .max()
+                .unwrap_or(0);
+            if title_score == 0 || artist_score == 0 {
+                return None;
+            }
+            Some(((title_score + artist_score, track.popularity), track))
+        })
+        // Take the first of equally good tracks, as Spotify puts the most relevant first.
+        .rev()
+        .max_by_key(|&(score, _)| score)
+        .map(|(_, track)| track)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{best_match, merge_market_results};
     use crate::endpoints::client;
-    use crate::{ItemType, Market};
+    use crate::{
+        AnyItem, Country, Error, ItemType, Market, Page, SearchQuery, SearchResults, Track,
+    };
+
+    #[test]
+    fn test_best_match() {
+        let track: Track =
+            serde_json::from_str(include_str!("../../tests/fixtures/track.json")).unwrap();
+        let mut remix = track.clone();
+        remix.name = "Walk Like an Egyptian (Remix)".to_owned();
+        remix.popularity = 100;
+        let mut cover = track.clone();
+        cover.artists[0].name = "Someone Else".to_owned();
+
+        let tracks = [remix.clone(), cover, track.clone()];
+        assert_eq!(
+            best_match(&tracks, "the bangles", "Walk like an Egyptian!"),
+            Some(&track)
+        );
+        assert_eq!(
+            best_match(&tracks, "Bangles", "Walk Like an Egyptian - Remix"),
+            Some(&remix)
+        );
+        assert_eq!(best_match(&tracks, "The Bangles", "Manic Monday"), None);
+        assert_eq!(
+            best_match(&[], "The Bangles", "Walk Like an Egyptian"),
+            None
+        );
+    }
+
+    #[test]
+    fn search_query() {
+        assert_eq!(SearchQuery::new().to_string(), "");
+        assert_eq!(
+            SearchQuery::new()
+                .keywords("  ")
+                .artist("Miles Davis")
+                .album(" Kind of Blue ")
+                .to_string(),
+            r#"artist:"Miles Davis" album:"Kind of Blue""#
+        );
+        assert_eq!(
+            SearchQuery::new()
+                .track(r#"The "Real" Slim Shady"#)
+                .genre("hip hop")
+                .label("Aftermath")
+                .to_string(),
+            r#"track:"The Real Slim Shady" genre:"hip hop" label:"Aftermath""#
+        );
+        assert_eq!(
+            SearchQuery::new()
+                .isrc("USSM 18600119")
+                .upc("\"886443927087\"")
+                .year(2020)
+                .tag_hipster()
+                .to_string(),
+            "isrc:USSM18600119 upc:886443927087 year:2020 tag:hipster"
+        );
+    }
+
+    #[test]
+    fn market_results() {
+        let track: Track =
+            serde_json::from_str(include_str!("../../tests/fixtures/track.json")).unwrap();
+        let mut other = track.clone();
+        other.id = Some("other".to_owned());
+        let results = |tracks: Vec<Track>| SearchResults {
+            artists: None,
+            albums: None,
+            tracks: Some(Page {
+                href: None,
+                total: tracks.len(),
+                items: tracks,
+                limit: 20,
+                next: None,
+                offset: 0,
+                previous: None,
+            }),
+            playlists: None,
+            shows: None,
+            episodes: None,
+        };
+        let gb = Market::Country(Country::new("GB").unwrap());
+        let us = Market::Country(Country::new("US").unwrap());
+
+        let merged = merge_market_results(vec![
+            (gb, results(vec![track.clone()])),
+            (us, results(vec![other.clone(), track.clone()])),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].item, AnyItem::from(track));
+        assert_eq!(merged[0].markets, [gb, us]);
+        assert_eq!(merged[1].item, AnyItem::from(other));
+        assert_eq!(merged[1].markets, [us]);
+    }
+
+    #[tokio::test]
+    async fn test_search_markets() {
+        let gb = Market::Country(Country::new("GB").unwrap());
+        let us = Market::Country(Country::new("US").unwrap());
+        let results = client()
+            .search()
+            .search_markets(
+                "tania bowra",
+                Some(ItemType::Artist),
+                false,
+                5,
+                0,
+                vec![gb, us],
+            )
+            .await
+            .unwrap()
+            .data;
+        let artist = results
+            .iter()
+            .find(|result| result.item.id() == Some("08td7MxkoHQkXnWAYD8d6Q"))
+            .unwrap();
+        assert_eq!(artist.markets, [gb, us]);
+    }
+
+    #[tokio::test]
+    async fn test_find_track() {
+        let track = client()
+            .search()
+            .find_track("The Bangles", "Walk Like an Egyptian", None)
+            .await
+            .unwrap()
+            .data
+            .unwrap();
+        assert_eq!(track.name, "Walk Like an Egyptian");
+        assert_eq!(track.artists[0].name, "The Bangles");
+    }
 
     #[tokio::test]
     async fn test_search_artist() {
@@ -71,7 +566,7 @@ mod tests {
             .search()
             .search(
                 "tania bowra",
-                [ItemType::Artist].iter().copied(),
+                [ItemType::Artist].iter().cloned(),
                 false,
                 1,
                 0,
@@ -96,7 +591,7 @@ mod tests {
             .search()
             .search(
                 "abba",
-                [ItemType::Album, ItemType::Track].iter().copied(),
+                [ItemType::Album, ItemType::Track].iter().cloned(),
                 true,
                 1,
                 0,
@@ -112,7 +607,7 @@ mod tests {
             .search()
             .search(
                 "doom metal",
-                [ItemType::Playlist].iter().copied(),
+                [ItemType::Playlist].iter().cloned(),
                 false,
                 1,
                 0,
@@ -122,11 +617,44 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_search_all_capped() {
+        let client = client();
+        let search = client.search();
+
+        let total = search
+            .search("love", Some(ItemType::Track), false, 1, 0, None)
+            .await
+            .unwrap()
+            .data
+            .total(&ItemType::Track)
+            .unwrap();
+        assert!(total > 1000);
+
+        let tracks = search
+            .search_all("love", ItemType::Track, 120, None)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(tracks.len(), 120);
+        assert!(tracks
+            .iter()
+            .all(|track| track.item_type() == ItemType::Track));
+
+        match search
+            .search_all("love", ItemType::Track, usize::MAX, None)
+            .await
+        {
+            Err(Error::SearchDepthExceeded(1000)) => {}
+            other => panic!("unexpected result {:?}", other.map(|r| r.data.len())),
+        }
+    }
+
     #[tokio::test]
     async fn test_search_all() {
         client()
             .search()
-            .search("test", [].iter().copied(), false, 3, 2, None)
+            .search("test", [].iter().cloned(), false, 3, 2, None)
             .await
             .unwrap();
     }
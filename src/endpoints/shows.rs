@@ -1,11 +1,10 @@
 use std::fmt::Display;
 
-use isocountry::CountryCode;
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::chunked_sequence;
-use crate::{Client, EpisodeSimplified, Error, Page, Response, Show, ShowSimplified};
+use crate::{AsClient, Country, EpisodeSimplified, Error, Page, Response, Show, ShowSimplified};
 
 /// Endpoint functions relating to shows.
 ///
@@ -13,9 +12,9 @@ use crate::{Client, EpisodeSimplified, Error, Page, Response, Show, ShowSimplifi
 /// user's. If the token is a user's and the market parameter is specified, the user's token will
 /// take precedence.
 #[derive(Debug, Clone, Copy)]
-pub struct Shows<'a>(pub &'a Client);
+pub struct Shows<C>(pub C);
 
-impl Shows<'_> {
+impl<C: AsClient> Shows<C> {
     /// Get information about a show.
     ///
     /// Either the client must have a refresh token or the `market` parameter must be provided,
@@ -24,16 +23,16 @@ impl Shows<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/shows/get-a-show/).
     pub async fn get_show(
-        self,
+        &self,
         id: &str,
-        market: Option<CountryCode>,
+        market: Option<Country>,
     ) -> Result<Response<Show>, Error> {
         self.0
             .send_json(
                 self.0
                     .client
                     .get(endpoint!("/v1/shows/{}", id))
-                    .query(&(market.map(|c| ("market", c.alpha2())),)),
+                    .query(&(market.map(|c| ("market", c)),)),
             )
             .await
     }
@@ -46,23 +45,24 @@ impl Shows<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/shows/get-several-shows/).
     pub async fn get_shows<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
-        market: Option<CountryCode>,
+        market: Option<Country>,
     ) -> Result<Response<Vec<ShowSimplified>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Shows {
             shows: Vec<ShowSimplified>,
         }
 
         chunked_sequence(ids, 50, |mut ids| {
-            let req = self.0.client.get(endpoint!("/v1/shows")).query(&(
-                ("ids", ids.join(",")),
-                market.map(|c| ("market", c.alpha2())),
-            ));
+            let req = self
+                .0
+                .client
+                .get(endpoint!("/v1/shows"))
+                .query(&(("ids", ids.join(",")), market.map(|c| ("market", c))));
             async move { Ok(self.0.send_json::<Shows>(req).await?.map(|res| res.shows)) }
         })
         .await
@@ -76,11 +76,11 @@ impl Shows<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/shows/get-shows-episodes/).
     pub async fn get_show_episodes(
-        self,
+        &self,
         id: &str,
         limit: usize,
         offset: usize,
-        market: Option<CountryCode>,
+        market: Option<Country>,
     ) -> Result<Response<Page<EpisodeSimplified>>, Error> {
         self.0
             .send_json(
@@ -90,7 +90,7 @@ impl Shows<'_> {
                     .query(&(
                         ("limit", limit.to_string()),
                         ("offset", offset.to_string()),
-                        market.map(|c| ("market", c.alpha2())),
+                        market.map(|c| ("market", c)),
                     )),
             )
             .await
@@ -99,15 +99,14 @@ impl Shows<'_> {
 
 #[cfg(test)]
 mod tests {
-    use isocountry::CountryCode;
-
     use crate::endpoints::client;
+    use crate::Country;
 
     #[tokio::test]
     async fn test_get_show() {
         let show = client()
             .shows()
-            .get_show("38bS44xjbVVZ3No3ByF1dJ", Some(CountryCode::AUS))
+            .get_show("38bS44xjbVVZ3No3ByF1dJ", Some(Country::new("AU").unwrap()))
             .await
             .unwrap()
             .data;
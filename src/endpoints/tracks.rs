@@ -1,20 +1,28 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::time::Instant;
 
+use futures_util::stream::{self, StreamExt};
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
 
 use super::chunked_sequence;
-use crate::{AudioAnalysis, AudioFeatures, Client, Error, Market, Response, Track};
+use crate::{
+    AsClient, AudioAnalysis, AudioFeatures, Country, Error, Market, Page, Response, Track,
+    TrackAvailability, TrackPopularity,
+};
 
 /// Endpoint functions related to tracks and audio analysis.
 #[derive(Debug, Clone, Copy)]
-pub struct Tracks<'a>(pub &'a Client);
+pub struct Tracks<C>(pub C);
 
-impl Tracks<'_> {
+impl<C: AsClient> Tracks<C> {
     /// Get audio analysis for a track.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-audio-analysis/).
-    pub async fn get_analysis(self, id: &str) -> Result<Response<AudioAnalysis>, Error> {
+    pub async fn get_analysis(&self, id: &str) -> Result<Response<AudioAnalysis>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/audio-analysis/{}", id)))
             .await
@@ -23,7 +31,7 @@ impl Tracks<'_> {
     /// Get audio features for a track.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-audio-features/).
-    pub async fn get_features_track(self, id: &str) -> Result<Response<AudioFeatures>, Error> {
+    pub async fn get_features_track(&self, id: &str) -> Result<Response<AudioFeatures>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/audio-features/{}", id)))
             .await
@@ -33,13 +41,13 @@ impl Tracks<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-several-audio-features/).
     pub async fn get_features_tracks<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
     ) -> Result<Response<Vec<AudioFeatures>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct ManyAudioFeatures {
             audio_features: Vec<AudioFeatures>,
         }
@@ -65,14 +73,14 @@ impl Tracks<'_> {
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-several-tracks/).
     pub async fn get_tracks<I: IntoIterator>(
-        self,
+        &self,
         ids: I,
         market: Option<Market>,
     ) -> Result<Response<Vec<Track>>, Error>
     where
         I::Item: Display,
     {
-        #[derive(Deserialize)]
+        #[derive(Deserialize, Serialize)]
         struct Tracks {
             tracks: Vec<Track>,
         }
@@ -88,11 +96,105 @@ impl Tracks<'_> {
         .await
     }
 
+    /// Get the popularity of several tracks.
+    ///
+    /// This is like [`get_tracks`](Self::get_tracks), but only the IDs, popularities and numbers
+    /// of available markets of the tracks are kept, which is cheaper when tracking popularity over
+    /// time.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-several-tracks/).
+    pub async fn get_popularity<I: IntoIterator>(
+        &self,
+        ids: I,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<TrackPopularity>>, Error>
+    where
+        I::Item: Display,
+    {
+        #[derive(Deserialize)]
+        struct Track {
+            id: String,
+            popularity: u32,
+            available_markets: Option<Vec<IgnoredAny>>,
+        }
+        #[derive(Deserialize)]
+        struct Tracks {
+            tracks: Vec<Track>,
+        }
+
+        chunked_sequence(ids, 50, |mut ids| {
+            let req = self
+                .0
+                .client
+                .get(endpoint!("/v1/tracks"))
+                .query(&(("ids", ids.join(",")), market.map(Market::query)));
+            async move {
+                Ok(self
+                    .0
+                    .send_json_unverified::<Tracks>(req)
+                    .await?
+                    .map(|res| {
+                        res.tracks
+                            .into_iter()
+                            .map(|track| TrackPopularity {
+                                id: track.id,
+                                popularity: track.popularity,
+                                available_markets: track
+                                    .available_markets
+                                    .map(|markets| markets.len()),
+                            })
+                            .collect()
+                    }))
+            }
+        })
+        .await
+    }
+
+    /// Find tracks by their [ISRC](https://en.wikipedia.org/wiki/International_Standard_Recording_Code).
+    ///
+    /// This searches for tracks with the given ISRC, which may contain hyphens or spaces. Several
+    /// tracks can have the same ISRC (for example, the same recording released on a single and an
+    /// album), so all the matches are returned, most popular first; the first track is the best
+    /// match.
+    ///
+    /// [Reference](https://developer.spotify.com/documentation/web-api/reference/search/search/).
+    pub async fn get_by_isrc(
+        &self,
+        isrc: &str,
+        market: Option<Market>,
+    ) -> Result<Response<Vec<Track>>, Error> {
+        #[derive(Deserialize, Serialize)]
+        struct Results {
+            tracks: Page<Track>,
+        }
+
+        let isrc: String = isrc
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        Ok(self
+            .0
+            .send_json::<Results>(self.0.client.get(endpoint!("/v1/search")).query(&(
+                ("q", format!("isrc:{}", isrc)),
+                ("type", "track"),
+                ("limit", "50"),
+                market.map(Market::query),
+            )))
+            .await?
+            .map(|res| {
+                let mut tracks = res.tracks.items;
+                tracks.sort_by_key(|track| Reverse(track.popularity));
+                tracks
+            }))
+    }
+
     /// Get information about a track.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/tracks/get-several-tracks/).
     pub async fn get_track(
-        self,
+        &self,
         id: &str,
         market: Option<Market>,
     ) -> Result<Response<Track>, Error> {
@@ -105,14 +207,42 @@ impl Tracks<'_> {
             )
             .await
     }
+
+    /// Check whether a track is playable in each of several markets.
+    ///
+    /// This requests the track once for every market, with up to 8 requests at a time, and reports
+    /// whether it is playable, was relinked to a different track or is restricted in each market.
+    pub async fn check_availability(
+        &self,
+        id: &str,
+        markets: &[Country],
+    ) -> Result<Response<HashMap<Country, TrackAvailability>>, Error> {
+        let mut responses = stream::iter(markets)
+            .map(|&market| async move {
+                let response = self.get_track(id, Some(Market::Country(market))).await?;
+                Ok::<_, Error>(response.map(|track| (market, TrackAvailability::of(&track))))
+            })
+            .buffer_unordered(8);
+
+        let mut response = Response {
+            data: HashMap::with_capacity(markets.len()),
+            expires: Instant::now(),
+        };
+
+        while let Some(r) = responses.next().await.transpose()? {
+            let (market, availability) = r.data;
+            response.data.insert(market, availability);
+            response.expires = r.expires;
+        }
+
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use isocountry::CountryCode;
-
     use crate::endpoints::client;
-    use crate::{Market, Mode};
+    use crate::{Country, Market, Mode};
 
     #[tokio::test]
     async fn test_get_track() {
@@ -142,6 +272,74 @@ mod tests {
         assert_eq!(tracks[1].name, "Play That Funky Music");
     }
 
+    #[tokio::test]
+    async fn test_check_availability() {
+        // "Walk Like an Egyptian"
+        let markets = [
+            Country::new("GB").unwrap(),
+            Country::new("US").unwrap(),
+            Country::new("JP").unwrap(),
+        ];
+        let availability = client()
+            .tracks()
+            .check_availability("1Jwc3ODLQxtbnS8M9TflSP", &markets)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(availability.len(), 3);
+        assert!(markets
+            .iter()
+            .all(|market| availability.contains_key(market)));
+    }
+
+    #[tokio::test]
+    async fn test_get_by_isrc() {
+        // "Walk Like an Egyptian"
+        let track = client()
+            .tracks()
+            .get_tracks(&["1Jwc3ODLQxtbnS8M9TflSP"], None)
+            .await
+            .unwrap()
+            .data
+            .remove(0);
+        let isrc = &track.external_ids["isrc"];
+
+        let tracks = client()
+            .tracks()
+            .get_by_isrc(&isrc.to_lowercase(), None)
+            .await
+            .unwrap()
+            .data;
+        assert!(!tracks.is_empty());
+        assert!(tracks
+            .iter()
+            .all(|found| found.external_ids["isrc"] == *isrc));
+    }
+
+    #[tokio::test]
+    async fn test_get_popularity() {
+        let client = client();
+        let ids = &["1Jwc3ODLQxtbnS8M9TflSP", "5uuJruktM9fMdN9Va0DUMl"];
+        let popularity = client
+            .tracks()
+            .get_popularity(ids, None)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(popularity.len(), 2);
+        assert_eq!(popularity[0].id, ids[0]);
+        assert!(popularity[0].popularity <= 100);
+        assert!(popularity[0].available_markets.unwrap() > 0);
+
+        let popularity = client
+            .tracks()
+            .get_popularity(ids, Some(Market::Country(Country::new("GB").unwrap())))
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(popularity[1].available_markets, None);
+    }
+
     #[tokio::test]
     async fn test_relink() {
         // Test track relinking with "Heaven and Hell"
@@ -149,7 +347,7 @@ mod tests {
             .tracks()
             .get_track(
                 "6kLCHFM39wkFjOuyPGLGeQ",
-                Some(Market::Country(CountryCode::USA)),
+                Some(Market::Country(Country::new("US").unwrap())),
             )
             .await
             .unwrap()
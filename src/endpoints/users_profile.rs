@@ -1,17 +1,17 @@
-use crate::{Client, Error, Response, UserPrivate, UserPublic};
+use crate::{AsClient, Error, Response, UserPrivate, UserPublic};
 
 /// Endpoint functions related to users' profiles.
 #[derive(Debug, Clone, Copy)]
-pub struct UsersProfile<'a>(pub &'a Client);
+pub struct UsersProfile<C>(pub C);
 
-impl UsersProfile<'_> {
+impl<C: AsClient> UsersProfile<C> {
     /// Get current user's profile.
     ///
     /// Reading the user's email requires `user-read-email`, reading their country and product
     /// subscription level requires `user-read-private`.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/users-profile/get-current-users-profile/).
-    pub async fn get_current_user(self) -> Result<Response<UserPrivate>, Error> {
+    pub async fn get_current_user(&self) -> Result<Response<UserPrivate>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/me")))
             .await
@@ -20,7 +20,7 @@ impl UsersProfile<'_> {
     /// Get a user's profile.
     ///
     /// [Reference](https://developer.spotify.com/documentation/web-api/reference/users-profile/get-users-profile/).
-    pub async fn get_user(self, id: &str) -> Result<Response<UserPublic>, Error> {
+    pub async fn get_user(&self, id: &str) -> Result<Response<UserPublic>, Error> {
         self.0
             .send_json(self.0.client.get(endpoint!("/v1/users/{}", id)))
             .await
@@ -30,6 +30,7 @@ impl UsersProfile<'_> {
 #[cfg(test)]
 mod tests {
     use crate::endpoints::client;
+    use crate::Client;
 
     #[tokio::test]
     async fn test_get_user() {
@@ -55,7 +56,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_current() {
-        let user = client()
+        let client = client();
+        let user = client
             .users_profile()
             .get_current_user()
             .await
@@ -66,5 +68,24 @@ mod tests {
             user.external_urls["spotify"],
             format!("https://open.spotify.com/user/{}", user.id)
         );
+        assert_eq!(client.current_user_id().await.unwrap(), user.id);
+    }
+
+    #[tokio::test]
+    async fn test_token_snapshot() {
+        let client = client();
+        let id = client.current_user_id().await.unwrap();
+
+        let snapshot = client.token_snapshot().await;
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored = Client::from_token_snapshot(
+            client.credentials.clone(),
+            serde_json::from_str(&json).unwrap(),
+        );
+        assert_eq!(
+            restored.token_snapshot().await.access_token,
+            snapshot.access_token
+        );
+        assert_eq!(restored.current_user_id().await.unwrap(), id);
     }
 }
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::str::FromStr;
 
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::model::{
-    ArtistSimplified, Copyright, DatePrecision, Image, Page, Restrictions, TrackSimplified,
-    TypeAlbum,
+    ArtistSimplified, Copyright, Country, DatePrecision, Genre, Image, Page, ParseEnumError,
+    Restrictions, Timestamp, TrackSimplified, TypeAlbum,
 };
 use crate::util;
 
@@ -25,6 +27,7 @@ macro_rules! inherit_album_simplified {
             /// Known external URLs for this album.
             external_urls: HashMap<String, String>,
             /// The cover art for the album in various sizes, widest first.
+            #[serde(default = "util::lenient_default")]
             images: Vec<Image>,
             /// The name of the album; if the album has been taken down, this is an empty string.
             name: String,
@@ -33,6 +36,9 @@ macro_rules! inherit_album_simplified {
             /// is applied, the original track isn't available in the given market and Spotify didn't have
             /// any tracks to relink it with, then this is Some.
             restrictions: Option<Restrictions>,
+            /// When a market is given in the request, whether the album is playable in it.
+            #[serde(default)]
+            is_playable: Option<bool>,
             /// The item type; `album`.
             #[serde(rename = "type")]
             item_type: TypeAlbum,
@@ -88,13 +94,15 @@ inherit_album_not_local!(
         copyrights: Vec<Copyright>,
         /// Known external IDs for this album.
         external_ids: HashMap<String, String>,
-        /// A list of the genres used to classify the album. For example: "Prog Rock", "Post-Grunge".
+        /// A list of the genres used to classify the album. For example: `prog-rock`, `post-grunge`.
         /// If not yet classified, the array is empty.
-        genres: Vec<String>,
+        #[serde(default = "util::lenient_default")]
+        genres: Vec<Genre>,
         /// The label of the album.
         label: String,
         /// The popularity of the album. The value will be between 0 and 100, with 100 being the most
         /// popular. The popularity is calculated from the popularity of the album's individual tracks.
+        #[serde(default = "util::lenient_default")]
         popularity: u32,
         /// A page of tracks in the album.
         tracks: Page<TrackSimplified>,
@@ -124,6 +132,7 @@ impl Album {
             release_date: Some(self.release_date),
             release_date_precision: Some(self.release_date_precision),
             restrictions: self.restrictions,
+            is_playable: self.is_playable,
             item_type: TypeAlbum,
         }
     }
@@ -148,6 +157,7 @@ impl ArtistsAlbum {
             release_date: Some(self.release_date),
             release_date_precision: Some(self.release_date_precision),
             restrictions: self.restrictions,
+            is_playable: self.is_playable,
             item_type: TypeAlbum,
         }
     }
@@ -201,11 +211,56 @@ impl AlbumGroup {
     }
 }
 
+impl FromStr for AlbumGroup {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(
+            s,
+            "album group",
+            &[
+                Self::Album,
+                Self::Single,
+                Self::Compilation,
+                Self::AppearsOn,
+            ],
+            Self::as_str,
+        )
+    }
+}
+impl TryFrom<&str> for AlbumGroup {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /// Information about an album that has been saved.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SavedAlbum {
     /// When the album was saved.
-    pub added_at: DateTime<Utc>,
+    pub added_at: Timestamp,
     /// Information about the album.
     pub album: Album,
+    /// The country that the album's availability information, such as its
+    /// [`restrictions`](Album::restrictions), was resolved for.
+    ///
+    /// This isn't sent by Spotify; it is filled in by
+    /// [`Library::get_saved_albums`](crate::Library::get_saved_albums) when the request was made
+    /// for a specific country, and is [`None`] otherwise.
+    #[serde(default)]
+    pub market: Option<Country>,
+}
+
+#[cfg(feature = "time")]
+impl SavedAlbum {
+    /// When the album was saved, as a [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `added_at` is outside the years -9999 to 9999 that `time` supports, which Spotify
+    /// never sends.
+    #[must_use]
+    pub fn added_at_time(&self) -> ::time::OffsetDateTime {
+        util::offset_date_time(self.added_at)
+    }
 }
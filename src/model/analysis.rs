@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Formatter};
 use std::time::Duration;
 
@@ -80,6 +82,148 @@ impl Serialize for Mode {
     }
 }
 
+/// One of the numeric attributes of [`AudioFeatures`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AudioFeature {
+    Acousticness,
+    Danceability,
+    Energy,
+    Instrumentalness,
+    Liveness,
+    Loudness,
+    Speechiness,
+    Tempo,
+    Valence,
+}
+
+impl AudioFeature {
+    /// Get the value of this attribute from a track's audio features.
+    #[must_use]
+    pub fn of(self, features: &AudioFeatures) -> f64 {
+        match self {
+            Self::Acousticness => features.acousticness,
+            Self::Danceability => features.danceability,
+            Self::Energy => features.energy,
+            Self::Instrumentalness => features.instrumentalness,
+            Self::Liveness => features.liveness,
+            Self::Loudness => features.loudness,
+            Self::Speechiness => features.speechiness,
+            Self::Tempo => features.tempo,
+            Self::Valence => features.valence,
+        }
+    }
+}
+
+/// Summary statistics of one attribute over a set of [`AudioFeatures`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureStats {
+    /// The mean value.
+    pub mean: f64,
+    /// The median value.
+    pub median: f64,
+    /// The population standard deviation.
+    pub std_dev: f64,
+    /// The smallest value.
+    pub min: f64,
+    /// The largest value.
+    pub max: f64,
+}
+
+impl FeatureStats {
+    /// Calculate the statistics of a non-empty list of values.
+    #[allow(clippy::cast_precision_loss)]
+    fn of(mut values: Vec<f64>) -> Self {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let len = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / len;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / len;
+        let middle = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            f64::midpoint(values[middle - 1], values[middle])
+        } else {
+            values[middle]
+        };
+
+        Self {
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            min: values[0],
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// Summary statistics over a set of [`AudioFeatures`], such as the tracks of a playlist.
+///
+/// The statistics of each attribute are described in the [`AudioFeatures`] documentation.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub struct FeaturesSummary {
+    /// The number of tracks summarized.
+    pub count: usize,
+    pub acousticness: FeatureStats,
+    pub danceability: FeatureStats,
+    pub energy: FeatureStats,
+    pub instrumentalness: FeatureStats,
+    pub liveness: FeatureStats,
+    pub loudness: FeatureStats,
+    pub speechiness: FeatureStats,
+    pub tempo: FeatureStats,
+    pub valence: FeatureStats,
+    /// The number of tracks in each tempo range, keyed by the start of the range in BPM. Each
+    /// range is [`TEMPO_BUCKET_SIZE`](Self::TEMPO_BUCKET_SIZE) BPM wide; for example the key 120
+    /// counts the tracks with a tempo of at least 120 and less than 130 BPM.
+    pub tempo_histogram: BTreeMap<u32, usize>,
+    /// The number of tracks in each key and mode. Keys use [pitch class
+    /// notation](https://en.wikipedia.org/wiki/Pitch_class), so 0 = C, 1 = C♯/D♭, and so on.
+    pub keys: HashMap<(u32, Mode), usize>,
+}
+
+impl FeaturesSummary {
+    /// The width in BPM of each range in [`tempo_histogram`](Self::tempo_histogram).
+    pub const TEMPO_BUCKET_SIZE: u32 = 10;
+
+    /// Summarize a set of audio features. Returns [`None`] if `features` is empty.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn new(features: &[AudioFeatures]) -> Option<Self> {
+        if features.is_empty() {
+            return None;
+        }
+
+        let stats = |feature: AudioFeature| {
+            FeatureStats::of(features.iter().map(|f| feature.of(f)).collect())
+        };
+
+        let mut tempo_histogram = BTreeMap::new();
+        let mut keys = HashMap::new();
+        for feature in features {
+            let bucket =
+                (feature.tempo.max(0.0) as u32) / Self::TEMPO_BUCKET_SIZE * Self::TEMPO_BUCKET_SIZE;
+            *tempo_histogram.entry(bucket).or_insert(0) += 1;
+            *keys.entry((feature.key, feature.mode)).or_insert(0) += 1;
+        }
+
+        Some(Self {
+            count: features.len(),
+            acousticness: stats(AudioFeature::Acousticness),
+            danceability: stats(AudioFeature::Danceability),
+            energy: stats(AudioFeature::Energy),
+            instrumentalness: stats(AudioFeature::Instrumentalness),
+            liveness: stats(AudioFeature::Liveness),
+            loudness: stats(AudioFeature::Loudness),
+            speechiness: stats(AudioFeature::Speechiness),
+            tempo: stats(AudioFeature::Tempo),
+            valence: stats(AudioFeature::Valence),
+            tempo_histogram,
+            keys,
+        })
+    }
+}
+
 mod serde_mode_opt {
     use super::{Mode, ModeVisitor};
     use serde::{
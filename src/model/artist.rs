@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::model::{Followers, Image, TypeArtist};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Followers, Genre, Image, TypeArtist};
+use crate::util;
 
 macro_rules! inherit_artist_simplified {
     ($(#[$attr:meta])* $name:ident { $($(#[$f_attr:meta])* $f_name:ident : $f_ty:ty,)* }) => {
@@ -36,14 +39,17 @@ inherit_artist_simplified!(
         id: String,
         /// Information about the followers of this artist.
         followers: Followers,
-        /// A list of the genres this artist is associated with. For example: "Prog Rock",
-        /// "Post-Grunge". If not yet classified, the array is empty.
-        genres: Vec<String>,
+        /// A list of the genres this artist is associated with. For example: `prog-rock`,
+        /// `post-grunge`. If not yet classified, the array is empty.
+        #[serde(default = "util::lenient_default")]
+        genres: Vec<Genre>,
         /// Images of the artist in various sizes, widest first.
+        #[serde(default = "util::lenient_default")]
         images: Vec<Image>,
         /// The popularity of the artist. The value will be between 0 and 100, with 100 being the most
         /// popular. The artist's popularity is calculated from the popularity of all the artist's
         /// tracks.
+        #[serde(default = "util::lenient_default")]
         popularity: u32,
     }
 );
@@ -65,3 +71,55 @@ impl From<Artist> for ArtistSimplified {
         artist.simplify()
     }
 }
+
+/// A borrowed view of the ID and name of an [`Artist`] or [`ArtistSimplified`], obtained from
+/// [`Artist::to_ref`] or [`ArtistSimplified::to_ref`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArtistRef<'a> {
+    /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// for the artist. Only `None` for local tracks on a playlist.
+    pub id: Option<&'a str>,
+    /// The name of the artist.
+    pub name: &'a str,
+}
+
+impl Artist {
+    /// Borrow the ID and name of the artist.
+    #[must_use]
+    pub fn to_ref(&self) -> ArtistRef<'_> {
+        ArtistRef {
+            id: Some(&self.id),
+            name: &self.name,
+        }
+    }
+}
+impl ArtistSimplified {
+    /// Borrow the ID and name of the artist.
+    #[must_use]
+    pub fn to_ref(&self) -> ArtistRef<'_> {
+        ArtistRef {
+            id: self.id.as_deref(),
+            name: &self.name,
+        }
+    }
+}
+impl<'a> From<&'a Artist> for ArtistRef<'a> {
+    fn from(artist: &'a Artist) -> Self {
+        artist.to_ref()
+    }
+}
+impl<'a> From<&'a ArtistSimplified> for ArtistRef<'a> {
+    fn from(artist: &'a ArtistSimplified) -> Self {
+        artist.to_ref()
+    }
+}
+
+/// The number of followers and popularity of an artist, obtained from
+/// [`Artists::get_follower_counts`](crate::Artists::get_follower_counts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ArtistFollowers {
+    /// The total number of followers of the artist.
+    pub followers: usize,
+    /// The popularity of the artist, between 0 and 100.
+    pub popularity: u32,
+}
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
 use std::time::Duration;
 
 use serde::ser::{SerializeStruct, Serializer};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 // See line 50
 //use chrono::serde::ts_milliseconds;
 
-use crate::model::{Episode, ItemType, Track};
+use crate::model::{Episode, Error, ItemType, ParseEnumError, Track};
 use crate::util;
 
 /// A device object.
@@ -27,11 +32,250 @@ pub struct Device {
     #[serde(rename = "type")]
     pub device_type: DeviceType,
     /// The current volume in percent. It can be [`None`], and I don't know why.
-    pub volume_percent: Option<u32>,
+    pub volume_percent: Option<Volume>,
 }
 
+/// A volume as a percentage, from 0 to 100 inclusive.
+///
+/// Adding to and subtracting from a volume saturates at the bounds.
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::Volume;
+///
+/// let volume = Volume::new(97).unwrap();
+/// assert_eq!((volume + 5).percent(), 100);
+/// assert_eq!((volume - 5).percent(), 92);
+/// assert_eq!(Volume::new(101), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub struct Volume(u8);
+
+impl Volume {
+    /// The lowest volume, 0%.
+    pub const MIN: Self = Self(0);
+    /// The highest volume, 100%.
+    pub const MAX: Self = Self(100);
+
+    /// Create a volume from a percentage, returning [`None`] if it is greater than 100.
+    #[must_use]
+    pub fn new(percent: u32) -> Option<Self> {
+        u8::try_from(percent)
+            .ok()
+            .filter(|&percent| percent <= 100)
+            .map(Self)
+    }
+    /// Create a volume from a percentage, clamping it to 100 if it is greater.
+    #[must_use]
+    pub fn saturating(percent: u32) -> Self {
+        Self::new(percent).unwrap_or(Self::MAX)
+    }
+    /// Get the volume as a percentage, from 0 to 100 inclusive.
+    #[must_use]
+    pub const fn percent(self) -> u8 {
+        self.0
+    }
+}
+
+impl Add<u8> for Volume {
+    type Output = Self;
+    fn add(self, rhs: u8) -> Self::Output {
+        Self::saturating(u32::from(self.0) + u32::from(rhs))
+    }
+}
+impl Sub<u8> for Volume {
+    type Output = Self;
+    fn sub(self, rhs: u8) -> Self::Output {
+        Self(self.0.saturating_sub(rhs))
+    }
+}
+impl AddAssign<u8> for Volume {
+    fn add_assign(&mut self, rhs: u8) {
+        *self = *self + rhs;
+    }
+}
+impl SubAssign<u8> for Volume {
+    fn sub_assign(&mut self, rhs: u8) {
+        *self = *self - rhs;
+    }
+}
+
+impl TryFrom<u32> for Volume {
+    type Error = VolumeOutOfRange;
+    fn try_from(percent: u32) -> Result<Self, Self::Error> {
+        Self::new(percent).ok_or(VolumeOutOfRange(percent))
+    }
+}
+impl From<Volume> for u32 {
+    fn from(volume: Volume) -> Self {
+        volume.0.into()
+    }
+}
+
+impl Display for Volume {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// An error creating a [`Volume`] from a percentage greater than 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VolumeOutOfRange(pub u32);
+
+impl Display for VolumeOutOfRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "volume {}% is greater than 100%", self.0)
+    }
+}
+
+impl error::Error for VolumeOutOfRange {}
+
+/// A position in a track or episode, to the millisecond, used by
+/// [`Player::play`](crate::Player::play) and [`Player::seek`](crate::Player::seek).
+///
+/// Spotify only accepts positions in whole milliseconds, so unlike a [`Duration`] this can't be
+/// more precise than that.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use std::time::Duration;
+///
+/// use aspotify::PlaybackPosition;
+///
+/// let position = PlaybackPosition::from_millis(1500);
+/// assert_eq!(PlaybackPosition::try_from(Duration::from_millis(1500)), Ok(position));
+/// assert!(PlaybackPosition::try_from(Duration::from_micros(1500)).is_err());
+///
+/// assert!(position.within(Duration::from_secs(2)).is_ok());
+/// assert!(position.within(Duration::from_secs(1)).is_err());
+/// ```
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(from = "u64", into = "u64")]
+pub struct PlaybackPosition(u64);
+
+impl PlaybackPosition {
+    /// The start of a track.
+    pub const START: Self = Self(0);
+
+    /// Create a position from a number of milliseconds.
+    #[must_use]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis)
+    }
+    /// Create a position from a duration, rounding it down to the millisecond.
+    ///
+    /// Durations of more than [`u64::MAX`] milliseconds are clamped to that.
+    #[must_use]
+    pub fn truncating(duration: Duration) -> Self {
+        Self(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+    /// Get the position in milliseconds.
+    #[must_use]
+    pub const fn as_millis(self) -> u64 {
+        self.0
+    }
+    /// Get the position as a duration.
+    #[must_use]
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+    /// Check that the position is within a track or episode of the given length.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidPosition::PastEnd`] if the position is after the end.
+    pub fn within(self, length: Duration) -> Result<Self, InvalidPosition> {
+        if self.as_duration() > length {
+            Err(InvalidPosition::PastEnd {
+                position: self,
+                length,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl TryFrom<Duration> for PlaybackPosition {
+    type Error = InvalidPosition;
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        if !duration.subsec_nanos().is_multiple_of(1_000_000) {
+            return Err(InvalidPosition::Imprecise(duration));
+        }
+        u64::try_from(duration.as_millis())
+            .map(Self)
+            .map_err(|_| InvalidPosition::TooLarge(duration))
+    }
+}
+impl From<PlaybackPosition> for Duration {
+    fn from(position: PlaybackPosition) -> Self {
+        position.as_duration()
+    }
+}
+impl From<u64> for PlaybackPosition {
+    fn from(millis: u64) -> Self {
+        Self(millis)
+    }
+}
+impl From<PlaybackPosition> for u64 {
+    fn from(position: PlaybackPosition) -> Self {
+        position.0
+    }
+}
+
+impl Display for PlaybackPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+/// An error creating a [`PlaybackPosition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvalidPosition {
+    /// The duration was more precise than a millisecond.
+    Imprecise(Duration),
+    /// The duration was more than [`u64::MAX`] milliseconds.
+    TooLarge(Duration),
+    /// The position was after the end of the track or episode.
+    PastEnd {
+        /// The position.
+        position: PlaybackPosition,
+        /// The length of the track or episode.
+        length: Duration,
+    },
+}
+
+impl Display for InvalidPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Imprecise(duration) => {
+                write!(
+                    f,
+                    "position {:?} is not a whole number of milliseconds",
+                    duration
+                )
+            }
+            Self::TooLarge(duration) => write!(f, "position {:?} is too large", duration),
+            Self::PastEnd { position, length } => write!(
+                f,
+                "position {} is past the end of an item of length {}ms",
+                position,
+                length.as_millis()
+            ),
+        }
+    }
+}
+
+impl error::Error for InvalidPosition {}
+
 /// A type of device.
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize)]
 #[allow(missing_docs)]
 pub enum DeviceType {
     Computer,
@@ -49,8 +293,63 @@ pub enum DeviceType {
     Unknown,
 }
 
+impl DeviceType {
+    /// Get the type of device as a string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::DeviceType::AudioDongle.as_str(), "AudioDongle");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Computer => "Computer",
+            Self::Tablet => "Tablet",
+            Self::Smartphone => "Smartphone",
+            Self::Speaker => "Speaker",
+            Self::TV => "TV",
+            Self::AVR => "AVR",
+            Self::STB => "STB",
+            Self::AudioDongle => "AudioDongle",
+            Self::GameConsole => "GameConsole",
+            Self::CastVideo => "CastVideo",
+            Self::CastAudio => "CastAudio",
+            Self::Automobile => "Automobile",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        util::deserialize_enum_lenient(
+            deserializer,
+            "device type",
+            &[
+                Self::Computer,
+                Self::Tablet,
+                Self::Smartphone,
+                Self::Speaker,
+                Self::TV,
+                Self::AVR,
+                Self::STB,
+                Self::AudioDongle,
+                Self::GameConsole,
+                Self::CastVideo,
+                Self::CastAudio,
+                Self::Automobile,
+                Self::Unknown,
+            ],
+            Self::as_str,
+            Self::Unknown,
+        )
+    }
+}
+
 /// Information about the currently playing track.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawCurrentlyPlaying", into = "RawCurrentlyPlaying")]
 pub struct CurrentlyPlaying {
     /// The context of the currently playing track. Is [`None`] for example if a private session is
     /// enabled.
@@ -61,17 +360,78 @@ pub struct CurrentlyPlaying {
     // pub timestamp: DateTime<Utc>,
     /// Progress into the currently playing track. Is [`None`] for example if a private session is
     /// enabled.
-    #[serde(rename = "progress_ms", with = "util::serde_duration_millis_option")]
     pub progress: Option<Duration>,
     /// If something is currently playing.
     pub is_playing: bool,
+    /// The type of the currently playing item as Spotify gives it, usually `track`, `episode`,
+    /// `ad` or `unknown`. This is useful for types that this library doesn't know about, which
+    /// don't have an [`item`](Self::item).
+    pub currently_playing_type: String,
     /// The currently playing item. Is [`None`] for example if a private session is enabled.
-    #[serde(flatten)]
     pub item: Option<PlayingType>,
     /// Which actions are disallowed in the current context.
     pub actions: Actions,
 }
 
+/// How [`CurrentlyPlaying`] is represented by Spotify. The item is kept as JSON until
+/// `currently_playing_type` says what it is.
+#[derive(Clone, Serialize, Deserialize)]
+struct RawCurrentlyPlaying {
+    context: Option<Context>,
+    #[serde(rename = "progress_ms", with = "util::serde_duration_millis_option")]
+    progress: Option<Duration>,
+    is_playing: bool,
+    currently_playing_type: String,
+    item: Option<serde_json::Value>,
+    actions: Actions,
+}
+
+impl TryFrom<RawCurrentlyPlaying> for CurrentlyPlaying {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawCurrentlyPlaying) -> Result<Self, Self::Error> {
+        let item = match (raw.item, &*raw.currently_playing_type) {
+            (None, "ad") => Some(PlayingType::Ad(None)),
+            (None, _) => None,
+            (Some(item), "track") => Some(PlayingType::Track(serde_json::from_value(item)?)),
+            (Some(item), "episode") => Some(PlayingType::Episode(serde_json::from_value(item)?)),
+            (Some(item), "ad") => Some(PlayingType::Ad(Some(serde_json::from_value(item)?))),
+            // Types this library doesn't know about are kept if they look like tracks, and
+            // otherwise ignored.
+            (Some(item), _) => serde_json::from_value(item).ok().map(PlayingType::Unknown),
+        };
+        Ok(Self {
+            context: raw.context,
+            progress: raw.progress,
+            is_playing: raw.is_playing,
+            currently_playing_type: raw.currently_playing_type,
+            item,
+            actions: raw.actions,
+        })
+    }
+}
+
+impl From<CurrentlyPlaying> for RawCurrentlyPlaying {
+    fn from(playing: CurrentlyPlaying) -> Self {
+        Self {
+            context: playing.context,
+            progress: playing.progress,
+            is_playing: playing.is_playing,
+            currently_playing_type: playing.currently_playing_type,
+            item: match playing.item {
+                Some(
+                    PlayingType::Track(track)
+                    | PlayingType::Ad(Some(track))
+                    | PlayingType::Unknown(track),
+                ) => serde_json::to_value(track).ok(),
+                Some(PlayingType::Episode(episode)) => serde_json::to_value(episode).ok(),
+                Some(PlayingType::Ad(None)) | None => None,
+            },
+            actions: playing.actions,
+        }
+    }
+}
+
 /// Information about a user's current playback state.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CurrentPlayback {
@@ -81,6 +441,10 @@ pub struct CurrentPlayback {
     pub repeat_state: RepeatState,
     /// Whether shuffle is on.
     pub shuffle_state: bool,
+    /// Whether smart shuffle is on, which adds recommended tracks to the shuffled context. When
+    /// this is true, [`shuffle_state`](Self::shuffle_state) is also true.
+    #[serde(default)]
+    pub smart_shuffle: bool,
     /// The currently playing track.
     #[serde(flatten)]
     pub currently_playing: CurrentlyPlaying,
@@ -94,6 +458,21 @@ pub struct Actions {
     pub disallows: Vec<Disallow>,
 }
 
+impl Actions {
+    /// Check whether an action is allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ActionDisallowed`] if it is disallowed.
+    pub fn check(&self, action: Disallow) -> Result<(), Error> {
+        if self.disallows.contains(&action) {
+            Err(Error::ActionDisallowed(action))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// An action that is currently not able to be performed.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -111,6 +490,60 @@ pub enum Disallow {
     TransferringPlayback,
 }
 
+impl Disallow {
+    /// Get the disallowed action as a `snake_case` string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::Disallow::SkippingNext.as_str(), "skipping_next");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::InterruptingPlayback => "interrupting_playback",
+            Self::Pausing => "pausing",
+            Self::Resuming => "resuming",
+            Self::Seeking => "seeking",
+            Self::SkippingNext => "skipping_next",
+            Self::SkippingPrev => "skipping_prev",
+            Self::TogglingRepeatContext => "toggling_repeat_context",
+            Self::TogglingShuffle => "toggling_shuffle",
+            Self::TogglingRepeatTrack => "toggling_repeat_track",
+            Self::TransferringPlayback => "transferring_playback",
+        }
+    }
+}
+
+impl FromStr for Disallow {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(
+            s,
+            "disallowed action",
+            &[
+                Self::InterruptingPlayback,
+                Self::Pausing,
+                Self::Resuming,
+                Self::Seeking,
+                Self::SkippingNext,
+                Self::SkippingPrev,
+                Self::TogglingRepeatContext,
+                Self::TogglingShuffle,
+                Self::TogglingRepeatTrack,
+                Self::TransferringPlayback,
+            ],
+            Self::as_str,
+        )
+    }
+}
+impl TryFrom<&str> for Disallow {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /// The type of a currently playing item.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(
@@ -123,12 +556,27 @@ pub enum PlayingType {
     Track(Track),
     /// An episode of a show.
     Episode(Episode),
-    /// An advert.
-    Ad(Track),
+    /// An advert. Spotify usually doesn't give any information about adverts.
+    Ad(Option<Track>),
     /// An unknown track type.
     Unknown(Track),
 }
 
+impl PlayingType {
+    /// Get the length of the item. This is only [`None`] for adverts that Spotify didn't give any
+    /// information about.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Track(track) | Self::Ad(Some(track)) | Self::Unknown(track) => {
+                Some(track.duration)
+            }
+            Self::Episode(episode) => Some(episode.duration),
+            Self::Ad(None) => None,
+        }
+    }
+}
+
 /// The context of the current playing track.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Context {
@@ -149,25 +597,7 @@ impl Serialize for Context {
         let mut context = serializer.serialize_struct("Context", 3)?;
         context.serialize_field("type", &self.context_type)?;
         context.serialize_field("external_urls", &self.external_urls)?;
-        context.serialize_field("uri", {
-            struct UriSerialize<'a> {
-                context_type: ItemType,
-                id: &'a str,
-            }
-            impl Serialize for UriSerialize<'_> {
-                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-                    serializer.serialize_str(&format!(
-                        "spotify:{}:{}",
-                        self.context_type.as_str(),
-                        self.id
-                    ))
-                }
-            }
-            &UriSerialize {
-                context_type: self.context_type,
-                id: &self.id,
-            }
-        })?;
+        context.serialize_field("uri", &util::Uri::context(&self.context_type, &self.id))?;
         context.end()
     }
 }
@@ -203,3 +633,21 @@ impl RepeatState {
         }
     }
 }
+
+impl FromStr for RepeatState {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        util::parse_enum(
+            s,
+            "repeat state",
+            &[Self::Off, Self::Track, Self::Context],
+            Self::as_str,
+        )
+    }
+}
+impl TryFrom<&str> for RepeatState {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
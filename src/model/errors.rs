@@ -1,9 +1,12 @@
 use std::error;
 use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use reqwest::StatusCode;
-use serde::{Deserialize, Serialize};
+use http::StatusCode;
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::model::{Disallow, ItemType};
 use crate::util;
 
 /// An error caused by one of the Web API endpoints relating to authentication.
@@ -13,6 +16,9 @@ pub struct AuthError {
     pub error: String,
     /// A more detailed description of the error.
     pub error_description: String,
+    /// The request that caused the error, if it came from a request made by the client.
+    #[serde(skip)]
+    pub request_id: Option<RequestId>,
 }
 
 impl Display for AuthError {
@@ -32,7 +38,13 @@ pub struct EndpointError {
     /// A short description of the error's cause.
     pub message: String,
     /// The reason for the error. Only present for player endpoints.
+    ///
+    /// Spotify sometimes omits the reason for errors caused by there being no active device or
+    /// by the user not having premium, in which case it is inferred from the status and message.
     pub reason: Option<PlayerErrorReason>,
+    /// The request that caused the error, if it came from a request made by the client. This is
+    /// not part of the error's JSON representation.
+    pub request_id: Option<RequestId>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -49,10 +61,17 @@ struct EndpointErrorInternal {
 }
 impl From<EndpointErrorWrapper> for EndpointError {
     fn from(error: EndpointErrorWrapper) -> Self {
+        let EndpointErrorInternal {
+            status,
+            message,
+            reason,
+        } = error.error;
+        let reason = reason.or_else(|| infer_player_reason(status, &message));
         Self {
-            status: error.error.status,
-            message: error.error.message,
-            reason: error.error.reason,
+            status,
+            message,
+            reason,
+            request_id: None,
         }
     }
 }
@@ -68,6 +87,28 @@ impl From<EndpointError> for EndpointErrorWrapper {
     }
 }
 
+/// Infer the reason for a player error that Spotify didn't give a reason for.
+fn infer_player_reason(status: StatusCode, message: &str) -> Option<PlayerErrorReason> {
+    let message = message.to_ascii_lowercase();
+    match status {
+        StatusCode::NOT_FOUND if message.contains("no active device") => {
+            Some(PlayerErrorReason::NoActiveDevice)
+        }
+        StatusCode::FORBIDDEN if message.contains("premium required") => {
+            Some(PlayerErrorReason::PremiumRequired)
+        }
+        _ => None,
+    }
+}
+
+impl EndpointError {
+    /// Get the HTTP status code of the error as a number.
+    #[must_use]
+    pub fn status_code(&self) -> u16 {
+        self.status.as_u16()
+    }
+}
+
 impl Display for EndpointError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         if let Some(reason) = self.reason {
@@ -80,11 +121,41 @@ impl Display for EndpointError {
 
 impl error::Error for EndpointError {}
 
+/// An identifier of a request made by a [`Client`](crate::Client).
+///
+/// Every call that sends a request to Spotify is given a new identifier, which is shared by all of
+/// its retries and by any token refresh it has to do first. It is included in the client's debug
+/// output and in [`AuthError`]s and [`EndpointError`]s, so that an error can be matched to what
+/// the client did to cause it.
+///
+/// Identifiers are unique within a process, not between processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestId(pub u64);
+
+impl RequestId {
+    /// Generate a new request identifier.
+    #[cfg_attr(not(feature = "client"), allow(dead_code))]
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "request {}", self.0)
+    }
+}
+
 /// An error sending a request to a Spotify endpoint.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
     /// An error caused when sending the HTTP request.
+    ///
+    /// This variant is only available when the `client` feature of this library is activated,
+    /// and it is activated by default.
+    #[cfg(feature = "client")]
     Http(reqwest::Error),
     /// An error caused parsing the response.
     Parse(serde_json::error::Error),
@@ -92,30 +163,137 @@ pub enum Error {
     Auth(AuthError),
     /// An error caused by a Spotify endpoint.
     Endpoint(EndpointError),
+    /// The action could not be performed because it is disallowed in the current context.
+    ActionDisallowed(Disallow),
+    /// The request was rate limited and the client ran out of retries. Contains how long Spotify
+    /// said to wait before retrying.
+    RateLimited(Duration),
+    /// Getting every item of a paginated endpoint would require an offset larger than this
+    /// library requests, so the remaining items can't be retrieved. Spotify provides no other
+    /// ordering of the items that could be used to reach them. Contains the largest offset.
+    OffsetLimitExceeded(usize),
+    /// More results of a search were asked for than Spotify returns, so they can't all be
+    /// retrieved. Contains the maximum number of results of a search.
+    SearchDepthExceeded(usize),
+    /// An operation didn't complete in time. Contains how long it was given.
+    Timeout(Duration),
+    /// A link that was requested isn't to the Spotify API, so it wasn't requested. Contains the
+    /// link.
+    UnexpectedUrl(String),
+    /// A playback context isn't an item, so it can't be resolved to one. Contains the type of the
+    /// context.
+    UnresolvableContext(ItemType),
+    /// A type of item that can't be a playback context, such as a track, was used as one.
+    /// Contains the type of item.
+    InvalidContextType(ItemType),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "client")]
             Self::Http(e) => e.fmt(f),
             Self::Parse(e) => e.fmt(f),
             Self::Auth(e) => e.fmt(f),
             Self::Endpoint(e) => e.fmt(f),
+            Self::ActionDisallowed(disallow) => {
+                write!(f, "action is disallowed: {}", disallow.as_str())
+            }
+            Self::RateLimited(retry_after) => write!(
+                f,
+                "rate limited; retry after {} seconds",
+                retry_after.as_secs()
+            ),
+            Self::OffsetLimitExceeded(max) => {
+                write!(f, "items past offset {} can't be retrieved", max)
+            }
+            Self::SearchDepthExceeded(max) => {
+                write!(
+                    f,
+                    "search results past the first {} can't be retrieved",
+                    max
+                )
+            }
+            Self::Timeout(timeout) => {
+                write!(f, "timed out after {} milliseconds", timeout.as_millis())
+            }
+            Self::UnexpectedUrl(url) => write!(f, "{} is not a Spotify API URL", url),
+            Self::UnresolvableContext(context_type) => {
+                write!(f, "{} contexts are not items", context_type)
+            }
+            Self::InvalidContextType(item_type) => {
+                write!(f, "a {} can't be played as a context", item_type)
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Get the reason for the error if it was caused by the Spotify player.
+    #[must_use]
+    pub fn player_reason(&self) -> Option<PlayerErrorReason> {
+        match self {
+            Self::Endpoint(e) => e.reason,
+            _ => None,
+        }
+    }
+
+    /// Get the HTTP status code of the response that caused the error, if there was one.
+    #[must_use]
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Endpoint(e) => Some(e.status),
+            #[cfg(feature = "client")]
+            Self::Http(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Get the identifier of the request that caused the error, if it was caused by an error
+    /// response from Spotify.
+    #[must_use]
+    pub fn request_id(&self) -> Option<RequestId> {
+        match self {
+            Self::Auth(e) => e.request_id,
+            Self::Endpoint(e) => e.request_id,
+            _ => None,
         }
     }
+
+    /// Whether the error was caused by the user not having an active device.
+    #[must_use]
+    pub fn is_no_active_device(&self) -> bool {
+        self.player_reason() == Some(PlayerErrorReason::NoActiveDevice)
+    }
+
+    /// Whether the error was caused by the action requiring premium, which the user doesn't have.
+    #[must_use]
+    pub fn is_premium_required(&self) -> bool {
+        self.player_reason() == Some(PlayerErrorReason::PremiumRequired)
+    }
 }
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         Some(match self {
+            #[cfg(feature = "client")]
             Self::Http(e) => e,
             Self::Parse(e) => e,
             Self::Auth(e) => e,
             Self::Endpoint(e) => e,
+            Self::ActionDisallowed(_)
+            | Self::RateLimited(_)
+            | Self::OffsetLimitExceeded(_)
+            | Self::SearchDepthExceeded(_)
+            | Self::Timeout(_)
+            | Self::UnexpectedUrl(_)
+            | Self::UnresolvableContext(_)
+            | Self::InvalidContextType(_) => return None,
         })
     }
 }
 
+#[cfg(feature = "client")]
 impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Self {
         Self::Http(error)
@@ -137,8 +315,39 @@ impl From<EndpointError> for Error {
     }
 }
 
+/// An error parsing a string into one of the enums of this crate, such as [`RepeatState`] or
+/// [`ItemType`], or into a code such as [`Country`].
+///
+/// [`RepeatState`]: crate::RepeatState
+/// [`ItemType`]: crate::ItemType
+/// [`Country`]: crate::Country
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::RepeatState;
+///
+/// assert_eq!("track".parse::<RepeatState>(), Ok(RepeatState::Track));
+/// assert!("everything".parse::<RepeatState>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEnumError {
+    /// A description of what was expected, for example `repeat state`.
+    pub expected: &'static str,
+    /// The string that could not be parsed.
+    pub value: String,
+}
+
+impl Display for ParseEnumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} '{}'", self.expected, self.value)
+    }
+}
+
+impl error::Error for ParseEnumError {}
+
 /// A reason for an error caused by the Spotify player.
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PlayerErrorReason {
     /// There is no previous track in the context.
@@ -180,6 +389,70 @@ pub enum PlayerErrorReason {
     Unknown,
 }
 
+impl PlayerErrorReason {
+    /// Get the reason as a string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::PlayerErrorReason::NoPrevTrack.as_str(), "NO_PREV_TRACK");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::NoPrevTrack => "NO_PREV_TRACK",
+            Self::NoNextTrack => "NO_NEXT_TRACK",
+            Self::NoSpecificTrack => "NO_SPECIFIC_TRACK",
+            Self::AlreadyPaused => "ALREADY_PAUSED",
+            Self::NotPaused => "NOT_PAUSED",
+            Self::NotPlayingLocally => "NOT_PLAYING_LOCALLY",
+            Self::NotPlayingTrack => "NOT_PLAYING_TRACK",
+            Self::NotPlayingContext => "NOT_PLAYING_CONTEXT",
+            Self::EndlessContext => "ENDLESS_CONTEXT",
+            Self::ContextDisallow => "CONTEXT_DISALLOW",
+            Self::AlreadyPlaying => "ALREADY_PLAYING",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::RemoteControlDisallow => "REMOTE_CONTROL_DISALLOW",
+            Self::DeviceNotControllable => "DEVICE_NOT_CONTROLLABLE",
+            Self::VolumeControlDisallow => "VOLUME_CONTROL_DISALLOW",
+            Self::NoActiveDevice => "NO_ACTIVE_DEVICE",
+            Self::PremiumRequired => "PREMIUM_REQUIRED",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PlayerErrorReason {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        util::deserialize_enum_lenient(
+            deserializer,
+            "player error reason",
+            &[
+                Self::NoPrevTrack,
+                Self::NoNextTrack,
+                Self::NoSpecificTrack,
+                Self::AlreadyPaused,
+                Self::NotPaused,
+                Self::NotPlayingLocally,
+                Self::NotPlayingTrack,
+                Self::NotPlayingContext,
+                Self::EndlessContext,
+                Self::ContextDisallow,
+                Self::AlreadyPlaying,
+                Self::RateLimited,
+                Self::RemoteControlDisallow,
+                Self::DeviceNotControllable,
+                Self::VolumeControlDisallow,
+                Self::NoActiveDevice,
+                Self::PremiumRequired,
+                Self::Unknown,
+            ],
+            Self::as_str,
+            Self::Unknown,
+        )
+    }
+}
+
 impl Display for PlayerErrorReason {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -0,0 +1,137 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::str::{self, FromStr};
+
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::model::ParseEnumError;
+
+macro_rules! two_letter_code {
+    ($(#[$attr:meta])* $name:ident, $expected:literal, $normalize:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name([u8; 2]);
+
+        impl $name {
+            /// Parse a code, which must be two ASCII letters in any case.
+            ///
+            /// # Errors
+            ///
+            /// Fails if the code is not two ASCII letters.
+            pub fn new(code: &str) -> Result<Self, ParseEnumError> {
+                match code.as_bytes() {
+                    &[a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+                        Ok(Self([a.$normalize(), b.$normalize()]))
+                    }
+                    _ => Err(ParseEnumError {
+                        expected: $expected,
+                        value: code.to_owned(),
+                    }),
+                }
+            }
+            /// Get the code as a string.
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                // The code is always ASCII.
+                str::from_utf8(&self.0).unwrap()
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseEnumError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::new(s)
+            }
+        }
+        impl TryFrom<&str> for $name {
+            type Error = ParseEnumError;
+            fn try_from(s: &str) -> Result<Self, Self::Error> {
+                Self::new(s)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct CodeVisitor;
+                impl<'de> Visitor<'de> for CodeVisitor {
+                    type Value = $name;
+                    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                        f.write_str(concat!("an ", $expected))
+                    }
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                        $name::new(v).map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+                    }
+                }
+                deserializer.deserialize_str(CodeVisitor)
+            }
+        }
+    };
+}
+
+two_letter_code!(
+    /// An [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2) country code,
+    /// such as `GB`. It is stored in uppercase.
+    ///
+    /// Any two letters are accepted, so codes that are newer than this library can still be used.
+    /// When the `isocountry` feature of this library is activated (it is by default) it can be
+    /// converted to and from [`isocountry::CountryCode`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aspotify::Country;
+    ///
+    /// assert_eq!(Country::new("gb").unwrap().as_str(), "GB");
+    /// assert!(Country::new("GBR").is_err());
+    /// ```
+    Country,
+    "ISO 3166-1 alpha-2 country code",
+    to_ascii_uppercase
+);
+
+two_letter_code!(
+    /// An [ISO 639-1](https://en.wikipedia.org/wiki/ISO_639-1) language code, such as `en`. It is
+    /// stored in lowercase.
+    ///
+    /// Any two letters are accepted, so codes that are newer than this library can still be used.
+    /// When the `isolanguage-1` feature of this library is activated (it is by default) it can be
+    /// converted from [`isolanguage_1::LanguageCode`].
+    Language,
+    "ISO 639-1 language code",
+    to_ascii_lowercase
+);
+
+#[cfg(feature = "isocountry")]
+impl From<isocountry::CountryCode> for Country {
+    fn from(code: isocountry::CountryCode) -> Self {
+        let code = code.alpha2().as_bytes();
+        Self([code[0], code[1]])
+    }
+}
+#[cfg(feature = "isocountry")]
+impl TryFrom<Country> for isocountry::CountryCode {
+    type Error = isocountry::CountryCodeParseErr;
+    fn try_from(country: Country) -> Result<Self, Self::Error> {
+        Self::for_alpha2(country.as_str())
+    }
+}
+
+#[cfg(feature = "isolanguage-1")]
+impl From<isolanguage_1::LanguageCode> for Language {
+    fn from(code: isolanguage_1::LanguageCode) -> Self {
+        let code = code.code().as_bytes();
+        Self([code[0], code[1]])
+    }
+}
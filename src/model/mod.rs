@@ -2,14 +2,25 @@
 //! Model](https://developer.spotify.com/documentation/web-api/reference/object-model/), in
 //! deserializable Rust structures.
 
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::de::{self, DeserializeOwned, Deserializer};
 use serde::{Deserialize, Serialize};
 
+use crate::util;
+
 pub use album::*;
 pub use analysis::*;
 pub use artist::*;
 pub use consts::*;
 pub use device::*;
 pub use errors::*;
+pub use locale::*;
 pub use playlist::*;
 pub use show::*;
 pub use track::*;
@@ -33,8 +44,13 @@ mod analysis;
 mod artist;
 mod device;
 mod errors;
+mod locale;
 mod playlist;
 mod show;
+#[cfg(feature = "test-util")]
+mod stub;
+#[cfg(test)]
+mod tests;
 mod track;
 mod user;
 
@@ -98,9 +114,19 @@ pub mod consts {
     }
 }
 
+/// A point in time, such as when an item was saved.
+///
+/// The types that contain one also have methods that return it as a
+/// [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html) if the `time` feature of this
+/// library is activated.
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
 /// A category of music, for example "Mood", "Top Lists", "Workout", et cetera.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Category {
+    /// A link to the Web API endpoint returning full details of the category.
+    #[serde(default = "util::lenient_default")]
+    pub href: String,
     /// The category icon, in various sizes, probably with widest first (although this is not
     /// specified by the Web API documentation).
     pub icons: Vec<Image>,
@@ -111,6 +137,14 @@ pub struct Category {
     pub name: String,
 }
 
+impl Category {
+    /// Get the largest of the category's icons.
+    #[must_use]
+    pub fn largest_icon(&self) -> Option<&Image> {
+        Image::largest(&self.icons)
+    }
+}
+
 /// The copyright information for a resource.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Copyright {
@@ -175,22 +209,81 @@ pub struct Image {
     pub width: Option<usize>,
 }
 
+impl Image {
+    /// Get the widest image out of a list of images, such as the `images` of an album.
+    ///
+    /// Images with an unknown width are only chosen if no image has a known width.
+    #[must_use]
+    pub fn largest(images: &[Self]) -> Option<&Self> {
+        images.iter().max_by_key(|image| image.width)
+    }
+
+    /// Get the most suitable image out of a list of images for displaying at the given width.
+    ///
+    /// This is the narrowest image that is at least `width` pixels wide, or the widest image if
+    /// none of them are wide enough.
+    #[must_use]
+    pub fn best_for(images: &[Self], width: usize) -> Option<&Self> {
+        images
+            .iter()
+            .filter(|image| image.width.is_some_and(|w| w >= width))
+            .min_by_key(|image| image.width)
+            .or_else(|| Self::largest(images))
+    }
+}
+
 /// A page of items.
+///
+/// The links to this page and the pages around it include all the parameters of the request
+/// that returned it, such as the market, so they can be requested again with
+/// [`Client::get_page`](crate::Client::get_page).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Page<T> {
+    /// A link to the Web API endpoint returning this page. [`None`] if Spotify didn't send it.
+    #[serde(default)]
+    pub href: Option<String>,
     /// The items in the page.
     pub items: Vec<T>,
     /// The maximum number of items in the page, as set by the request or a default value.
     pub limit: usize,
+    /// A link to the next page, if there is one.
+    #[serde(default)]
+    pub next: Option<String>,
     /// The offset of the page in the items.
     pub offset: usize,
+    /// A link to the previous page, if there is one.
+    #[serde(default)]
+    pub previous: Option<String>,
     /// The total number of items.
     pub total: usize,
 }
 
+impl<T> Page<T> {
+    /// Convert each item in the page, keeping the page's other fields.
+    pub fn map_items<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            href: self.href,
+            items: self.items.into_iter().map(f).collect(),
+            limit: self.limit,
+            next: self.next,
+            offset: self.offset,
+            previous: self.previous,
+            total: self.total,
+        }
+    }
+}
+
 /// A page of items, using a cursor to find the next page.
+///
+/// Like [`Page`], the links include all the parameters of the request that returned it.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CursorPage<T> {
+    /// A link to the Web API endpoint returning this page. [`None`] if Spotify didn't send it.
+    #[serde(default)]
+    pub href: Option<String>,
+    /// A link to the next page, if there is one.
+    #[serde(default)]
+    pub next: Option<String>,
     /// The items in the page.
     pub items: Vec<T>,
     /// The maximum number of items in the page, as set by the request or a default value.
@@ -209,8 +302,16 @@ pub struct Cursor {
 }
 
 /// A page of items, using a cursor to move backwards and forwards.
+///
+/// Like [`Page`], the links include all the parameters of the request that returned it.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TwoWayCursorPage<T> {
+    /// A link to the Web API endpoint returning this page. [`None`] if Spotify didn't send it.
+    #[serde(default)]
+    pub href: Option<String>,
+    /// A link to the next page, if there is one.
+    #[serde(default)]
+    pub next: Option<String>,
     /// The items in the page.
     pub items: Vec<T>,
     /// The maximum number of items in the page, as set by the request or a default value.
@@ -235,6 +336,10 @@ pub struct Recommendations {
     pub seeds: Vec<RecommendationSeed>,
     /// An array of simplified track objects.
     pub tracks: Vec<TrackSimplified>,
+    /// Any other fields in the response, which aren't documented by Spotify. These are kept
+    /// to help with debugging the quality of recommendations.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// How the recommendation was chosen.
@@ -245,6 +350,9 @@ pub struct RecommendationSeed {
     pub after_filtering_size: usize,
     /// The number of tracks available after relinking for regional availability.
     pub after_relinking_size: usize,
+    /// A link to the full track or artist data for this seed. [`None`] for genre seeds.
+    #[serde(default)]
+    pub href: Option<String>,
     /// The id used to select this seed, given by the user.
     pub id: String,
     /// The number of recommended tracks available for this seed.
@@ -265,6 +373,111 @@ pub enum SeedType {
     Genre,
 }
 
+/// A genre, such as `hip-hop`.
+///
+/// Spotify names genres inconsistently: artists and albums have genres like `hip hop`, while
+/// recommendations are seeded with genres like `hip-hop`. Genres keep the name Spotify gave them,
+/// but are compared, hashed and displayed in a normalized form (trimmed, lowercased and with
+/// whitespace replaced by hyphens), so that an artist's genres can be passed directly to
+/// [`Browse::get_recommendations`](crate::Browse::get_recommendations). Use
+/// [`is_seed`](Self::is_seed) to check whether a genre can be used as a seed.
+///
+/// # Examples
+///
+/// ```
+/// use aspotify::Genre;
+///
+/// let genre = Genre::new(" Hip Hop ");
+/// assert_eq!(genre.as_str(), " Hip Hop ");
+/// assert_eq!(genre.normalized(), "hip-hop");
+/// assert_eq!(Genre::new("hip hop"), Genre::new("hip-hop"));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Genre(String);
+
+impl Genre {
+    /// Create a genre from its name.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+    /// Get the name of the genre as it was given.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    /// Get the normalized name of the genre.
+    #[must_use]
+    pub fn normalized(&self) -> String {
+        self.normalized_chars().collect()
+    }
+    /// Whether the genre is in a list of genre seeds, such as the one returned by
+    /// [`Browse::get_genre_seeds`](crate::Browse::get_genre_seeds).
+    #[must_use]
+    pub fn is_seed(&self, seeds: &[Self]) -> bool {
+        seeds.contains(self)
+    }
+
+    fn normalized_chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.split_whitespace().enumerate().flat_map(|(i, word)| {
+            Some('-')
+                .filter(|_| i > 0)
+                .into_iter()
+                .chain(word.chars().flat_map(char::to_lowercase))
+        })
+    }
+}
+
+impl PartialEq for Genre {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized_chars().eq(other.normalized_chars())
+    }
+}
+impl Eq for Genre {}
+
+impl PartialOrd for Genre {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Genre {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized_chars().cmp(other.normalized_chars())
+    }
+}
+
+impl Hash for Genre {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for c in self.normalized_chars() {
+            c.hash(state);
+        }
+        state.write_u8(0xff);
+    }
+}
+
+impl Display for Genre {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.normalized_chars().try_for_each(|c| f.write_char(c))
+    }
+}
+
+impl From<&str> for Genre {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+impl From<String> for Genre {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+impl From<Genre> for String {
+    fn from(genre: Genre) -> Self {
+        genre.0
+    }
+}
+
 /// How precise a date measurement is.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -277,16 +490,88 @@ pub enum DatePrecision {
     Day,
 }
 
-/// Restrictions applied to a track due to markets.
+/// Restrictions applied to a track, album or episode.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Restrictions {
     /// Why the restriction was applied.
-    pub reason: String,
+    pub reason: RestrictionReason,
+}
+
+/// Why a track, album or episode is restricted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RestrictionReason {
+    /// The item isn't available in the market.
+    Market,
+    /// The item isn't available for the user's subscription type.
+    Product,
+    /// The item is explicit and the user's account is set to not play explicit content.
+    Explicit,
+    /// A reason that Spotify didn't document when this library was written.
+    Unknown(String),
+}
+
+impl RestrictionReason {
+    /// Get the reason as a string, as Spotify represents it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(aspotify::RestrictionReason::Market.as_str(), "market");
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Market => "market",
+            Self::Product => "product",
+            Self::Explicit => "explicit",
+            Self::Unknown(reason) => reason,
+        }
+    }
+}
+
+impl Display for RestrictionReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for RestrictionReason {
+    fn from(reason: &str) -> Self {
+        match reason {
+            "market" => Self::Market,
+            "product" => Self::Product,
+            "explicit" => Self::Explicit,
+            _ => Self::Unknown(reason.to_owned()),
+        }
+    }
+}
+impl From<String> for RestrictionReason {
+    fn from(reason: String) -> Self {
+        match reason.as_str() {
+            "market" => Self::Market,
+            "product" => Self::Product,
+            "explicit" => Self::Explicit,
+            _ => Self::Unknown(reason),
+        }
+    }
+}
+impl From<RestrictionReason> for String {
+    fn from(reason: RestrictionReason) -> Self {
+        match reason {
+            RestrictionReason::Unknown(reason) => reason,
+            known => known.as_str().to_owned(),
+        }
+    }
 }
 
 /// A type of item in the Spotify model.
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// Types that this library doesn't know about, such as ones Spotify added later, are
+/// [`Other`](Self::Other). Parsing an `ItemType` with [`FromStr`] or [`TryFrom<&str>`] only
+/// accepts known types, while converting a [`String`] with [`From`] accepts any type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 #[allow(missing_docs)]
 pub enum ItemType {
     Album,
@@ -295,6 +580,13 @@ pub enum ItemType {
     Track,
     Show,
     Episode,
+    /// A user's saved tracks (Liked Songs), which is only a playback [`Context`] and not an item.
+    /// Its ID is the ID of the user.
+    Collection,
+    /// A user, which can be the owner of a playlist or the user whose collection a context is.
+    User,
+    /// A type that this library doesn't know about.
+    Other(String),
 }
 
 impl ItemType {
@@ -304,7 +596,7 @@ impl ItemType {
     /// assert_eq!(aspotify::ItemType::Episode.as_str(), "episode");
     /// ```
     #[must_use]
-    pub const fn as_str(self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Self::Album => "album",
             Self::Artist => "artist",
@@ -312,10 +604,276 @@ impl ItemType {
             Self::Track => "track",
             Self::Show => "show",
             Self::Episode => "episode",
+            Self::Collection => "collection",
+            Self::User => "user",
+            Self::Other(item_type) => item_type,
+        }
+    }
+    /// Whether the type can be played as a playback context with [`Play::Context`]: an album,
+    /// artist, playlist, show or collection.
+    ///
+    /// [`Play::Context`]: crate::Play::Context
+    #[must_use]
+    pub fn is_context(&self) -> bool {
+        matches!(
+            self,
+            Self::Album | Self::Artist | Self::Playlist | Self::Show | Self::Collection
+        )
+    }
+}
+
+impl Display for ItemType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ItemType {
+    fn known(item_type: &str) -> Option<Self> {
+        Some(match item_type {
+            "album" => Self::Album,
+            "artist" => Self::Artist,
+            "playlist" => Self::Playlist,
+            "track" => Self::Track,
+            "show" => Self::Show,
+            "episode" => Self::Episode,
+            "collection" => Self::Collection,
+            "user" => Self::User,
+            _ => return None,
+        })
+    }
+}
+
+impl From<String> for ItemType {
+    fn from(item_type: String) -> Self {
+        Self::known(&item_type).unwrap_or(Self::Other(item_type))
+    }
+}
+impl From<ItemType> for String {
+    fn from(item_type: ItemType) -> Self {
+        match item_type {
+            ItemType::Other(item_type) => item_type,
+            known => known.as_str().to_owned(),
         }
     }
 }
 
+impl FromStr for ItemType {
+    type Err = ParseEnumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::known(s).ok_or_else(|| ParseEnumError {
+            expected: "item type",
+            value: s.to_owned(),
+        })
+    }
+}
+impl TryFrom<&str> for ItemType {
+    type Error = ParseEnumError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Any item in the Spotify model, for storing different types of item in one collection.
+///
+/// It is serialized as the item itself, and deserialized according to the item's `type` field.
+/// Tracks and artists are boxed because they are much larger than the other items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+#[allow(missing_docs)]
+pub enum AnyItem {
+    Track(Box<Track>),
+    Album(AlbumSimplified),
+    Artist(Box<Artist>),
+    Playlist(PlaylistSimplified),
+    Show(ShowSimplified),
+    Episode(EpisodeSimplified),
+}
+
+impl AnyItem {
+    /// Get the type of the item.
+    #[must_use]
+    pub const fn item_type(&self) -> ItemType {
+        match self {
+            Self::Track(_) => ItemType::Track,
+            Self::Album(_) => ItemType::Album,
+            Self::Artist(_) => ItemType::Artist,
+            Self::Playlist(_) => ItemType::Playlist,
+            Self::Show(_) => ItemType::Show,
+            Self::Episode(_) => ItemType::Episode,
+        }
+    }
+    /// Get the [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// of the item. This is only [`None`] for local tracks and albums.
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::Track(track) => track.id.as_deref(),
+            Self::Album(album) => album.id.as_deref(),
+            Self::Artist(artist) => Some(&artist.id),
+            Self::Playlist(playlist) => Some(&playlist.id),
+            Self::Show(show) => Some(&show.id),
+            Self::Episode(episode) => Some(&episode.id),
+        }
+    }
+    /// Get the name of the item.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Track(track) => &track.name,
+            Self::Album(album) => &album.name,
+            Self::Artist(artist) => &artist.name,
+            Self::Playlist(playlist) => &playlist.name,
+            Self::Show(show) => &show.name,
+            Self::Episode(episode) => &episode.name,
+        }
+    }
+    /// Get the images of the item, widest first. For tracks these are the images of the track's
+    /// album.
+    #[must_use]
+    pub fn images(&self) -> &[Image] {
+        match self {
+            Self::Track(track) => &track.album.images,
+            Self::Album(album) => &album.images,
+            Self::Artist(artist) => &artist.images,
+            Self::Playlist(playlist) => &playlist.images,
+            Self::Show(show) => &show.images,
+            Self::Episode(episode) => &episode.images,
+        }
+    }
+    /// Get the [Spotify URI](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// of the item, such as `spotify:track:6rqhFgbbKwnb9MLmUQDhG6`. This is only [`None`] for
+    /// local tracks and albums.
+    #[must_use]
+    pub fn uri(&self) -> Option<String> {
+        let id = self.id()?;
+        Some(util::Uri::new(self.item_type().as_str(), &id).to_string())
+    }
+    /// Get the link to the item on the Spotify website, such as
+    /// `https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6`. This is only [`None`] for local
+    /// tracks and albums.
+    #[must_use]
+    pub fn web_url(&self) -> Option<String> {
+        let id = self.id()?;
+        Some(util::Uri::new(self.item_type().as_str(), &id).web_url())
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn from_value<T: DeserializeOwned, E: de::Error>(value: serde_json::Value) -> Result<T, E> {
+            serde_json::from_value(value).map_err(E::custom)
+        }
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let item_type = match value.get("type").and_then(serde_json::Value::as_str) {
+            Some(item_type) => match item_type.parse::<ItemType>() {
+                Ok(item_type) => item_type,
+                Err(e) => return Err(de::Error::custom(e)),
+            },
+            None => return Err(de::Error::missing_field("type")),
+        };
+
+        Ok(match item_type {
+            ItemType::Track => Self::Track(from_value(value)?),
+            ItemType::Album => Self::Album(from_value(value)?),
+            ItemType::Artist => Self::Artist(from_value(value)?),
+            ItemType::Playlist => Self::Playlist(from_value(value)?),
+            ItemType::Show => Self::Show(from_value(value)?),
+            ItemType::Episode => Self::Episode(from_value(value)?),
+            other => {
+                return Err(de::Error::custom(format_args!(
+                    "a {} is not an item",
+                    other
+                )));
+            }
+        })
+    }
+}
+
+macro_rules! impl_from_for_any_item {
+    ($($variant:ident($ty:ty),)*) => {
+        $(
+            impl From<$ty> for AnyItem {
+                fn from(item: $ty) -> Self {
+                    Self::$variant(item.into())
+                }
+            }
+        )*
+    }
+}
+
+impl_from_for_any_item!(
+    Track(Track),
+    Album(AlbumSimplified),
+    Artist(Artist),
+    Playlist(PlaylistSimplified),
+    Show(ShowSimplified),
+    Episode(EpisodeSimplified),
+);
+
+macro_rules! impl_links {
+    ($($ty:ident: $item_type:literal,)*) => {
+        $(
+            impl $ty {
+                /// Get the [Spotify
+                /// URI](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids),
+                /// such as `spotify:track:6rqhFgbbKwnb9MLmUQDhG6`.
+                #[must_use]
+                pub fn uri(&self) -> String {
+                    util::Uri::new($item_type, &self.id).to_string()
+                }
+                /// Get the link on the Spotify website, such as
+                /// `https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6`.
+                #[must_use]
+                pub fn web_url(&self) -> String {
+                    util::Uri::new($item_type, &self.id).web_url()
+                }
+            }
+        )*
+    };
+    ($($ty:ident: $item_type:literal?,)*) => {
+        $(
+            impl $ty {
+                /// Get the [Spotify
+                /// URI](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids),
+                /// such as `spotify:track:6rqhFgbbKwnb9MLmUQDhG6`. This is only [`None`] for local
+                /// items.
+                #[must_use]
+                pub fn uri(&self) -> Option<String> {
+                    let id = self.id.as_ref()?;
+                    Some(util::Uri::new($item_type, id).to_string())
+                }
+                /// Get the link on the Spotify website, such as
+                /// `https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6`. This is only [`None`]
+                /// for local items.
+                #[must_use]
+                pub fn web_url(&self) -> Option<String> {
+                    let id = self.id.as_ref()?;
+                    Some(util::Uri::new($item_type, id).web_url())
+                }
+            }
+        )*
+    };
+}
+
+impl_links!(
+    Album: "album",
+    Artist: "artist",
+    Playlist: "playlist",
+    PlaylistSimplified: "playlist",
+    Show: "show",
+    ShowSimplified: "show",
+    Episode: "episode",
+    EpisodeSimplified: "episode",
+);
+impl_links!(
+    Track: "track"?,
+    TrackSimplified: "track"?,
+    AlbumSimplified: "album"?,
+    ArtistSimplified: "artist"?,
+);
+
 /// The results of a search.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchResults {
@@ -332,3 +890,34 @@ pub struct SearchResults {
     /// The resulting episodes of the search.
     pub episodes: Option<Page<EpisodeSimplified>>,
 }
+
+impl SearchResults {
+    /// Get the total number of results of a type, or [`None`] if that type wasn't searched for.
+    ///
+    /// This is Spotify's estimate, and only the first few hundred results can be retrieved.
+    #[must_use]
+    pub fn total(&self, item_type: &ItemType) -> Option<usize> {
+        match item_type {
+            ItemType::Artist => self.artists.as_ref().map(|page| page.total),
+            ItemType::Album => self.albums.as_ref().map(|page| page.total),
+            ItemType::Track => self.tracks.as_ref().map(|page| page.total),
+            ItemType::Playlist => self.playlists.as_ref().map(|page| page.total),
+            ItemType::Show => self.shows.as_ref().map(|page| page.total),
+            ItemType::Episode => self.episodes.as_ref().map(|page| page.total),
+            ItemType::Collection | ItemType::User | ItemType::Other(_) => None,
+        }
+    }
+    /// Take the results of a type, or [`None`] if that type wasn't searched for.
+    #[must_use]
+    pub fn into_items(self, item_type: &ItemType) -> Option<Page<AnyItem>> {
+        Some(match item_type {
+            ItemType::Artist => self.artists?.map_items(AnyItem::from),
+            ItemType::Album => self.albums?.map_items(AnyItem::from),
+            ItemType::Track => self.tracks?.map_items(AnyItem::from),
+            ItemType::Playlist => self.playlists?.map_items(AnyItem::from),
+            ItemType::Show => self.shows?.map_items(AnyItem::from),
+            ItemType::Episode => self.episodes?.map_items(AnyItem::from),
+            ItemType::Collection | ItemType::User | ItemType::Other(_) => return None,
+        })
+    }
+}
@@ -1,10 +1,17 @@
+#[cfg(feature = "html-escape")]
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::time::Duration;
 
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::model::{Episode, Followers, Image, Page, Track, Tracks, TypePlaylist, UserSimplified};
+use crate::model::{
+    AudioFeature, AudioFeatures, Episode, Followers, Image, Page, Restrictions, Timestamp, Track,
+    Tracks, TypePlaylist, UserSimplified,
+};
+use crate::util;
 
 macro_rules! inherit_playlist_simplified {
     ($(#[$attr:meta])* $name:ident { $($(#[$f_attr:meta])* $f_name:ident : $f_ty:ty,)* }) => {
@@ -18,17 +25,26 @@ macro_rules! inherit_playlist_simplified {
             collaborative: bool,
             /// Known external URLs for this playlist.
             external_urls: HashMap<String, String>,
+            /// A link to the Web API endpoint providing full details of the playlist. [`None`] if
+            /// Spotify didn't send it.
+            #[serde(default)]
+            href: Option<String>,
             /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
             /// for this playlist.
             id: String,
             /// Images for the playlist. It may be empty, or contain up to three images, in descending
             /// order of size. The URLs are temporary and will expire in less than a day.
+            #[serde(default = "util::lenient_default")]
             images: Vec<Image>,
             /// The name of the playlist.
             name: String,
             /// The user who owns the playlist. This is a [`UserPublic`](crate::UserPublic)
             /// according to the documentation, but in practice it is not.
             owner: UserSimplified,
+            /// The colour of the playlist, as a hex colour code such as `#FFFFFF`. This is usually
+            /// [`None`].
+            #[serde(default)]
+            primary_color: Option<String>,
             /// Whether the playlist is public; None if not relevant.
             public: Option<bool>,
             /// The version identifier of the playlist.
@@ -45,6 +61,12 @@ inherit_playlist_simplified!(
     PlaylistSimplified {
         /// The number of tracks in the playlist.
         tracks: Tracks,
+        /// The followers of the playlist. Spotify doesn't include this in simplified playlists,
+        /// so it is only [`Some`] if the playlist was simplified from a full [`Playlist`] or
+        /// filled in with
+        /// [`Playlists::get_follower_count`](crate::Playlists::get_follower_count).
+        #[serde(default)]
+        followers: Option<Followers>,
     }
 );
 inherit_playlist_simplified!(
@@ -59,26 +81,160 @@ inherit_playlist_simplified!(
     }
 );
 
+impl PlaylistSimplified {
+    /// Whether the playlist is owned by the user with the given ID.
+    #[must_use]
+    pub fn is_owned_by(&self, user_id: &str) -> bool {
+        self.owner.id == user_id
+    }
+}
+
 impl Playlist {
+    /// Whether the playlist is owned by the user with the given ID.
+    #[must_use]
+    pub fn is_owned_by(&self, user_id: &str) -> bool {
+        self.owner.id == user_id
+    }
     /// Convert to a `PlaylistSimplified`.
     #[must_use]
     pub fn simplify(self) -> PlaylistSimplified {
         PlaylistSimplified {
             collaborative: self.collaborative,
             external_urls: self.external_urls,
+            href: self.href,
             id: self.id,
             images: self.images,
             name: self.name,
             owner: self.owner,
+            primary_color: self.primary_color,
             public: self.public,
             snapshot_id: self.snapshot_id,
+            followers: Some(self.followers),
             tracks: Tracks {
+                href: self.tracks.href,
                 total: self.tracks.total,
             },
             item_type: TypePlaylist,
         }
     }
+
+    /// Get the playlist description with its HTML entities (such as `&amp;` and `&#x27;`)
+    /// decoded.
+    ///
+    /// Spotify returns playlist descriptions HTML-escaped, so [`description`](Self::description)
+    /// is not suitable for displaying as plain text.
+    ///
+    /// This function is only available when the `html-escape` feature of this library is
+    /// activated.
+    #[cfg(feature = "html-escape")]
+    #[must_use]
+    pub fn description_decoded(&self) -> Option<Cow<'_, str>> {
+        self.description
+            .as_deref()
+            .map(html_escape::decode_html_entities)
+    }
+}
+/// The version identifier of a playlist, which changes every time the playlist is modified.
+///
+/// This is returned by [`Playlists::get_snapshot_id`](crate::Playlists::get_snapshot_id), and
+/// can be compared to a previously stored snapshot ID to check whether a playlist has changed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnapshotId(pub String);
+
+impl SnapshotId {
+    /// Get the snapshot ID as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SnapshotId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for SnapshotId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+impl From<SnapshotId> for String {
+    fn from(id: SnapshotId) -> Self {
+        id.0
+    }
+}
+impl PartialEq<str> for SnapshotId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+impl PartialEq<String> for SnapshotId {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+/// A playlist object with only some of its fields, returned when a fields filter
+/// is used.
+///
+/// Fields that weren't requested are [`None`]. Other than `tracks`, fields that are objects must be
+/// requested whole, as they can't be parsed if only some of their fields are present.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistPartial {
+    /// Whether the owner allows other people to modify the playlist.
+    pub collaborative: Option<bool>,
+    /// The playlist description.
+    pub description: Option<String>,
+    /// Known external URLs for this playlist.
+    pub external_urls: Option<HashMap<String, String>>,
+    /// The followers of the playlist.
+    pub followers: Option<Followers>,
+    /// A link to the Web API endpoint providing full details of the playlist.
+    pub href: Option<String>,
+    /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// for this playlist.
+    pub id: Option<String>,
+    /// Images for the playlist.
+    pub images: Option<Vec<Image>>,
+    /// The name of the playlist.
+    pub name: Option<String>,
+    /// The user who owns the playlist.
+    pub owner: Option<UserSimplified>,
+    /// The colour of the playlist, as a hex colour code such as `#FFFFFF`.
+    pub primary_color: Option<String>,
+    /// Whether the playlist is public.
+    pub public: Option<bool>,
+    /// The version identifier of the playlist.
+    pub snapshot_id: Option<String>,
+    /// Information about the tracks and episodes of the playlist.
+    pub tracks: Option<PlaylistItemsPartial>,
 }
+
+/// A page of playlist items with only some of its fields, returned when a fields filter
+/// is used.
+///
+/// As the items themselves can have any subset of their fields, they are kept as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistItemsPartial {
+    /// A link to the Web API endpoint returning this page.
+    pub href: Option<String>,
+    /// The items in the page.
+    pub items: Option<Vec<serde_json::Value>>,
+    /// The maximum number of items in the page.
+    pub limit: Option<usize>,
+    /// A link to the next page, if there is one.
+    pub next: Option<String>,
+    /// The offset of the page in the items.
+    pub offset: Option<usize>,
+    /// A link to the previous page, if there is one.
+    pub previous: Option<String>,
+    /// The total number of items.
+    pub total: Option<usize>,
+}
+
 impl From<Playlist> for PlaylistSimplified {
     fn from(playlist: Playlist) -> Self {
         playlist.simplify()
@@ -89,17 +245,175 @@ impl From<Playlist> for PlaylistSimplified {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PlaylistItem {
     /// The date and time that the item was added. Some very old playlists might have [`None`].
-    pub added_at: Option<DateTime<Utc>>,
+    pub added_at: Option<Timestamp>,
     /// The Spotify user who added the item. Some very old playlists might have [`None`]. This is a
     /// [`UserPublic`](crate::UserPublic) according to the documentation, but in practice it is not.
     pub added_by: Option<UserSimplified>,
     /// Whether the item is a local file or not.
     pub is_local: bool,
-    /// The item itself. Spotify API sometimes returns null for this, and I don't know why.
+    /// The item itself. Spotify returns null for this when the item can't be shown, for example
+    /// episodes that aren't available in the user's region; use
+    /// [`unavailable_reason`](Self::unavailable_reason) to find out why an item can't be played.
     #[serde(rename = "track")]
     pub item: Option<PlaylistItemType<Track, Episode>>,
 }
 
+impl PlaylistItem {
+    /// The date and time that the item was added, as a
+    /// [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `added_at` is outside the years -9999 to 9999 that `time` supports, which
+    /// Spotify never sends.
+    #[cfg(feature = "time")]
+    #[must_use]
+    pub fn added_at_time(&self) -> Option<::time::OffsetDateTime> {
+        self.added_at.map(util::offset_date_time)
+    }
+
+    /// Get the ID of the track or episode, or [`None`] if it is a local file or the item is
+    /// missing.
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        match self.item.as_ref()? {
+            PlaylistItemType::Track(track) => track.id.as_deref(),
+            PlaylistItemType::Episode(episode) => Some(&episode.id),
+        }
+    }
+
+    /// Get the duration of the track or episode.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        match self.item.as_ref()? {
+            PlaylistItemType::Track(track) => Some(track.duration),
+            PlaylistItemType::Episode(episode) => Some(episode.duration),
+        }
+    }
+
+    /// Get the name of the first artist of the track.
+    #[must_use]
+    pub fn artist_name(&self) -> Option<&str> {
+        match self.item.as_ref()? {
+            PlaylistItemType::Track(track) => track.artists.first().map(|artist| &*artist.name),
+            PlaylistItemType::Episode(_) => None,
+        }
+    }
+
+    /// Get the name of the album of the track.
+    #[must_use]
+    pub fn album_name(&self) -> Option<&str> {
+        match self.item.as_ref()? {
+            PlaylistItemType::Track(track) => Some(&track.album.name),
+            PlaylistItemType::Episode(_) => None,
+        }
+    }
+
+    /// Get why the item can't be played, or [`None`] if it can be.
+    ///
+    /// Whether an item is playable is only known if a market was given in the request, otherwise
+    /// only missing items are detected.
+    #[must_use]
+    pub fn unavailable_reason(&self) -> Option<UnavailableReason> {
+        let (is_playable, restrictions) = match self.item.as_ref() {
+            None => return Some(UnavailableReason::Missing),
+            Some(PlaylistItemType::Track(track)) => (track.is_playable, &track.restrictions),
+            Some(PlaylistItemType::Episode(episode)) => {
+                (Some(episode.is_playable), &episode.restrictions)
+            }
+        };
+        if is_playable == Some(false) || restrictions.is_some() {
+            Some(UnavailableReason::Restricted(restrictions.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Sort playlist items in ascending order.
+    ///
+    /// Sorting by an audio feature requires `features`, the audio features of the tracks keyed by
+    /// their ID; these can be obtained from
+    /// [`Tracks::get_features_tracks`](crate::Tracks::get_features_tracks). Items that don't have
+    /// the attribute being sorted by, such as episodes when sorting by artist, are placed at the
+    /// end in their original order.
+    pub fn sort(
+        items: &mut [Self],
+        by: PlaylistItemSort,
+        features: Option<&HashMap<String, AudioFeatures>>,
+    ) {
+        fn compare<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        let lowercase = |name: Option<&str>| name.map(str::to_lowercase);
+        let feature = |item: &Self, feature: AudioFeature| {
+            features?
+                .get(item.id()?)
+                .map(|features| feature.of(features))
+        };
+
+        items.sort_by(|a, b| match by {
+            PlaylistItemSort::AddedAt => compare(a.added_at, b.added_at),
+            PlaylistItemSort::Artist => {
+                compare(lowercase(a.artist_name()), lowercase(b.artist_name()))
+            }
+            PlaylistItemSort::Album => {
+                compare(lowercase(a.album_name()), lowercase(b.album_name()))
+            }
+            PlaylistItemSort::Duration => compare(a.duration(), b.duration()),
+            PlaylistItemSort::Feature(f) => compare(feature(a, f), feature(b, f)),
+        });
+    }
+
+    /// Get the IDs of playlist items in order, skipping local files and missing items.
+    ///
+    /// The result can be passed directly to
+    /// [`Playlists::replace_playlists_items`](crate::Playlists::replace_playlists_items) or
+    /// [`Playlists::add_to_playlist`](crate::Playlists::add_to_playlist).
+    #[must_use]
+    pub fn ids(items: &[Self]) -> Vec<PlaylistItemType<&str, &str>> {
+        items
+            .iter()
+            .filter_map(|item| match item.item.as_ref()? {
+                PlaylistItemType::Track(track) => track.id.as_deref().map(PlaylistItemType::Track),
+                PlaylistItemType::Episode(episode) => Some(PlaylistItemType::Episode(&*episode.id)),
+            })
+            .collect()
+    }
+}
+
+/// Why a playlist item can't be played, obtained from [`PlaylistItem::unavailable_reason`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnavailableReason {
+    /// Spotify didn't return the item at all. This happens for episodes that aren't available in
+    /// the user's region and for items that have been removed from Spotify, and the two can't be
+    /// told apart. The rest of the [`PlaylistItem`], such as when it was added, is still present.
+    Missing,
+    /// The item was returned but isn't playable in the requested market. Contains the
+    /// restrictions Spotify gave, if any.
+    Restricted(Option<Restrictions>),
+}
+
+/// An attribute to sort playlist items by, used by [`PlaylistItem::sort`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlaylistItemSort {
+    /// When the item was added to the playlist.
+    AddedAt,
+    /// The name of the first artist of the track, case-insensitively.
+    Artist,
+    /// The name of the album of the track, case-insensitively.
+    Album,
+    /// The duration of the track or episode.
+    Duration,
+    /// An audio feature of the track.
+    Feature(AudioFeature),
+}
+
 /// The types of item that can go in a playlist.
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -112,14 +426,107 @@ pub enum PlaylistItemType<T, E> {
 
 impl<T: Display, E: Display> PlaylistItemType<T, E> {
     /// Formats a Spotify URI using the [`Display`] implementations of the track and episode types.
+    ///
+    /// The track or episode can be either an ID or a URI of the same type, so both
+    /// `PlaylistItemType::Track("1Jwc3ODLQxtbnS8M9TflSP")` and
+    /// `PlaylistItemType::Track("spotify:track:1Jwc3ODLQxtbnS8M9TflSP")` have the URI
+    /// `spotify:track:1Jwc3ODLQxtbnS8M9TflSP`.
     pub fn uri(&self) -> String {
+        self.uri_ref().to_string()
+    }
+
+    /// Borrow the item as a URI that can be displayed or serialized without allocating.
+    pub(crate) fn uri_ref(&self) -> util::Uri<'_> {
         match self {
-            Self::Track(track) => format!("spotify:track:{}", track),
-            Self::Episode(episode) => format!("spotify:episode:{}", episode),
+            Self::Track(track) => util::Uri::new("track", track),
+            Self::Episode(episode) => util::Uri::new("episode", episode),
+        }
+    }
+}
+
+/// A backup of a playlist and all of its items, created by
+/// [`Playlists::export`](crate::Playlists::export).
+///
+/// It can be serialized to store it, recreated as a new playlist with
+/// [`Playlists::import`](crate::Playlists::import), or converted to an M3U8 playlist with
+/// [`to_m3u8`](Self::to_m3u8).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistExport {
+    /// The name of the playlist.
+    pub name: String,
+    /// The playlist description.
+    pub description: Option<String>,
+    /// Whether the playlist is public; None if not relevant.
+    pub public: Option<bool>,
+    /// Whether the owner allows other people to modify the playlist.
+    pub collaborative: bool,
+    /// All the items in the playlist, in order.
+    pub items: Vec<PlaylistItem>,
+}
+
+impl PlaylistExport {
+    /// Write the playlist as an extended M3U playlist, with each item's name, artists and length,
+    /// and its URL on the Spotify website.
+    ///
+    /// Local tracks don't have a URL, so they are only written as a comment. Items that Spotify
+    /// returned as null are skipped.
+    #[must_use]
+    pub fn to_m3u8(&self) -> String {
+        let mut m3u = format!("#EXTM3U\n#PLAYLIST:{}\n", m3u_line(&self.name));
+
+        for item in self.items.iter().filter_map(|item| item.item.as_ref()) {
+            let (duration, title, url) = match item {
+                PlaylistItemType::Track(track) => {
+                    let artists = track
+                        .artists
+                        .iter()
+                        .map(|artist| artist.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let url = track.id.as_ref().map(|id| {
+                        track
+                            .external_urls
+                            .get("spotify")
+                            .cloned()
+                            .unwrap_or_else(|| format!("https://open.spotify.com/track/{}", id))
+                    });
+                    (track.duration, format!("{} - {}", artists, track.name), url)
+                }
+                PlaylistItemType::Episode(episode) => (
+                    episode.duration,
+                    format!("{} - {}", episode.show.name, episode.name),
+                    Some(
+                        episode
+                            .external_urls
+                            .get("spotify")
+                            .cloned()
+                            .unwrap_or_else(|| {
+                                format!("https://open.spotify.com/episode/{}", episode.id)
+                            }),
+                    ),
+                ),
+            };
+
+            let title = m3u_line(&title);
+            match url {
+                Some(url) => {
+                    let _ = write!(m3u, "#EXTINF:{},{}\n{}\n", duration.as_secs(), title, url);
+                }
+                None => {
+                    let _ = writeln!(m3u, "# Local file: {}", title);
+                }
+            }
         }
+
+        m3u
     }
 }
 
+/// Make a string safe to put on a single line of an M3U file.
+fn m3u_line(s: &str) -> String {
+    s.replace(['\n', '\r'], " ")
+}
+
 /// A list of featured playlists, and a message.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FeaturedPlaylists {
@@ -128,3 +535,13 @@ pub struct FeaturedPlaylists {
     /// The list of featured playlists.
     pub playlists: Page<PlaylistSimplified>,
 }
+
+/// A category's playlists, and possibly a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryPlaylists {
+    /// A localized message about the category's playlists, if there is one.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The category's playlists.
+    pub playlists: Page<PlaylistSimplified>,
+}
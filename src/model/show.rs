@@ -4,9 +4,11 @@ use std::time::Duration;
 use serde::{Deserialize, Serialize};
 // See line 38+120
 //use isolanguage_1::LanguageCode;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::NaiveDate;
 
-use crate::model::{Copyright, DatePrecision, Image, Page, TypeEpisode, TypeShow};
+use crate::model::{
+    Copyright, DatePrecision, Image, Page, Restrictions, Timestamp, TypeEpisode, TypeShow,
+};
 use crate::util;
 
 macro_rules! inherit_show_simplified {
@@ -24,13 +26,19 @@ macro_rules! inherit_show_simplified {
             /// A description of the show.
             description: String,
             /// Whether the show is explicit.
+            #[serde(default = "util::lenient_default")]
             explicit: bool,
             /// Known externals URLs for this show.
             external_urls: HashMap<String, String>,
+            /// A description of the show that may contain HTML tags. [`None`] if Spotify didn't
+            /// send it.
+            #[serde(default)]
+            html_description: Option<String>,
             /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
             /// for this show.
             id: String,
             /// The cover art for the show in various sizes, widest first.
+            #[serde(default = "util::lenient_default")]
             images: Vec<Image>,
             /// Whether the episode is hosted outside of Spotify's CDN. Can be [`None`].
             is_externally_hosted: Option<bool>,
@@ -73,6 +81,7 @@ impl Show {
             description: self.description,
             explicit: self.explicit,
             external_urls: self.external_urls,
+            html_description: self.html_description,
             id: self.id,
             images: self.images,
             is_externally_hosted: self.is_externally_hosted,
@@ -94,11 +103,25 @@ impl From<Show> for ShowSimplified {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SavedShow {
     /// When the show was saved.
-    pub added_at: DateTime<Utc>,
+    pub added_at: Timestamp,
     /// Information about the show.
     pub show: ShowSimplified,
 }
 
+#[cfg(feature = "time")]
+impl SavedShow {
+    /// When the show was saved, as a [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `added_at` is outside the years -9999 to 9999 that `time` supports, which Spotify
+    /// never sends.
+    #[must_use]
+    pub fn added_at_time(&self) -> ::time::OffsetDateTime {
+        util::offset_date_time(self.added_at)
+    }
+}
+
 macro_rules! inherit_episode_simplified {
     ($(#[$attr:meta])* $name:ident { $($(#[$f_attr:meta])* $f_name:ident : $f_ty:ty,)* }) => {
         to_struct!($(#[$attr])* $name {
@@ -114,17 +137,24 @@ macro_rules! inherit_episode_simplified {
             #[serde(rename = "duration_ms", with = "serde_millis")]
             duration: Duration,
             /// Whether the episode is explicit.
+            #[serde(default = "util::lenient_default")]
             explicit: bool,
             /// Externals URLs for this episode.
             external_urls: HashMap<String, String>,
+            /// A description of the episode that may contain HTML tags. [`None`] if Spotify didn't
+            /// send it.
+            #[serde(default)]
+            html_description: Option<String>,
             /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
             /// for this episode.
             id: String,
             /// The cover art for this episode in sizes, widest first.
+            #[serde(default = "util::lenient_default")]
             images: Vec<Image>,
             /// Whether the episode is hosted outside of Spotify's CDN.
             is_externally_hosted: bool,
             /// Whether the episode is playable in the given market.
+            #[serde(default = "util::lenient_default")]
             is_playable: bool,
             /// The list of languages used in this episode.
             // TODO: it can be en-US/en-GB
@@ -138,6 +168,10 @@ macro_rules! inherit_episode_simplified {
             release_date_precision: DatePrecision,
             /// The user's most recent position in the episode. [`None`] if there is no user.
             resume_point: Option<ResumePoint>,
+            /// Why the episode is not playable, if it isn't; for example because it is not
+            /// available in the given market or because the user has disabled explicit content.
+            #[serde(default)]
+            restrictions: Option<Restrictions>,
             /// The item type; `episode`.
             #[serde(rename = "type")]
             item_type: TypeEpisode,
@@ -168,6 +202,7 @@ impl Episode {
             duration: self.duration,
             explicit: self.explicit,
             external_urls: self.external_urls,
+            html_description: self.html_description,
             id: self.id,
             images: self.images,
             is_externally_hosted: self.is_externally_hosted,
@@ -177,6 +212,7 @@ impl Episode {
             release_date: self.release_date,
             release_date_precision: self.release_date_precision,
             resume_point: self.resume_point,
+            restrictions: self.restrictions,
             item_type: TypeEpisode,
         }
     }
@@ -0,0 +1,256 @@
+//! Constructors of placeholder objects, for tests of code that uses this library that don't talk to
+//! Spotify.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
+use crate::model::{
+    Album, AlbumSimplified, AlbumType, Artist, ArtistSimplified, DatePrecision, Followers, Page,
+    Playlist, PlaylistSimplified, Track, TrackSimplified, Tracks, TypeAlbum, TypeArtist,
+    TypePlaylist, TypeTrack, TypeUser, UserSimplified,
+};
+
+/// The external URLs of an item of `item_type` with the ID `id`.
+fn external_urls(item_type: &str, id: &str) -> HashMap<String, String> {
+    let mut urls = HashMap::with_capacity(1);
+    urls.insert(
+        "spotify".to_owned(),
+        format!("https://open.spotify.com/{}/{}", item_type, id),
+    );
+    urls
+}
+
+/// The release date of stub albums.
+fn release_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()
+}
+
+impl<T> Page<T> {
+    /// Create a page that contains every item.
+    #[must_use]
+    pub fn stub(items: Vec<T>) -> Self {
+        Self {
+            href: None,
+            limit: items.len(),
+            next: None,
+            offset: 0,
+            previous: None,
+            total: items.len(),
+            items,
+        }
+    }
+}
+
+impl ArtistSimplified {
+    /// Create an artist with an ID and name.
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            external_urls: external_urls("artist", id),
+            name: name.to_owned(),
+            item_type: TypeArtist,
+            id: Some(id.to_owned()),
+        }
+    }
+}
+
+impl Artist {
+    /// Create an artist with an ID and name, no followers, genres or images, and a popularity of
+    /// 0.
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            external_urls: external_urls("artist", id),
+            name: name.to_owned(),
+            item_type: TypeArtist,
+            id: id.to_owned(),
+            followers: Followers { total: 0 },
+            genres: Vec::new(),
+            images: Vec::new(),
+            popularity: 0,
+        }
+    }
+}
+
+impl AlbumSimplified {
+    /// Create an album with an ID and name, by [`ArtistSimplified::stub("artist", "Artist")`] and
+    /// released on the 1st of January 2020.
+    ///
+    /// [`ArtistSimplified::stub("artist", "Artist")`]: ArtistSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            artists: vec![ArtistSimplified::stub("artist", "Artist")],
+            available_markets: None,
+            external_urls: external_urls("album", id),
+            images: Vec::new(),
+            name: name.to_owned(),
+            restrictions: None,
+            is_playable: None,
+            item_type: TypeAlbum,
+            album_type: Some(AlbumType::Album),
+            id: Some(id.to_owned()),
+            release_date: Some(release_date()),
+            release_date_precision: Some(DatePrecision::Day),
+        }
+    }
+}
+
+impl Album {
+    /// Create an album with an ID and name and no tracks, by
+    /// [`ArtistSimplified::stub("artist", "Artist")`] and released on the 1st of January 2020.
+    ///
+    /// [`ArtistSimplified::stub("artist", "Artist")`]: ArtistSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            artists: vec![ArtistSimplified::stub("artist", "Artist")],
+            available_markets: None,
+            external_urls: external_urls("album", id),
+            images: Vec::new(),
+            name: name.to_owned(),
+            restrictions: None,
+            is_playable: None,
+            item_type: TypeAlbum,
+            album_type: AlbumType::Album,
+            id: id.to_owned(),
+            release_date: release_date(),
+            release_date_precision: DatePrecision::Day,
+            copyrights: Vec::new(),
+            external_ids: HashMap::new(),
+            genres: Vec::new(),
+            label: String::new(),
+            popularity: 0,
+            tracks: Page::stub(Vec::new()),
+        }
+    }
+}
+
+impl TrackSimplified {
+    /// Create a three minute long track with an ID and name, by
+    /// [`ArtistSimplified::stub("artist", "Artist")`].
+    ///
+    /// [`ArtistSimplified::stub("artist", "Artist")`]: ArtistSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            artists: vec![ArtistSimplified::stub("artist", "Artist")],
+            available_markets: None,
+            disc_number: 1,
+            duration: Duration::from_secs(180),
+            explicit: false,
+            external_urls: external_urls("track", id),
+            id: Some(id.to_owned()),
+            is_playable: None,
+            linked_from: None,
+            restrictions: None,
+            name: name.to_owned(),
+            preview_url: None,
+            track_number: 1,
+            item_type: TypeTrack,
+            is_local: false,
+        }
+    }
+}
+
+impl Track {
+    /// Create a three minute long track with an ID and name, by
+    /// [`ArtistSimplified::stub("artist", "Artist")`] on
+    /// [`AlbumSimplified::stub("album", "Album")`]. It has no external IDs and a popularity of 0.
+    ///
+    /// [`ArtistSimplified::stub("artist", "Artist")`]: ArtistSimplified::stub
+    /// [`AlbumSimplified::stub("album", "Album")`]: AlbumSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        let track = TrackSimplified::stub(id, name);
+        Self {
+            album: AlbumSimplified::stub("album", "Album"),
+            external_ids: HashMap::new(),
+            popularity: 0,
+            artists: track.artists,
+            available_markets: track.available_markets,
+            disc_number: track.disc_number,
+            duration: track.duration,
+            explicit: track.explicit,
+            external_urls: track.external_urls,
+            id: track.id,
+            is_playable: track.is_playable,
+            linked_from: track.linked_from,
+            restrictions: track.restrictions,
+            name: track.name,
+            preview_url: track.preview_url,
+            track_number: track.track_number,
+            item_type: TypeTrack,
+            is_local: track.is_local,
+        }
+    }
+}
+
+impl UserSimplified {
+    /// Create a user with an ID and display name.
+    #[must_use]
+    pub fn stub(id: &str, display_name: &str) -> Self {
+        Self {
+            display_name: Some(display_name.to_owned()),
+            external_urls: external_urls("user", id),
+            id: id.to_owned(),
+            item_type: TypeUser,
+        }
+    }
+}
+
+impl Playlist {
+    /// Create a public playlist with an ID and name and no items, owned by
+    /// [`UserSimplified::stub("user", "User")`]. Its snapshot ID is `snapshot`.
+    ///
+    /// [`UserSimplified::stub("user", "User")`]: UserSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            collaborative: false,
+            external_urls: external_urls("playlist", id),
+            href: None,
+            id: id.to_owned(),
+            images: Vec::new(),
+            name: name.to_owned(),
+            owner: UserSimplified::stub("user", "User"),
+            primary_color: None,
+            public: Some(true),
+            snapshot_id: "snapshot".to_owned(),
+            item_type: TypePlaylist,
+            description: None,
+            followers: Followers { total: 0 },
+            tracks: Page::stub(Vec::new()),
+        }
+    }
+}
+
+impl PlaylistSimplified {
+    /// Create a public playlist with an ID and name and no items, owned by
+    /// [`UserSimplified::stub("user", "User")`]. Its snapshot ID is `snapshot`.
+    ///
+    /// [`UserSimplified::stub("user", "User")`]: UserSimplified::stub
+    #[must_use]
+    pub fn stub(id: &str, name: &str) -> Self {
+        Self {
+            collaborative: false,
+            external_urls: external_urls("playlist", id),
+            href: None,
+            id: id.to_owned(),
+            images: Vec::new(),
+            name: name.to_owned(),
+            owner: UserSimplified::stub("user", "User"),
+            primary_color: None,
+            public: Some(true),
+            snapshot_id: "snapshot".to_owned(),
+            item_type: TypePlaylist,
+            tracks: Tracks {
+                href: None,
+                total: 0,
+            },
+            followers: None,
+        }
+    }
+}
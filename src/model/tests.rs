@@ -0,0 +1,869 @@
+//! Offline tests of the object model against JSON payloads captured from Spotify, stored in
+//! `tests/fixtures`.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::fs;
+use std::iter;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{
+    util, Album, AlbumGroup, AnyItem, Artist, AudioFeature, AudioFeatures, Context, Copyright,
+    Country, CurrentPlayback, CurrentlyPlaying, Device, DeviceType, Disallow, EndpointError,
+    Episode, Error, FeaturesSummary, Genre, Image, InvalidPosition, ItemType, Language, Mode,
+    ParseEnumError, ParseMode, PlaybackPosition, PlayerErrorReason, PlayingType, Playlist,
+    PlaylistExport, PlaylistItem, PlaylistItemSort, PlaylistItemType, PlaylistPartial,
+    Recommendations, RepeatState, RequestId, RestrictionReason, Restrictions, SeedType, SnapshotId,
+    StatusCode, Subscription, Track, TrackAvailability, TrackLink, TypeTrack, UnavailableReason,
+    UserPrivate,
+};
+
+/// Deserialize the fixture `name`, and check that it survives being serialized and deserialized
+/// again.
+fn fixture<T: DeserializeOwned + Serialize + PartialEq + Debug>(name: &str) -> T {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let json = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read fixture {}: {}", path, e));
+    let value: T = serde_json::from_str(&json)
+        .unwrap_or_else(|e| panic!("failed to deserialize fixture {}: {}", name, e));
+
+    let reserialized = serde_json::to_string(&value).unwrap();
+    let round_tripped: T = serde_json::from_str(&reserialized)
+        .unwrap_or_else(|e| panic!("failed to deserialize reserialized {}: {}", name, e));
+    assert_eq!(round_tripped, value, "fixture {} did not round trip", name);
+
+    value
+}
+
+/// Check that every variant of an enum serializes to the same string as its `as_str`.
+fn assert_serde_str<T: Serialize + Copy + Debug>(
+    variants: &[T],
+    as_str: impl Fn(T) -> &'static str,
+) {
+    for &variant in variants {
+        assert_eq!(
+            serde_json::to_value(variant).unwrap(),
+            as_str(variant),
+            "{:?} serializes differently to its string form",
+            variant
+        );
+    }
+}
+
+#[test]
+fn album() {
+    let album: Album = fixture("album");
+    assert_eq!(album.name, "Inside In / Inside Out");
+    assert_eq!(
+        album.release_date,
+        NaiveDate::from_ymd_opt(2006, 1, 23).unwrap()
+    );
+    assert_eq!(album.copyrights.len(), 2);
+    assert!(!album.copyrights[0].performance_copyright);
+    assert!(album.copyrights[1].performance_copyright);
+    assert_eq!(album.tracks.total, 14);
+    assert_eq!(album.tracks.items[0].duration, Duration::from_millis(98400));
+    assert_eq!(album.is_playable, None);
+}
+
+#[test]
+fn image_sizes() {
+    let album: Album = fixture("album");
+    assert_eq!(Image::largest(&album.images).unwrap().width, Some(640));
+    assert_eq!(
+        Image::best_for(&album.images, 200).unwrap().width,
+        Some(300)
+    );
+    assert_eq!(
+        Image::best_for(&album.images, 1000).unwrap().width,
+        Some(640)
+    );
+    assert_eq!(Image::best_for(&[], 100), None);
+}
+
+#[test]
+fn artist() {
+    let artist: Artist = fixture("artist");
+    assert_eq!(artist.name, "Red Hot Chili Peppers");
+    assert_eq!(artist.followers.total, 17_458_211);
+    assert_eq!(artist.images.len(), 2);
+    assert_eq!(artist.to_ref(), artist.clone().simplify().to_ref());
+    assert_eq!(artist.genres[0].as_str(), "alternative rock");
+    assert_eq!(artist.genres[0].normalized(), "alternative-rock");
+    assert_eq!(artist.genres[4], Genre::new("Rock"));
+}
+
+#[test]
+fn genre() {
+    assert_eq!(Genre::new("Hip  Hop").to_string(), "hip-hop");
+    assert_eq!(Genre::from("hip-hop"), Genre::new("hip hop"));
+    assert!(Genre::new("A C") < Genre::new("b"));
+
+    let genre: Genre = serde_json::from_str(r#""R&B ""#).unwrap();
+    assert_eq!(genre.as_str(), "R&B ");
+    assert_eq!(genre.normalized(), "r&b");
+    assert_eq!(genre, Genre::new("r&b"));
+    assert_eq!(serde_json::to_string(&genre).unwrap(), r#""R&B ""#);
+    assert_eq!(
+        serde_json::to_string(&Genre::new("funk rock")).unwrap(),
+        r#""funk rock""#
+    );
+
+    let seeds = [Genre::new("hip-hop"), Genre::new("rock")];
+    assert!(Genre::new("Hip Hop").is_seed(&seeds));
+    assert!(!Genre::new("funk rock").is_seed(&seeds));
+}
+
+#[test]
+fn track() {
+    let track: Track = fixture("track");
+    assert_eq!(track.id.as_deref(), Some("1Jwc3ODLQxtbnS8M9TflSP"));
+    assert_eq!(
+        track.album.release_date,
+        Some(NaiveDate::from_ymd_opt(1986, 1, 1).unwrap())
+    );
+    assert_eq!(track.is_playable, Some(true));
+    assert_eq!(track.external_ids["isrc"], "USSM18600119");
+
+    let track_ref = track.to_ref();
+    assert_eq!(track_ref.id, Some("1Jwc3ODLQxtbnS8M9TflSP"));
+    assert_eq!(track_ref.name, track.name);
+    assert_eq!(track_ref.artists[0].to_ref().name, track.artists[0].name);
+    assert_eq!(track.clone().simplify().to_ref(), track_ref);
+
+    assert_eq!(
+        track.uri().as_deref(),
+        Some("spotify:track:1Jwc3ODLQxtbnS8M9TflSP")
+    );
+    assert_eq!(
+        track.web_url().as_deref(),
+        Some("https://open.spotify.com/track/1Jwc3ODLQxtbnS8M9TflSP")
+    );
+    assert_eq!(
+        track.album.web_url(),
+        track.album.external_urls.get("spotify").cloned()
+    );
+}
+
+#[test]
+fn track_availability() {
+    let mut track: Track = fixture("track");
+    assert_eq!(TrackAvailability::of(&track), TrackAvailability::Playable);
+
+    track.linked_from = Some(TrackLink {
+        external_urls: HashMap::new(),
+        id: "6kLCHFM39wkFjOuyPGLGeQ".to_owned(),
+        item_type: TypeTrack,
+    });
+    assert_eq!(
+        TrackAvailability::of(&track),
+        TrackAvailability::Relinked(Some("1Jwc3ODLQxtbnS8M9TflSP".to_owned()))
+    );
+
+    track.is_playable = Some(false);
+    track.restrictions = Some(Restrictions {
+        reason: RestrictionReason::Market,
+    });
+    assert_eq!(
+        TrackAvailability::of(&track),
+        TrackAvailability::Restricted(track.restrictions.clone())
+    );
+}
+
+#[test]
+fn episode() {
+    let episode: Episode = fixture("episode");
+    assert_eq!(episode.show.id, "38bS44xjbVVZ3No3ByF1dJ");
+    assert_eq!(
+        episode.resume_point.unwrap().resume_position,
+        Duration::from_secs(0)
+    );
+    assert_eq!(
+        episode.html_description.as_deref(),
+        Some("<p>En ny tysk bok granskar för första gången Tredje rikets drogberoende.</p>")
+    );
+    assert_eq!(episode.show.html_description, None);
+    assert!(episode.simplify().html_description.is_some());
+}
+
+#[test]
+fn restricted_episode() {
+    let mut json: serde_json::Value = fixture("episode");
+    let object = json.as_object_mut().unwrap();
+    object.remove("is_playable");
+    object.insert(
+        "restrictions".to_owned(),
+        serde_json::json!({ "reason": "market" }),
+    );
+
+    let episode: Episode = serde_json::from_value(json).unwrap();
+    assert!(!episode.is_playable);
+    assert_eq!(
+        episode.restrictions.unwrap().reason,
+        RestrictionReason::Market
+    );
+}
+
+#[test]
+fn restriction_reason() {
+    for (reason, s) in &[
+        (RestrictionReason::Market, "market"),
+        (RestrictionReason::Product, "product"),
+        (RestrictionReason::Explicit, "explicit"),
+        (RestrictionReason::Unknown("payment".to_owned()), "payment"),
+    ] {
+        assert_eq!(reason.as_str(), *s);
+        assert_eq!(RestrictionReason::from(*s), *reason);
+        let json = serde_json::to_value(reason).unwrap();
+        assert_eq!(json, *s);
+        assert_eq!(
+            serde_json::from_value::<RestrictionReason>(json).unwrap(),
+            *reason
+        );
+    }
+}
+
+#[test]
+fn playlist_with_episodes() {
+    let playlist: Playlist = fixture("playlist_with_episodes");
+    let items = &playlist.tracks.items;
+    assert_eq!(items.len(), 3);
+    assert!(matches!(items[0].item, Some(PlaylistItemType::Track(_))));
+    assert!(matches!(items[1].item, Some(PlaylistItemType::Episode(_))));
+    assert_eq!(items[2].item, None);
+    assert_eq!(items[2].added_at, None);
+    assert_eq!(items[0].unavailable_reason(), None);
+    assert_eq!(items[1].unavailable_reason(), None);
+    assert_eq!(
+        items[2].unavailable_reason(),
+        Some(UnavailableReason::Missing)
+    );
+    let mut restricted = items[0].clone();
+    if let Some(PlaylistItemType::Track(track)) = &mut restricted.item {
+        track.is_playable = Some(false);
+    }
+    assert_eq!(
+        restricted.unavailable_reason(),
+        Some(UnavailableReason::Restricted(None))
+    );
+
+    assert_eq!(playlist.primary_color, None);
+    assert_eq!(
+        playlist.tracks.href.as_deref(),
+        Some(
+            "https://api.spotify.com/v1/playlists/37i9dQZF1DXacZOGa5EAdH/tracks?offset=0&limit=100"
+        )
+    );
+    let followers = playlist.followers.clone();
+    let simplified = playlist.simplify();
+    assert_eq!(simplified.followers, Some(followers));
+    assert_eq!(
+        simplified.href.as_deref(),
+        Some("https://api.spotify.com/v1/playlists/37i9dQZF1DXacZOGa5EAdH")
+    );
+    assert!(simplified.tracks.href.is_some());
+}
+
+#[test]
+fn playlist_primary_color() {
+    let playlist: Playlist = fixture("playlist_with_primary_color");
+    assert_eq!(playlist.primary_color.as_deref(), Some("#FFC864"));
+    assert_eq!(
+        playlist.simplify().primary_color.as_deref(),
+        Some("#FFC864")
+    );
+}
+
+#[test]
+fn sort_playlist_items() {
+    let playlist: Playlist = fixture("playlist_with_episodes");
+    let mut items = playlist.tracks.items;
+
+    PlaylistItem::sort(&mut items, PlaylistItemSort::Duration, None);
+    assert!(items[0].duration() < items[1].duration());
+    assert_eq!(items[2].item, None);
+
+    PlaylistItem::sort(&mut items, PlaylistItemSort::Artist, None);
+    assert!(matches!(items[0].item, Some(PlaylistItemType::Track(_))));
+
+    let track_id = items[0].id().unwrap().to_owned();
+    let mut features: AudioFeatures = fixture("audio_features");
+    features.id = track_id.clone();
+    let features = iter::once((track_id.clone(), features)).collect();
+    PlaylistItem::sort(
+        &mut items,
+        PlaylistItemSort::Feature(AudioFeature::Tempo),
+        Some(&features),
+    );
+    assert_eq!(items[0].id(), Some(&*track_id));
+
+    let ids = PlaylistItem::ids(&items);
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], PlaylistItemType::Track(&*track_id));
+}
+
+#[test]
+fn playlist_item_uris() {
+    let track: PlaylistItemType<_, &str> = PlaylistItemType::Track("1Jwc3ODLQxtbnS8M9TflSP");
+    assert_eq!(track.uri(), "spotify:track:1Jwc3ODLQxtbnS8M9TflSP");
+    let episode: PlaylistItemType<&str, _> = PlaylistItemType::Episode("512ojhOuo1ktJprKbVcKyQ");
+    assert_eq!(
+        serde_json::to_value(episode.uri_ref()).unwrap(),
+        "spotify:episode:512ojhOuo1ktJprKbVcKyQ"
+    );
+
+    // Items that are already URIs aren't prefixed again.
+    let uri: PlaylistItemType<_, &str> =
+        PlaylistItemType::Track("spotify:track:1Jwc3ODLQxtbnS8M9TflSP");
+    assert_eq!(uri.uri(), track.uri());
+    let uri: PlaylistItemType<&str, _> =
+        PlaylistItemType::Episode(String::from("spotify:episode:512ojhOuo1ktJprKbVcKyQ"));
+    assert_eq!(uri.uri(), episode.uri());
+    assert_eq!(
+        util::Uri::new("track", &"spotify:track:x").web_url(),
+        "https://open.spotify.com/track/x"
+    );
+
+    // Only URIs of the same type are recognized.
+    for id in &[
+        "spotify:episode:x",
+        "spotify:trac",
+        "spotify:tracks:x",
+        "spotifyx",
+        "",
+    ] {
+        let item: PlaylistItemType<_, &str> = PlaylistItemType::Track(id);
+        assert_eq!(item.uri(), format!("spotify:track:{}", id));
+    }
+}
+
+#[test]
+fn playlist_export_m3u8() {
+    let playlist: Playlist = fixture("playlist_with_episodes");
+    let export = PlaylistExport {
+        name: playlist.name,
+        description: playlist.description,
+        public: playlist.public,
+        collaborative: playlist.collaborative,
+        items: playlist.tracks.items,
+    };
+    let export: PlaylistExport =
+        serde_json::from_str(&serde_json::to_string(&export).unwrap()).unwrap();
+
+    let m3u = export.to_m3u8();
+    let mut lines = m3u.lines();
+    assert_eq!(lines.next(), Some("#EXTM3U"));
+    assert_eq!(
+        lines.next(),
+        Some(format!("#PLAYLIST:{}", export.name).as_str())
+    );
+    assert_eq!(m3u.matches("#EXTINF:").count(), 2);
+    assert!(m3u.contains("\nhttps://open.spotify.com/track/"));
+    assert!(m3u.contains("\nhttps://open.spotify.com/episode/"));
+}
+
+#[cfg(feature = "html-escape")]
+#[test]
+fn playlist_description_decoded() {
+    let playlist: Playlist = fixture("playlist_with_episodes");
+    assert_eq!(
+        playlist.description_decoded().unwrap(),
+        "A playlist with a track & an episode. It's mixed."
+    );
+}
+
+#[test]
+fn playback_with_ad() {
+    let playback: CurrentPlayback = fixture("playback_ad");
+    assert_eq!(playback.repeat_state, RepeatState::Off);
+    assert_eq!(playback.device.volume_percent.unwrap().percent(), 100);
+    assert_eq!(playback.currently_playing.currently_playing_type, "ad");
+    assert_eq!(playback.currently_playing.item, Some(PlayingType::Ad(None)));
+    assert!(playback
+        .currently_playing
+        .actions
+        .disallows
+        .contains(&Disallow::SkippingNext));
+    assert!(!playback
+        .currently_playing
+        .actions
+        .disallows
+        .contains(&Disallow::Pausing));
+    assert!(!playback.smart_shuffle);
+
+    let actions = &playback.currently_playing.actions;
+    assert!(actions.check(Disallow::Pausing).is_ok());
+    assert!(matches!(
+        actions.check(Disallow::SkippingNext),
+        Err(Error::ActionDisallowed(Disallow::SkippingNext))
+    ));
+}
+
+#[test]
+fn currently_playing_track() {
+    let playing: CurrentlyPlaying = fixture("currently_playing_track");
+    let context = playing.context.unwrap();
+    assert_eq!(context.context_type, ItemType::Album);
+    assert_eq!(context.id, "1uQ1hOZFr7ZwI3xi7dQKHe");
+    assert!(matches!(playing.item, Some(PlayingType::Track(_))));
+    assert_eq!(playing.currently_playing_type, "track");
+
+    let mut json: serde_json::Value = fixture("currently_playing_track");
+    json["currently_playing_type"] = "audiobook".into();
+    let playing: CurrentlyPlaying = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(playing.currently_playing_type, "audiobook");
+    assert!(matches!(playing.item, Some(PlayingType::Unknown(_))));
+    json["item"] = serde_json::json!({ "type": "chapter" });
+    let playing: CurrentlyPlaying = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(playing.item, None);
+
+    // A track that fails to parse is an error, not an unknown item.
+    json["currently_playing_type"] = "track".into();
+    serde_json::from_value::<CurrentlyPlaying>(json).unwrap_err();
+}
+
+#[test]
+fn user_private() {
+    let user: UserPrivate = fixture("user_private");
+    assert_eq!(user.id, "testuser");
+    assert_eq!(user.product, Some(Subscription::Premium));
+}
+
+#[test]
+fn devices() {
+    let devices: Vec<Device> = fixture("devices");
+    assert!(devices[0].is_private_session);
+    assert!(!devices[0].is_restricted);
+    assert_eq!(
+        devices.iter().find(|device| device.is_active).unwrap().name,
+        "Living Room"
+    );
+    assert!(devices[2].is_restricted);
+    assert_eq!(devices[2].id, None);
+    assert_eq!(devices[2].volume_percent, None);
+    assert_eq!(devices[2].device_type, DeviceType::Automobile);
+}
+
+#[test]
+fn audio_features() {
+    let features: AudioFeatures = fixture("audio_features");
+    assert_eq!(features.mode, Mode::Major);
+    assert_eq!(features.tempo, 103.022);
+}
+
+#[test]
+fn features_summary() {
+    assert_eq!(FeaturesSummary::new(&[]), None);
+
+    let features: AudioFeatures = fixture("audio_features");
+    let mut tracks = vec![features.clone(), features.clone(), features];
+    tracks[1].tempo = 120.0;
+    tracks[2].tempo = 128.0;
+    tracks[2].key = 9;
+    tracks[2].mode = Mode::Minor;
+
+    let summary = FeaturesSummary::new(&tracks).unwrap();
+    assert_eq!(summary.count, 3);
+    assert_eq!(summary.tempo.min, 103.022);
+    assert_eq!(summary.tempo.median, 120.0);
+    assert_eq!(summary.tempo.max, 128.0);
+    assert!(summary.energy.std_dev < 1e-9);
+    assert_eq!(
+        summary.tempo_histogram.into_iter().collect::<Vec<_>>(),
+        [(100, 1), (120, 2)]
+    );
+    assert_eq!(summary.keys[&(9, Mode::Minor)], 1);
+    assert_eq!(summary.keys.values().sum::<usize>(), 3);
+}
+
+#[test]
+fn endpoint_error() {
+    let error: EndpointError = fixture("endpoint_error");
+    assert_eq!(error.status, 404);
+    assert_eq!(error.status_code(), 404);
+    assert_eq!(error.reason, Some(PlayerErrorReason::NoActiveDevice));
+    let error = Error::Endpoint(error);
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+    assert!(error.is_no_active_device());
+    assert!(!error.is_premium_required());
+
+    let json =
+        r#"{"error": {"status": 403, "message": "Player command failed: Premium required"}}"#;
+    let error = Error::Endpoint(serde_json::from_str(json).unwrap());
+    assert!(error.is_premium_required());
+
+    let json = r#"{"error": {"status": 404, "message": "Non existing id"}}"#;
+    let mut error: EndpointError = serde_json::from_str(json).unwrap();
+    assert_eq!(error.request_id, None);
+    error.request_id = Some(RequestId(7));
+    assert_eq!(
+        serde_json::from_str::<EndpointError>(&serde_json::to_string(&error).unwrap())
+            .unwrap()
+            .request_id,
+        None
+    );
+    let error = Error::Endpoint(error);
+    assert_eq!(error.player_reason(), None);
+    assert_eq!(error.request_id(), Some(RequestId(7)));
+    assert_eq!(RequestId(7).to_string(), "request 7");
+}
+
+#[test]
+fn recommendations() {
+    let recommendations: Recommendations = fixture("recommendations");
+    assert_eq!(recommendations.seeds[0].entity_type, SeedType::Artist);
+    assert_eq!(recommendations.seeds[1].entity_type, SeedType::Genre);
+    assert_eq!(
+        recommendations.seeds[0].href.as_deref(),
+        Some("https://api.spotify.com/v1/artists/0L8ExT028jH3ddEcZwqJJ5")
+    );
+    assert_eq!(recommendations.seeds[1].href, None);
+    assert!(recommendations.extra.is_empty());
+
+    let json =
+        r#"{"seeds": [], "tracks": [], "href": "https://api.spotify.com/v1/recommendations"}"#;
+    let recommendations: Recommendations = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        recommendations.extra["href"],
+        "https://api.spotify.com/v1/recommendations"
+    );
+}
+
+#[test]
+fn any_item() {
+    let track: Track = fixture("track");
+    let artist: Artist = fixture("artist");
+    let episode: Episode = fixture("episode");
+    let items = vec![
+        AnyItem::from(track),
+        AnyItem::from(artist),
+        AnyItem::from(episode.simplify()),
+    ];
+
+    let types: Vec<_> = items.iter().map(AnyItem::item_type).collect();
+    assert_eq!(
+        types,
+        [ItemType::Track, ItemType::Artist, ItemType::Episode]
+    );
+    assert_eq!(items[0].name(), "Walk Like an Egyptian");
+    assert_eq!(items[1].id(), Some("0L8ExT028jH3ddEcZwqJJ5"));
+    assert!(!items[2].images().is_empty());
+    assert_eq!(
+        items[1].uri().as_deref(),
+        Some("spotify:artist:0L8ExT028jH3ddEcZwqJJ5")
+    );
+    assert_eq!(
+        items[2].web_url().as_deref(),
+        Some("https://open.spotify.com/episode/512ojhOuo1ktJprKbVcKyQ")
+    );
+
+    let json = serde_json::to_string(&items).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<AnyItem>>(&json).unwrap(), items);
+
+    serde_json::from_str::<AnyItem>(r#"{"type": "podcast"}"#).unwrap_err();
+}
+
+#[test]
+fn parse_modes() {
+    let json = r#"{
+        "id": null,
+        "is_active": false,
+        "is_private_session": false,
+        "is_restricted": false,
+        "name": "Fridge",
+        "type": "Refrigerator",
+        "volume_percent": null
+    }"#;
+
+    let device: Device =
+        util::with_parse_mode(ParseMode::Lenient, || serde_json::from_str(json)).unwrap();
+    assert_eq!(device.device_type, DeviceType::Unknown);
+    util::with_parse_mode(ParseMode::Strict, || serde_json::from_str::<Device>(json)).unwrap_err();
+
+    let json = r#"{"text": "2020 Label", "type": "P"}"#;
+    util::with_parse_mode(ParseMode::Strict, || {
+        serde_json::from_str::<Copyright>(json)
+    })
+    .unwrap();
+
+    let json = r#"{
+        "external_urls": {},
+        "followers": { "total": 0 },
+        "id": "0L8ExT028jH3ddEcZwqJJ5",
+        "name": "Nameless",
+        "type": "artist"
+    }"#;
+    let artist: Artist =
+        util::with_parse_mode(ParseMode::Lenient, || serde_json::from_str(json)).unwrap();
+    assert!(artist.genres.is_empty());
+    util::with_parse_mode(ParseMode::Strict, || serde_json::from_str::<Artist>(json)).unwrap_err();
+}
+
+#[test]
+fn collection_context() {
+    let json = serde_json::json!({
+        "type": "collection",
+        "external_urls": { "spotify": "https://open.spotify.com/collection/tracks" },
+        "uri": "spotify:user:wizzler:collection",
+    });
+    let context: Context = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(context.context_type, ItemType::Collection);
+    assert_eq!(context.id, "wizzler");
+    assert_eq!(serde_json::to_value(&context).unwrap(), json);
+
+    let mut invalid = json;
+    invalid["uri"] = "spotify:user:wizzler:collection:extra".into();
+    assert!(serde_json::from_value::<Context>(invalid).is_err());
+}
+
+#[test]
+fn item_types() {
+    for item_type in &[
+        ItemType::Album,
+        ItemType::Artist,
+        ItemType::Playlist,
+        ItemType::Track,
+        ItemType::Show,
+        ItemType::Episode,
+        ItemType::Collection,
+        ItemType::User,
+    ] {
+        assert_eq!(serde_json::to_value(item_type).unwrap(), item_type.as_str());
+        assert_eq!(
+            item_type.as_str().parse::<ItemType>().as_ref(),
+            Ok(item_type)
+        );
+        assert_eq!(
+            ItemType::try_from(item_type.as_str()).as_ref(),
+            Ok(item_type)
+        );
+    }
+
+    let audiobook: ItemType = serde_json::from_str(r#""audiobook""#).unwrap();
+    assert_eq!(audiobook, ItemType::Other("audiobook".to_owned()));
+    assert_eq!(serde_json::to_string(&audiobook).unwrap(), r#""audiobook""#);
+    assert!("audiobook".parse::<ItemType>().is_err());
+    assert_eq!(
+        ItemType::try_from("bogus"),
+        Err(ParseEnumError {
+            expected: "item type",
+            value: "bogus".to_owned(),
+        })
+    );
+
+    assert!(ItemType::Playlist.is_context());
+    assert!(!ItemType::Track.is_context());
+    assert!(!audiobook.is_context());
+}
+
+#[test]
+fn enum_strings() {
+    assert_serde_str(
+        &[RepeatState::Off, RepeatState::Track, RepeatState::Context],
+        RepeatState::as_str,
+    );
+    assert_serde_str(
+        &[
+            AlbumGroup::Album,
+            AlbumGroup::Single,
+            AlbumGroup::Compilation,
+            AlbumGroup::AppearsOn,
+        ],
+        AlbumGroup::as_str,
+    );
+    assert_serde_str(
+        &[
+            Disallow::InterruptingPlayback,
+            Disallow::Pausing,
+            Disallow::Resuming,
+            Disallow::Seeking,
+            Disallow::SkippingNext,
+            Disallow::SkippingPrev,
+            Disallow::TogglingRepeatContext,
+            Disallow::TogglingShuffle,
+            Disallow::TogglingRepeatTrack,
+            Disallow::TransferringPlayback,
+        ],
+        Disallow::as_str,
+    );
+}
+
+#[test]
+fn locale_codes() {
+    let country = Country::new("gb").unwrap();
+    assert_eq!(country.as_str(), "GB");
+    assert_eq!("GB".parse::<Country>(), Ok(country));
+    assert_eq!(serde_json::to_string(&country).unwrap(), r#""GB""#);
+    assert_eq!(serde_json::from_str::<Country>(r#""gb""#).unwrap(), country);
+    // Codes that aren't assigned yet are still accepted.
+    assert_eq!(Country::new("XZ").unwrap().to_string(), "XZ");
+    for invalid in &["", "G", "GBR", "G1", "ü"] {
+        let error = Country::new(invalid).unwrap_err();
+        assert_eq!(error.value, *invalid);
+    }
+    assert!(serde_json::from_str::<Country>(r#""GBR""#).is_err());
+
+    let language = Language::new("EN").unwrap();
+    assert_eq!(language.as_str(), "en");
+    assert_eq!(serde_json::to_string(&language).unwrap(), r#""en""#);
+    assert!(Language::new("eng").is_err());
+}
+
+#[cfg(feature = "isocountry")]
+#[test]
+fn locale_codes_isocountry() {
+    use std::convert::TryFrom;
+
+    use isocountry::CountryCode;
+
+    let country = Country::from(CountryCode::GBR);
+    assert_eq!(country.as_str(), "GB");
+    assert_eq!(CountryCode::try_from(country).unwrap(), CountryCode::GBR);
+}
+
+#[test]
+fn playlist_partial() {
+    let partial: PlaylistPartial = serde_json::from_str(
+        r#"{"snapshot_id":"MTAsZWYxMjM0","tracks":{"total":250,"items":[{"track":{"id":"x"}}]}}"#,
+    )
+    .unwrap();
+    assert_eq!(partial.snapshot_id.as_deref(), Some("MTAsZWYxMjM0"));
+    assert_eq!(partial.name, None);
+    assert_eq!(partial.owner, None);
+    let tracks = partial.tracks.unwrap();
+    assert_eq!(tracks.total, Some(250));
+    assert_eq!(tracks.next, None);
+    assert_eq!(tracks.items.unwrap()[0]["track"]["id"], "x");
+
+    // A full playlist is also a valid partial playlist.
+    let full: PlaylistPartial = fixture("playlist_with_episodes");
+    assert!(full.name.is_some());
+    assert!(full.tracks.unwrap().items.is_some());
+}
+
+#[test]
+fn snapshot_id() {
+    let id: SnapshotId = serde_json::from_str(r#""MTAsZWYxMjM0""#).unwrap();
+    assert_eq!(id, *"MTAsZWYxMjM0");
+    assert_eq!(id.to_string(), "MTAsZWYxMjM0");
+    assert_eq!(serde_json::to_string(&id).unwrap(), r#""MTAsZWYxMjM0""#);
+}
+
+#[test]
+fn playback_position() {
+    let position = PlaybackPosition::from_millis(152_104);
+    assert_eq!(serde_json::to_string(&position).unwrap(), "152104");
+    assert_eq!(
+        serde_json::from_str::<PlaybackPosition>("152104").unwrap(),
+        position
+    );
+    assert_eq!(position.to_string(), "152104ms");
+
+    assert_eq!(
+        PlaybackPosition::truncating(Duration::from_micros(152_104_999)),
+        position
+    );
+    assert_eq!(
+        PlaybackPosition::try_from(Duration::from_micros(152_104_999)),
+        Err(InvalidPosition::Imprecise(Duration::from_micros(
+            152_104_999
+        )))
+    );
+    assert_eq!(
+        PlaybackPosition::try_from(Duration::from_secs(u64::MAX)),
+        Err(InvalidPosition::TooLarge(Duration::from_secs(u64::MAX)))
+    );
+
+    let length = Duration::from_millis(152_106);
+    assert_eq!(position.within(length), Ok(position));
+    assert_eq!(
+        PlaybackPosition::from_millis(152_107).within(length),
+        Err(InvalidPosition::PastEnd {
+            position: PlaybackPosition::from_millis(152_107),
+            length,
+        })
+    );
+}
+
+#[test]
+fn timestamps() {
+    let json =
+        r#"{"added_at":"2020-06-01T12:34:56Z","added_by":null,"is_local":false,"track":null}"#;
+    let item: PlaylistItem = serde_json::from_str(json).unwrap();
+    let added_at = item.added_at.unwrap();
+    assert_eq!(
+        serde_json::to_string(&added_at).unwrap(),
+        r#""2020-06-01T12:34:56Z""#
+    );
+    assert_eq!(
+        serde_json::to_value(&item).unwrap()["added_at"],
+        "2020-06-01T12:34:56Z"
+    );
+    #[cfg(feature = "time")]
+    assert_eq!(
+        item.added_at_time(),
+        Some(::time::OffsetDateTime::from_unix_timestamp(1_591_014_896).unwrap())
+    );
+
+    let item: PlaylistItem =
+        serde_json::from_str(r#"{"added_at":null,"added_by":null,"is_local":false,"track":null}"#)
+            .unwrap();
+    assert_eq!(item.added_at, None);
+    #[cfg(feature = "time")]
+    assert_eq!(item.added_at_time(), None);
+    assert!(serde_json::from_str::<PlaylistItem>(
+        r#"{"added_at":"yesterday","added_by":null,"is_local":false,"track":null}"#
+    )
+    .is_err());
+}
+
+#[test]
+fn playlist_owner() {
+    let playlist: Playlist = fixture("playlist_with_episodes");
+    assert!(playlist.is_owned_by("spotify"));
+    assert!(!playlist.is_owned_by("someone"));
+    assert!(playlist.owner.is_spotify_official());
+
+    let mut playlist = playlist.simplify();
+    assert!(playlist.is_owned_by("spotify"));
+    playlist.owner.id = "someone".to_owned();
+    assert!(playlist.is_owned_by("someone"));
+    assert!(!playlist.owner.is_spotify_official());
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn stubs() {
+    fn round_trip<T: DeserializeOwned + Serialize + PartialEq + Debug>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value);
+    }
+
+    let track = Track::stub("track", "Track");
+    round_trip(&track);
+    assert_eq!(track.id.as_deref(), Some("track"));
+    assert_eq!(track.album.id.as_deref(), Some("album"));
+    assert_eq!(
+        track.simplify(),
+        crate::TrackSimplified::stub("track", "Track")
+    );
+
+    let playlist = Playlist::stub("playlist", "Playlist");
+    round_trip(&playlist);
+    assert!(playlist.is_owned_by("user"));
+    let mut simplified = crate::PlaylistSimplified::stub("playlist", "Playlist");
+    simplified.followers = Some(playlist.followers.clone());
+    assert_eq!(playlist.simplify(), simplified);
+
+    round_trip(&Album::stub("album", "Album"));
+    round_trip(&Artist::stub("artist", "Artist"));
+}
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::model::{AlbumSimplified, ArtistSimplified, Context, Restrictions, TypeTrack};
+use crate::model::{
+    AlbumSimplified, ArtistSimplified, Context, Restrictions, Timestamp, TypeTrack,
+};
+use crate::util;
 
 macro_rules! inherit_track_simplified {
     ($(#[$attr:meta])* $name:ident { $($(#[$f_attr:meta])* $f_name:ident : $f_ty:ty,)* }) => {
@@ -24,6 +26,7 @@ macro_rules! inherit_track_simplified {
             #[serde(rename = "duration_ms", with = "serde_millis")]
             duration: Duration,
             /// Whether the track has explicit lyrics, false if unknown.
+            #[serde(default = "util::lenient_default")]
             explicit: bool,
             /// Known external URLs for this track.
             external_urls: HashMap<String, String>,
@@ -55,6 +58,7 @@ macro_rules! inherit_track_simplified {
             #[serde(rename = "type")]
             item_type: TypeTrack,
             /// Whether the track is a local track.
+            #[serde(default = "util::lenient_default")]
             is_local: bool,
         });
     }
@@ -74,6 +78,7 @@ inherit_track_simplified!(
         /// The popularity of the track. The value will be between 0 and 100, with 100 being the most
         /// popular. The popularity is calculated from the total number of plays and how recent they
         /// are.
+        #[serde(default = "util::lenient_default")]
         popularity: u32,
     }
 );
@@ -107,6 +112,95 @@ impl From<Track> for TrackSimplified {
     }
 }
 
+/// The availability of a track in a market, obtained from
+/// [`Tracks::check_availability`](crate::Tracks::check_availability).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackAvailability {
+    /// The track is playable in the market.
+    Playable,
+    /// The track is not available in the market, but [track
+    /// relinking](https://developer.spotify.com/documentation/general/guides/track-relinking-guide/)
+    /// replaced it with another track that is. Contains the ID of the replacement track, if it has
+    /// one.
+    Relinked(Option<String>),
+    /// The track is not playable in the market. Contains the restrictions Spotify gave, if any.
+    Restricted(Option<Restrictions>),
+}
+
+impl TrackAvailability {
+    /// Get the availability of a track that was requested in a market.
+    #[must_use]
+    pub fn of(track: &Track) -> Self {
+        if track.is_playable == Some(false) || track.restrictions.is_some() {
+            Self::Restricted(track.restrictions.clone())
+        } else if track.linked_from.is_some() {
+            Self::Relinked(track.id.clone())
+        } else {
+            Self::Playable
+        }
+    }
+}
+
+/// A borrowed view of the most commonly used fields of a [`Track`] or [`TrackSimplified`],
+/// obtained from [`Track::to_ref`] or [`TrackSimplified::to_ref`].
+///
+/// Unlike [`Track::simplify`], this doesn't consume or clone the track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackRef<'a> {
+    /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// for this track. Only not present for a local track.
+    pub id: Option<&'a str>,
+    /// The name of the track.
+    pub name: &'a str,
+    /// The artists who performed the track.
+    pub artists: &'a [ArtistSimplified],
+    /// The track length.
+    pub duration: Duration,
+    /// Whether the track has explicit lyrics, false if unknown.
+    pub explicit: bool,
+    /// Whether the track is a local track.
+    pub is_local: bool,
+}
+
+impl Track {
+    /// Borrow the most commonly used fields of the track.
+    #[must_use]
+    pub fn to_ref(&self) -> TrackRef<'_> {
+        TrackRef {
+            id: self.id.as_deref(),
+            name: &self.name,
+            artists: &self.artists,
+            duration: self.duration,
+            explicit: self.explicit,
+            is_local: self.is_local,
+        }
+    }
+}
+impl TrackSimplified {
+    /// Borrow the most commonly used fields of the track.
+    #[must_use]
+    pub fn to_ref(&self) -> TrackRef<'_> {
+        TrackRef {
+            id: self.id.as_deref(),
+            name: &self.name,
+            artists: &self.artists,
+            duration: self.duration,
+            explicit: self.explicit,
+            is_local: self.is_local,
+        }
+    }
+}
+impl<'a> From<&'a Track> for TrackRef<'a> {
+    fn from(track: &'a Track) -> Self {
+        track.to_ref()
+    }
+}
+impl<'a> From<&'a TrackSimplified> for TrackRef<'a> {
+    fn from(track: &'a TrackSimplified) -> Self {
+        track.to_ref()
+    }
+}
+
 /// A link to a track.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TrackLink {
@@ -120,29 +214,74 @@ pub struct TrackLink {
     pub item_type: TypeTrack,
 }
 
+/// The popularity of a track, obtained from [`Tracks::get_popularity`](crate::Tracks::get_popularity).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackPopularity {
+    /// The [Spotify ID](https://developer.spotify.com/documentation/web-api/#spotify-uris-and-ids)
+    /// for the track. If [track
+    /// relinking](https://developer.spotify.com/documentation/general/guides/track-relinking-guide/)
+    /// was applied, this may not be the requested ID.
+    pub id: String,
+    /// The popularity of the track, between 0 and 100.
+    pub popularity: u32,
+    /// The number of markets the track is available in. Only [`None`] if a market was given.
+    pub available_markets: Option<usize>,
+}
+
 /// When and how a track was played.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PlayHistory {
     /// The track the user listened to.
     pub track: TrackSimplified,
     /// When the track was played.
-    pub played_at: DateTime<Utc>,
+    pub played_at: Timestamp,
     /// The context from which the track was played.
     pub context: Option<Context>,
 }
 
+#[cfg(feature = "time")]
+impl PlayHistory {
+    /// When the track was played, as a [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `played_at` is outside the years -9999 to 9999 that `time` supports, which Spotify
+    /// never sends.
+    #[must_use]
+    pub fn played_at_time(&self) -> ::time::OffsetDateTime {
+        util::offset_date_time(self.played_at)
+    }
+}
+
 /// Information about a track that has been saved.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SavedTrack {
     /// When the track was saved.
-    pub added_at: DateTime<Utc>,
+    pub added_at: Timestamp,
     /// Information about the track.
     pub track: Track,
 }
 
+#[cfg(feature = "time")]
+impl SavedTrack {
+    /// When the track was saved, as a [`time::OffsetDateTime`](https://docs.rs/time/0.3/time/struct.OffsetDateTime.html).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `added_at` is outside the years -9999 to 9999 that `time` supports, which Spotify
+    /// never sends.
+    #[must_use]
+    pub fn added_at_time(&self) -> ::time::OffsetDateTime {
+        util::offset_date_time(self.added_at)
+    }
+}
+
 /// The number of tracks an object contains.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tracks {
+    /// A link to the Web API endpoint returning the tracks. [`None`] if Spotify didn't send it.
+    #[serde(default)]
+    pub href: Option<String>,
     /// The number of tracks.
     pub total: usize,
 }
@@ -66,6 +66,14 @@ inherit_user_public!(
     }
 );
 
+impl UserSimplified {
+    /// Whether this is Spotify's own account, which owns Spotify's editorial playlists.
+    #[must_use]
+    pub fn is_spotify_official(&self) -> bool {
+        self.id == "spotify"
+    }
+}
+
 impl UserPublic {
     /// Convert to a [`UserSimplified`].
     #[must_use]
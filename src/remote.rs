@@ -0,0 +1,310 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    AsClient, Client, CurrentPlayback, Device, Error, ItemType, Play, Player, PlayingType, Volume,
+};
+
+/// A high-level remote control for the current user's Spotify playback, created by
+/// [`Client::remote`].
+///
+/// Unlike [`Player`], which maps directly to Spotify's endpoints, this keeps track of which
+/// device to control and remembers the current playback for a short time so that commands such
+/// as [`volume_up`](Self::volume_up) don't have to fetch it every time. The remembered playback
+/// is forgotten whenever a command is sent.
+///
+/// This is only available when the `remote` feature of this library is activated, which it is
+/// not by default.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example(client: &aspotify::Client) -> Result<(), aspotify::Error> {
+/// let remote = client.remote();
+///
+/// remote.play_album("03JPFQvZRnHHysSZrSFmKY").await?;
+/// if let Some(playing) = remote.now_playing().await? {
+///     println!("Now playing: {:?}", playing);
+/// }
+/// remote.volume_up(5).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SpotifyRemote<C> {
+    player: Player<C>,
+    device: Option<String>,
+    cache_duration: Duration,
+    playback: Mutex<Option<(Instant, Option<CurrentPlayback>)>>,
+}
+
+impl Client {
+    /// Create a high-level remote control for the current user's playback.
+    ///
+    /// This is only available when the `remote` feature of this library is activated.
+    #[must_use]
+    pub fn remote(&self) -> SpotifyRemote<&Self> {
+        SpotifyRemote::new(self)
+    }
+}
+
+impl<C: AsClient> SpotifyRemote<C> {
+    /// Create a remote control from a client. It controls the user's active device, and remembers
+    /// the playback for one second.
+    pub fn new(client: C) -> Self {
+        Self {
+            player: Player(client),
+            device: None,
+            cache_duration: Duration::from_secs(1),
+            playback: Mutex::new(None),
+        }
+    }
+    /// Get the device that is being controlled, or [`None`] if the active device is.
+    #[must_use]
+    pub fn device(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+    /// Set the device to control by its ID, or [`None`] to control the active device.
+    pub fn set_device(&mut self, device: Option<String>) {
+        self.device = device;
+        *self.playback.get_mut() = None;
+    }
+    /// Set how long the current playback is remembered for.
+    pub fn set_cache_duration(&mut self, duration: Duration) {
+        self.cache_duration = duration;
+    }
+    /// Get the endpoints the remote is built on, for operations it doesn't support.
+    #[must_use]
+    pub const fn player(&self) -> &Player<C> {
+        &self.player
+    }
+
+    /// Get the user's available devices.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn devices(&self) -> Result<Vec<Device>, Error> {
+        Ok(self.player.get_devices().await?.data)
+    }
+    /// Get the current playback, or [`None`] if nothing is playing. This may be up to the
+    /// [cache duration](Self::set_cache_duration) old.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn playback(&self) -> Result<Option<CurrentPlayback>, Error> {
+        let mut cached = self.playback.lock().await;
+        let now = self.player.0.as_client().clock.now();
+        if let Some((fetched, playback)) = &*cached {
+            if now.saturating_duration_since(*fetched) < self.cache_duration {
+                return Ok(playback.clone());
+            }
+        }
+        let playback = self.player.get_playback(None).await?.data;
+        *cached = Some((now, playback.clone()));
+        Ok(playback)
+    }
+    /// Get what is currently playing, or [`None`] if nothing is.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn now_playing(&self) -> Result<Option<PlayingType>, Error> {
+        Ok(self
+            .playback()
+            .await?
+            .and_then(|playback| playback.currently_playing.item))
+    }
+
+    /// Play an album from its start.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn play_album(&self, id: &str) -> Result<(), Error> {
+        self.play_context(ItemType::Album, id).await
+    }
+    /// Play an artist's tracks.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn play_artist(&self, id: &str) -> Result<(), Error> {
+        self.play_context(ItemType::Artist, id).await
+    }
+    /// Play a playlist from its start.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn play_playlist(&self, id: &str) -> Result<(), Error> {
+        self.play_context(ItemType::Playlist, id).await
+    }
+    /// Play a list of tracks, given by their IDs or URIs.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn play_tracks(&self, ids: &[&str]) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player
+            .play(Some(Play::Tracks(ids)), None, self.device())
+            .await
+    }
+    async fn play_context(&self, context_type: ItemType, id: &str) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player
+            .play(
+                Some(Play::<'_, &[u8]>::Context(context_type, id, 0)),
+                None,
+                self.device(),
+            )
+            .await
+    }
+
+    /// Pause playback.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn pause(&self) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player.pause(self.device()).await
+    }
+    /// Resume playback.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn resume(&self) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player.resume(self.device()).await
+    }
+    /// Pause playback if it is playing, or resume it if it is paused. Returns whether it is now
+    /// playing, or [`None`] if nothing is playing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn toggle(&self) -> Result<Option<bool>, Error> {
+        self.forget_playback().await;
+        self.player.toggle_playback(self.device()).await
+    }
+    /// Skip to the next track.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn next(&self) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player.skip_next(self.device()).await
+    }
+    /// Skip to the previous track.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn previous(&self) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player.skip_prev(self.device()).await
+    }
+
+    /// Set the volume.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn set_volume(&self, volume: Volume) -> Result<(), Error> {
+        self.forget_playback().await;
+        self.player.set_volume(volume, self.device()).await
+    }
+    /// Turn the volume up by a number of percentage points, saturating at 100%. Returns the new
+    /// volume, or [`None`] if nothing is playing or the device's volume isn't known.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn volume_up(&self, points: u8) -> Result<Option<Volume>, Error> {
+        self.change_volume(|volume| volume + points).await
+    }
+    /// Turn the volume down by a number of percentage points, saturating at 0%. Returns the new
+    /// volume, or [`None`] if nothing is playing or the device's volume isn't known.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request fails.
+    pub async fn volume_down(&self, points: u8) -> Result<Option<Volume>, Error> {
+        self.change_volume(|volume| volume - points).await
+    }
+    async fn change_volume(
+        &self,
+        change: impl FnOnce(Volume) -> Volume,
+    ) -> Result<Option<Volume>, Error> {
+        let current = match self.device() {
+            Some(id) => self
+                .devices()
+                .await?
+                .into_iter()
+                .find(|device| device.id.as_deref() == Some(id))
+                .and_then(|device| device.volume_percent),
+            None => self
+                .playback()
+                .await?
+                .and_then(|playback| playback.device.volume_percent),
+        };
+        let volume = match current {
+            Some(current) => change(current),
+            None => return Ok(None),
+        };
+        self.set_volume(volume).await?;
+        Ok(Some(volume))
+    }
+
+    async fn forget_playback(&self) {
+        *self.playback.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time;
+
+    use crate::endpoints::client;
+    use crate::PlayingType;
+
+    #[tokio::test]
+    async fn test_remote() {
+        let client = client();
+        let mut remote = client.remote();
+        let Some(device) = remote
+            .devices()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|device| !device.is_restricted)
+        else {
+            return;
+        };
+        remote.set_device(device.id);
+
+        remote.play_album("03JPFQvZRnHHysSZrSFmKY").await.unwrap();
+        time::sleep(Duration::from_millis(500)).await;
+        match remote.now_playing().await.unwrap() {
+            Some(PlayingType::Track(track)) => {
+                assert_eq!(track.album.id.as_deref(), Some("03JPFQvZRnHHysSZrSFmKY"));
+            }
+            other => panic!("unexpected playing item {:?}", other),
+        }
+
+        if let Some(volume) = remote.volume_down(10).await.unwrap() {
+            time::sleep(Duration::from_millis(500)).await;
+            assert_eq!(remote.volume_up(10).await.unwrap(), Some(volume + 10));
+        }
+
+        remote.pause().await.unwrap();
+    }
+}
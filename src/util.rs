@@ -1,29 +1,97 @@
 //! Useful serialization and deserialization functions.
 
-use std::fmt::{self, Formatter};
-use std::time::{Duration, Instant};
+use std::cell::Cell;
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::iter;
 
 use chrono::NaiveDate;
 use serde::de::{self, Deserializer, Unexpected, Visitor};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
-pub(crate) fn deserialize_instant_seconds<'de, D>(deserializer: D) -> Result<Instant, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct Expires;
+#[cfg(feature = "time")]
+use crate::Timestamp;
+use crate::{ItemType, ParseEnumError, ParseMode};
 
-    impl<'de> Visitor<'de> for Expires {
-        type Value = Instant;
-        fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
-            f.write_str("number of seconds until the token expires")
-        }
-        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
-            Ok(Instant::now() + Duration::from_secs(v))
-        }
+thread_local! {
+    static PARSE_MODE: Cell<ParseMode> = const { Cell::new(ParseMode::Lenient) };
+    static DEFAULTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Convert a [`Timestamp`] to a `time::OffsetDateTime`, for the accessors added by the `time`
+/// feature.
+#[cfg(feature = "time")]
+pub(crate) fn offset_date_time(timestamp: Timestamp) -> ::time::OffsetDateTime {
+    let nanos = i128::from(timestamp.timestamp()) * 1_000_000_000
+        + i128::from(timestamp.timestamp_subsec_nanos());
+    ::time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+        .expect("timestamp is out of the range supported by time")
+}
+
+/// Run a deserialization function in the given parse mode.
+///
+/// In strict mode, this fails if any field was defaulted by [`lenient_default`].
+#[cfg(any(feature = "client", test))]
+pub(crate) fn with_parse_mode<T, E: de::Error>(
+    mode: ParseMode,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let old_mode = PARSE_MODE.with(|cell| cell.replace(mode));
+    DEFAULTED.with(|cell| cell.set(false));
+
+    let res = f();
+
+    PARSE_MODE.with(|cell| cell.set(old_mode));
+    let defaulted = DEFAULTED.with(|cell| cell.replace(false));
+
+    if mode == ParseMode::Strict && defaulted && res.is_ok() {
+        return Err(E::custom("a non-critical field is missing"));
+    }
+    res
+}
+
+pub(crate) fn is_lenient() -> bool {
+    PARSE_MODE.with(Cell::get) == ParseMode::Lenient
+}
+
+/// Used as `#[serde(default = "util::lenient_default")]` for fields that can be defaulted when
+/// missing in lenient mode.
+pub(crate) fn lenient_default<T: Default>() -> T {
+    DEFAULTED.with(|cell| cell.set(true));
+    T::default()
+}
+
+/// Deserialize an enum that has an `Unknown` variant, which unknown values become in lenient
+/// mode.
+pub(crate) fn deserialize_enum_lenient<'de, D: Deserializer<'de>, T: Copy>(
+    deserializer: D,
+    expected: &'static str,
+    variants: &[T],
+    as_str: impl Fn(T) -> &'static str,
+    unknown: T,
+) -> Result<T, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    match parse_enum(&s, expected, variants, as_str) {
+        Ok(variant) => Ok(variant),
+        Err(_) if is_lenient() => Ok(unknown),
+        Err(e) => Err(de::Error::custom(e)),
     }
+}
 
-    deserializer.deserialize_u64(Expires)
+/// Parse a string into whichever of `variants` has that string as its string representation.
+pub(crate) fn parse_enum<T: Copy>(
+    s: &str,
+    expected: &'static str,
+    variants: &[T],
+    as_str: impl Fn(T) -> &'static str,
+) -> Result<T, ParseEnumError> {
+    variants
+        .iter()
+        .copied()
+        .find(|&variant| as_str(variant) == s)
+        .ok_or_else(|| ParseEnumError {
+            expected,
+            value: s.to_owned(),
+        })
 }
 
 pub(crate) mod serde_duration_secs {
@@ -123,7 +191,7 @@ pub(crate) mod serde_status_code {
     use std::convert::TryInto;
     use std::fmt::{self, Formatter};
 
-    use reqwest::StatusCode;
+    use http::StatusCode;
     use serde::{
         de::{self, Visitor},
         Deserializer, Serializer,
@@ -161,7 +229,7 @@ pub(crate) mod serde_disallows {
     use std::fmt::{self, Formatter};
 
     use serde::{
-        de::{MapAccess, Visitor},
+        de::{self, MapAccess, Visitor},
         ser::SerializeMap,
         Deserializer, Serializer,
     };
@@ -182,7 +250,12 @@ pub(crate) mod serde_disallows {
             fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
                 let mut v = Vec::with_capacity(10);
 
-                while let Some((key, val)) = map.next_entry::<Disallow, Option<bool>>()? {
+                while let Some((key, val)) = map.next_entry::<String, Option<bool>>()? {
+                    let key = match key.parse::<Disallow>() {
+                        Ok(key) => key,
+                        Err(_) if super::is_lenient() => continue,
+                        Err(e) => return Err(de::Error::custom(e)),
+                    };
                     if val == Some(true) {
                         v.push(key);
                     }
@@ -207,6 +280,128 @@ pub(crate) mod serde_disallows {
     }
 }
 
+/// A Spotify URI such as `spotify:track:{id}`, written straight into the formatter or serializer
+/// instead of being allocated as a `String` first.
+///
+/// The ID may also already be a URI of the same type, in which case it is used as is.
+#[derive(Clone, Copy)]
+pub(crate) struct Uri<'a> {
+    item_type: &'a str,
+    id: &'a dyn Display,
+    suffix: &'static str,
+}
+
+impl<'a> Uri<'a> {
+    pub(crate) fn new(item_type: &'a str, id: &'a dyn Display) -> Self {
+        Self {
+            item_type,
+            id,
+            suffix: "",
+        }
+    }
+    /// The URI of a playback context. This is the same as [`new`](Self::new), except that the
+    /// URI of a user's collection is `spotify:user:{id}:collection`.
+    pub(crate) fn context(context_type: &'a ItemType, id: &'a dyn Display) -> Self {
+        match context_type {
+            ItemType::Collection => Self {
+                item_type: "user",
+                id,
+                suffix: ":collection",
+            },
+            _ => Self::new(context_type.as_str(), id),
+        }
+    }
+    /// The link to the item on the Spotify website.
+    pub(crate) fn web_url(&self) -> String {
+        format!("https://open.spotify.com/{}/{}", self.item_type, self.id())
+    }
+    /// The ID, with the `spotify:{type}:` prefix removed if there is one.
+    fn id(&self) -> impl Display + 'a {
+        struct Id<'a>(Uri<'a>);
+        impl Display for Id<'_> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                let mut writer = StripPrefix {
+                    f,
+                    item_type: self.0.item_type,
+                    matched: Some(0),
+                };
+                write!(writer, "{}", self.0.id)?;
+                writer.finish()
+            }
+        }
+        Id(*self)
+    }
+}
+
+impl Display for Uri<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "spotify:{}:{}{}", self.item_type, self.id(), self.suffix)
+    }
+}
+
+/// A writer that removes a `spotify:{type}:` prefix from what is written to it.
+struct StripPrefix<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    item_type: &'a str,
+    /// How many bytes of the prefix have been written and held back, or [`None`] once it is known
+    /// whether there is a prefix.
+    matched: Option<usize>,
+}
+
+impl<'a> StripPrefix<'a, '_> {
+    fn prefix(item_type: &'a str) -> impl Iterator<Item = u8> + 'a {
+        "spotify:"
+            .bytes()
+            .chain(item_type.bytes())
+            .chain(iter::once(b':'))
+    }
+    /// Write the part of the prefix that was held back, as it turned out not to be a prefix.
+    fn finish(&mut self) -> fmt::Result {
+        if let Some(matched) = self.matched.take() {
+            for byte in Self::prefix(self.item_type).take(matched) {
+                self.f.write_char(char::from(byte))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Write for StripPrefix<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let Some(mut matched) = self.matched else {
+            return self.f.write_str(s);
+        };
+        let mut prefix = Self::prefix(self.item_type).skip(matched);
+        for (i, byte) in s.bytes().enumerate() {
+            match prefix.next() {
+                Some(expected) if expected == byte => matched += 1,
+                // The prefix is ASCII, so `i` is always at a character boundary.
+                Some(_) => {
+                    self.matched = Some(matched);
+                    self.finish()?;
+                    return self.f.write_str(&s[i..]);
+                }
+                None => {
+                    self.matched = None;
+                    return self.f.write_str(&s[i..]);
+                }
+            }
+        }
+        self.matched = if prefix.next().is_some() {
+            Some(matched)
+        } else {
+            None
+        };
+        Ok(())
+    }
+}
+
+impl Serialize for Uri<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 pub(crate) fn de_any_uri<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -230,17 +425,17 @@ where
 
             let id = parts.next().ok_or_else(|| E::missing_field("id"))?;
 
-            let id = if let Some(val) = parts.next() {
-                if item_type != "user" || val != "playlist" {
-                    return Err(E::unknown_field(val, &[]));
-                }
+            let id = match (item_type, parts.next()) {
+                (_, None) => id,
                 // Old-style playlist ids:
                 // spotify:user:{name}:playlist:{id}) instead of spotify:playlist:{id}.
-                parts
+                ("user", Some("playlist")) => parts
                     .next()
-                    .ok_or_else(|| E::missing_field("playlist id"))?
-            } else {
-                id
+                    .ok_or_else(|| E::missing_field("playlist id"))?,
+                // A user's saved tracks, whose ID is the user's ID:
+                // spotify:user:{name}:collection.
+                ("user", Some("collection")) if parts.next().is_none() => id,
+                (_, Some(val)) => return Err(E::unknown_field(val, &[])),
             };
 
             Ok(id.to_owned())
@@ -293,3 +488,50 @@ where
 
     Ok(Option::deserialize(deserializer)?.map(|Wrapper(val)| val))
 }
+
+/// Get the paths of the non-null fields in `original` that aren't in `reserialized`, which is
+/// `original` after being parsed and serialized again.
+#[cfg(feature = "client")]
+pub(crate) fn dropped_fields(
+    original: &serde_json::Value,
+    reserialized: &serde_json::Value,
+) -> Vec<String> {
+    fn find(
+        original: &serde_json::Value,
+        reserialized: &serde_json::Value,
+        path: &mut String,
+        dropped: &mut Vec<String>,
+    ) {
+        use serde_json::Value;
+
+        let len = path.len();
+        match (original, reserialized) {
+            (Value::Object(original), Value::Object(reserialized)) => {
+                for (key, value) in original {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    match reserialized.get(key) {
+                        Some(new) => find(value, new, path, dropped),
+                        None if !value.is_null() => dropped.push(path.clone()),
+                        None => {}
+                    }
+                    path.truncate(len);
+                }
+            }
+            (Value::Array(original), Value::Array(reserialized)) => {
+                for (i, (value, new)) in original.iter().zip(reserialized).enumerate() {
+                    let _ = write!(path, "[{}]", i);
+                    find(value, new, path, dropped);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut dropped = Vec::new();
+    find(original, reserialized, &mut String::new(), &mut dropped);
+    dropped
+}